@@ -0,0 +1,25 @@
+use rsyslog::{
+    parser::{msg::MultilineRaw, StructuredData},
+    Message,
+};
+
+type TraceMessage<'a> = Message<'a, Option<&'a str>, Vec<StructuredData<'a>>, MultilineRaw<'a>>;
+
+fn main() -> Result<(), String> {
+    let msg = r#"<11>1 2016-02-21T04:32:57+00:00 web1 someservice - - - java.lang.IllegalStateException: boom
+	at com.foo.Bar.baz(Bar.java:42)
+Caused by: java.io.IOException: closed
+	at com.foo.Io.read(Io.java:7)
+<14>1 2016-02-21T04:32:58+00:00 web1 someservice - - - recovered"#;
+
+    for message in TraceMessage::iter(msg) {
+        let message = message.map_err(|e| e.to_string())?;
+        println!(
+            "{} lines: {:?}",
+            message.msg.msg.lines().count(),
+            message.msg.msg
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,126 @@
+//! Derive macros for rsyslog's `ParseMsg` and `ParsePart` traits.
+//!
+//! `#[derive(ParseMsg)]` on a struct with named fields parses a logfmt-like
+//! line of `key=value` pairs (values may be double quoted):
+//!
+//! ```ignore
+//! #[derive(ParseMsg)]
+//! struct Request<'a> {
+//!     method: &'a str,
+//!     #[rsyslog(key = "service", suffix = "ms")]
+//!     service_ms: u32,
+//!     bytes: Option<u64>,
+//!     #[rsyslog(extra)]
+//!     extra: Vec<(&'a str, &'a str)>,
+//! }
+//! ```
+//!
+//! Every field type must implement `rsyslog::ParseValue`. Keys default to the
+//! field name, `Option` fields may be missing or empty, and a single
+//! `Vec<(&str, &str)>` field marked `extra` collects the unknown keys (which
+//! are otherwise ignored).
+//!
+//! `#[derive(ParseMsg)]` on an enum of single field tuple variants dispatches
+//! on the `Originator`:
+//!
+//! ```ignore
+//! #[derive(ParseMsg)]
+//! enum Drain<'a> {
+//!     #[rsyslog(when(app_name = "heroku", proc_id = "router"))]
+//!     Router(HerokuRouter<'a>),
+//!     Other(LineRaw<'a>),
+//! }
+//! ```
+//!
+//! Variants are tried in order, skipping those whose `when` conditions do not
+//! all match, and the first one that parses wins. `From` impls are generated
+//! for each variant's type.
+//!
+//! `#[derive(ParsePart)]` on an enum of single field tuple variants tries each
+//! variant's `ParsePart` impl in order.
+
+mod parse_msg;
+mod parse_part;
+
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput};
+
+#[proc_macro_derive(ParseMsg, attributes(rsyslog))]
+pub fn derive_parse_msg(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    parse_msg::expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+#[proc_macro_derive(ParsePart, attributes(rsyslog))]
+pub fn derive_parse_part(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    parse_part::expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// The impl generics for `input`, with the lifetime the trait is implemented
+/// for: the first lifetime parameter, or a new one if there is none.
+fn impl_generics(input: &DeriveInput) -> (syn::Generics, syn::Lifetime) {
+    let mut generics = input.generics.clone();
+    let lifetime = match generics.lifetimes().next() {
+        Some(param) => param.lifetime.clone(),
+        None => {
+            let lifetime = syn::Lifetime::new("'rsyslog", proc_macro2::Span::call_site());
+            generics
+                .params
+                .insert(0, syn::LifetimeParam::new(lifetime.clone()).into());
+            lifetime
+        }
+    };
+
+    (generics, lifetime)
+}
+
+/// The single field type of a `Variant(Type)` enum variant.
+fn variant_type(variant: &syn::Variant) -> syn::Result<&syn::Type> {
+    match &variant.fields {
+        syn::Fields::Unnamed(fields) if fields.unnamed.len() == 1 => Ok(&fields.unnamed[0].ty),
+        _ => Err(syn::Error::new_spanned(
+            variant,
+            "rsyslog: variants must have exactly one unnamed field",
+        )),
+    }
+}
+
+/// `From<Type>` impls for every variant, skipping types used by more than one
+/// variant since those would conflict.
+fn from_impls(input: &DeriveInput, data: &syn::DataEnum) -> syn::Result<proc_macro2::TokenStream> {
+    use quote::{quote, ToTokens};
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let types = data
+        .variants
+        .iter()
+        .map(|variant| variant_type(variant).map(|ty| ty.to_token_stream().to_string()))
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    let mut impls = proc_macro2::TokenStream::new();
+    for (variant, ty_name) in data.variants.iter().zip(&types) {
+        if types.iter().filter(|other| *other == ty_name).count() > 1 {
+            continue;
+        }
+        let ident = &variant.ident;
+        let ty = variant_type(variant)?;
+        impls.extend(quote! {
+            impl #impl_generics ::core::convert::From<#ty> for #name #ty_generics #where_clause {
+                fn from(msg: #ty) -> Self {
+                    Self::#ident(msg)
+                }
+            }
+        });
+    }
+
+    Ok(impls)
+}
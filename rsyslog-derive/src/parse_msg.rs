@@ -0,0 +1,213 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{Data, DeriveInput, Fields, LitStr};
+
+pub(crate) fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    match &input.data {
+        Data::Struct(data) => expand_struct(&input, &data.fields),
+        Data::Enum(data) => expand_enum(&input, data),
+        Data::Union(_) => Err(syn::Error::new_spanned(
+            &input,
+            "rsyslog: ParseMsg cannot be derived for unions",
+        )),
+    }
+}
+
+#[derive(Default)]
+struct FieldAttrs {
+    key: Option<LitStr>,
+    suffix: Option<LitStr>,
+    extra: bool,
+}
+
+impl FieldAttrs {
+    fn from_field(field: &syn::Field) -> syn::Result<Self> {
+        let mut attrs = Self::default();
+        for attr in field.attrs.iter().filter(|a| a.path().is_ident("rsyslog")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("key") {
+                    attrs.key = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("suffix") {
+                    attrs.suffix = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("extra") {
+                    attrs.extra = true;
+                } else {
+                    return Err(meta.error("rsyslog: expected `key`, `suffix` or `extra`"));
+                }
+                Ok(())
+            })?;
+        }
+
+        Ok(attrs)
+    }
+}
+
+fn expand_struct(input: &DeriveInput, fields: &Fields) -> syn::Result<TokenStream> {
+    let fields = match fields {
+        Fields::Named(fields) => &fields.named,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                input,
+                "rsyslog: ParseMsg can only be derived for structs with named fields",
+            ))
+        }
+    };
+
+    let name = &input.ident;
+    let context = name.to_string();
+    let (generics, lt) = super::impl_generics(input);
+    let (impl_generics, _, _) = generics.split_for_impl();
+    let (_, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let mut slots = Vec::new();
+    let mut arms = Vec::new();
+    let mut inits = Vec::new();
+    let mut extra = None;
+    for field in fields {
+        let ident = field.ident.as_ref().expect("named field");
+        let attrs = FieldAttrs::from_field(field)?;
+
+        if attrs.extra {
+            if extra.is_some() {
+                return Err(syn::Error::new_spanned(
+                    field,
+                    "rsyslog: only one field can be marked `extra`",
+                ));
+            }
+            extra = Some(ident);
+            inits.push(quote!(#ident: __extra));
+            continue;
+        }
+
+        let slot = format_ident!("__field_{}", ident);
+        let key = attrs
+            .key
+            .unwrap_or_else(|| LitStr::new(&ident.to_string(), ident.span()));
+        let suffix = match attrs.suffix {
+            Some(suffix) => quote!(::core::option::Option::Some(#suffix)),
+            None => quote!(::core::option::Option::None),
+        };
+
+        slots.push(quote! {
+            let mut #slot: ::core::option::Option<&#lt str> = ::core::option::Option::None;
+        });
+        arms.push(quote! {
+            #key => #slot = ::core::option::Option::Some(value),
+        });
+        inits.push(quote! {
+            #ident: ::rsyslog::parser::helpers::parse_field(#context, #key, #slot, #suffix)?
+        });
+    }
+
+    let fallback = match extra {
+        Some(_) => quote!(_ => __extra.push((key, value)),),
+        None => quote!(_ => {}),
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::rsyslog::ParseMsg<#lt> for #name #ty_generics #where_clause {
+            fn parse(
+                msg: &#lt str,
+                _: &::rsyslog::Originator<#lt>,
+            ) -> ::core::result::Result<(&#lt str, Self), ::rsyslog::Error<#lt>> {
+                let (rem, line) = ::rsyslog::parser::helpers::split_line(msg);
+
+                #(#slots)*
+                #[allow(unused_mut)]
+                let mut __extra: ::std::vec::Vec<(&#lt str, &#lt str)> = ::std::vec::Vec::new();
+                for (key, value) in ::rsyslog::parser::helpers::parse_pairs(line, #context)? {
+                    match key {
+                        #(#arms)*
+                        #fallback
+                    }
+                }
+
+                ::core::result::Result::Ok((rem, Self { #(#inits),* }))
+            }
+        }
+    })
+}
+
+#[derive(Default)]
+struct VariantAttrs {
+    when: Vec<(syn::Ident, LitStr)>,
+}
+
+impl VariantAttrs {
+    const KEYS: [&'static str; 4] = ["hostname", "app_name", "proc_id", "msg_id"];
+
+    fn from_variant(variant: &syn::Variant) -> syn::Result<Self> {
+        let mut attrs = Self::default();
+        for attr in variant
+            .attrs
+            .iter()
+            .filter(|a| a.path().is_ident("rsyslog"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if !meta.path.is_ident("when") {
+                    return Err(meta.error("rsyslog: expected `when(...)`"));
+                }
+                meta.parse_nested_meta(|meta| {
+                    match meta.path.get_ident() {
+                        Some(ident) if Self::KEYS.iter().any(|key| ident == key) => {
+                            attrs.when.push((ident.clone(), meta.value()?.parse()?));
+                        }
+                        _ => {
+                            return Err(meta.error(
+                                "rsyslog: expected `hostname`, `app_name`, `proc_id` or `msg_id`",
+                            ))
+                        }
+                    }
+                    Ok(())
+                })
+            })?;
+        }
+
+        Ok(attrs)
+    }
+}
+
+fn expand_enum(input: &DeriveInput, data: &syn::DataEnum) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+    let (generics, lt) = super::impl_generics(input);
+    let (impl_generics, _, _) = generics.split_for_impl();
+    let (_, ty_generics, where_clause) = input.generics.split_for_impl();
+    let no_match = format!("{}: no variant matches the originator", name);
+
+    let mut attempts = Vec::new();
+    for variant in &data.variants {
+        let ident = &variant.ident;
+        let ty = super::variant_type(variant)?;
+        let conditions = VariantAttrs::from_variant(variant)?
+            .when
+            .into_iter()
+            .map(|(key, value)| quote!(&& originator.#key == ::core::option::Option::Some(#value)));
+
+        attempts.push(quote! {
+            if res.is_err() #(#conditions)* {
+                res = <#ty as ::rsyslog::ParseMsg<#lt>>::parse(msg, originator)
+                    .map(|(rem, msg)| (rem, Self::#ident(msg)));
+            }
+        });
+    }
+
+    let from_impls = super::from_impls(input, data)?;
+
+    Ok(quote! {
+        impl #impl_generics ::rsyslog::ParseMsg<#lt> for #name #ty_generics #where_clause {
+            fn parse(
+                msg: &#lt str,
+                originator: &::rsyslog::Originator<#lt>,
+            ) -> ::core::result::Result<(&#lt str, Self), ::rsyslog::Error<#lt>> {
+                let mut res = ::core::result::Result::Err(
+                    ::rsyslog::Error::Custom(::std::string::String::from(#no_match)),
+                );
+                #(#attempts)*
+
+                res
+            }
+        }
+
+        #from_impls
+    })
+}
@@ -0,0 +1,52 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput};
+
+pub(crate) fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    let data = match &input.data {
+        Data::Enum(data) if !data.variants.is_empty() => data,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input,
+                "rsyslog: ParsePart can only be derived for non-empty enums",
+            ))
+        }
+    };
+
+    let name = &input.ident;
+    let (generics, lt) = super::impl_generics(&input);
+    let (impl_generics, _, _) = generics.split_for_impl();
+    let (_, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let mut attempts = data
+        .variants
+        .iter()
+        .map(|variant| -> syn::Result<TokenStream> {
+            let ident = &variant.ident;
+            let ty = super::variant_type(variant)?;
+
+            Ok(quote! {
+                <#ty as ::rsyslog::ParsePart<#lt>>::parse(part)
+                    .map(|(rem, part)| (rem, Self::#ident(part)))
+            })
+        });
+    let first = attempts.next().expect("non-empty enum")?;
+    let body = attempts.try_fold(first, |body, attempt| {
+        let attempt = attempt?;
+        syn::Result::Ok(quote!(#body.or_else(|_| #attempt)))
+    })?;
+
+    let from_impls = super::from_impls(&input, data)?;
+
+    Ok(quote! {
+        impl #impl_generics ::rsyslog::ParsePart<#lt> for #name #ty_generics #where_clause {
+            fn parse(
+                part: &#lt str,
+            ) -> ::core::result::Result<(&#lt str, Self), ::rsyslog::Error<#lt>> {
+                #body
+            }
+        }
+
+        #from_impls
+    })
+}
@@ -0,0 +1,370 @@
+//! Conversion of parsed messages to [Apache Arrow](https://arrow.apache.org/)
+//! record batches and, with the `parquet` feature, Parquet files.
+
+use crate::{
+    parser::{datetime::UnixTimestamp, msg::HerokuRouter, StructuredData},
+    Message,
+};
+use arrow_array::{
+    builder::{
+        ArrayBuilder, ListBuilder, MapBuilder, StringBuilder, StringDictionaryBuilder,
+        StructBuilder, TimestampNanosecondBuilder, UInt16Builder, UInt32Builder, UInt64Builder,
+        UInt8Builder,
+    },
+    types::Int32Type,
+    ArrayRef, RecordBatch,
+};
+use arrow_schema::{ArrowError, DataType, Field, Fields, Schema};
+use std::sync::Arc;
+
+type DictionaryBuilder = StringDictionaryBuilder<Int32Type>;
+type ParamsBuilder = MapBuilder<StringBuilder, StringBuilder>;
+
+/// Columns a MSG type contributes to a record batch, after the header and SD
+/// columns.
+pub trait MsgColumns {
+    /// Builds the columns from the MSGs appended so far and resets them.
+    fn finish(&mut self) -> Vec<(Field, ArrayRef)>;
+}
+
+/// Appends one MSG to the [`MsgColumns`].
+pub trait AppendMsg<M>: MsgColumns {
+    fn append(&mut self, msg: &M);
+}
+
+/// MSG as a single `msg` string column, for [`Raw`](crate::parser::msg::Raw)
+/// and the other text MSG types.
+#[derive(Default)]
+pub struct MsgText {
+    msg: StringBuilder,
+}
+
+impl MsgColumns for MsgText {
+    fn finish(&mut self) -> Vec<(Field, ArrayRef)> {
+        vec![column("msg", false, Arc::new(self.msg.finish()))]
+    }
+}
+
+impl<M: AsRef<str>> AppendMsg<M> for MsgText {
+    fn append(&mut self, msg: &M) {
+        self.msg.append_value(msg.as_ref());
+    }
+}
+
+/// The fields of [`HerokuRouter`] as columns named after them, with `extra`
+/// left out.
+#[derive(Default)]
+pub struct HerokuRouterColumns {
+    at: DictionaryBuilder,
+    code: DictionaryBuilder,
+    desc: DictionaryBuilder,
+    method: DictionaryBuilder,
+    path: StringBuilder,
+    host: DictionaryBuilder,
+    request_id: StringBuilder,
+    fwd: StringBuilder,
+    dyno: DictionaryBuilder,
+    connect: UInt32Builder,
+    service: UInt32Builder,
+    status: UInt16Builder,
+    bytes: UInt64Builder,
+    protocol: DictionaryBuilder,
+}
+
+impl MsgColumns for HerokuRouterColumns {
+    fn finish(&mut self) -> Vec<(Field, ArrayRef)> {
+        vec![
+            column("at", false, Arc::new(self.at.finish())),
+            column("code", true, Arc::new(self.code.finish())),
+            column("desc", true, Arc::new(self.desc.finish())),
+            column("method", false, Arc::new(self.method.finish())),
+            column("path", false, Arc::new(self.path.finish())),
+            column("host", false, Arc::new(self.host.finish())),
+            column("request_id", false, Arc::new(self.request_id.finish())),
+            column("fwd", false, Arc::new(self.fwd.finish())),
+            column("dyno", false, Arc::new(self.dyno.finish())),
+            column("connect", false, Arc::new(self.connect.finish())),
+            column("service", false, Arc::new(self.service.finish())),
+            column("status", false, Arc::new(self.status.finish())),
+            column("bytes", true, Arc::new(self.bytes.finish())),
+            column("protocol", false, Arc::new(self.protocol.finish())),
+        ]
+    }
+}
+
+impl AppendMsg<HerokuRouter<'_>> for HerokuRouterColumns {
+    fn append(&mut self, msg: &HerokuRouter<'_>) {
+        self.at.append_value(msg.at);
+        self.code.append_option(msg.code.map(|code| code.code()));
+        self.desc.append_option(msg.desc.as_deref());
+        self.method.append_value(msg.method);
+        self.path.append_value(&msg.path);
+        self.host.append_value(msg.host);
+        self.request_id.append_value(msg.request_id);
+        self.fwd.append_value(msg.fwd);
+        self.dyno.append_value(msg.dyno);
+        self.connect.append_value(msg.connect);
+        self.service.append_value(msg.service);
+        self.status.append_value(msg.status);
+        self.bytes.append_option(msg.bytes);
+        self.protocol.append_value(msg.protocol);
+    }
+}
+
+/// Appends messages into Arrow arrays and builds them into a [`RecordBatch`]
+/// with the columns:
+///
+/// - `facility`, `severity` and `version` as `UInt8`,
+/// - `timestamp` as nanoseconds since the epoch in UTC, null when the
+///   timestamp can't be converted (see [`UnixTimestamp`]),
+/// - `hostname`, `app_name`, `proc_id` and `msg_id` as dictionary encoded
+///   strings,
+/// - `structured_data` as `list<struct<id, params: map<string, string>>>`,
+/// - the columns of `C` for MSG, a single `msg` column by default.
+pub struct RecordBatchBuilder<C = MsgText> {
+    facility: UInt8Builder,
+    severity: UInt8Builder,
+    version: UInt8Builder,
+    timestamp: TimestampNanosecondBuilder,
+    hostname: DictionaryBuilder,
+    app_name: DictionaryBuilder,
+    proc_id: DictionaryBuilder,
+    msg_id: DictionaryBuilder,
+    structured_data: ListBuilder<StructBuilder>,
+    msg: C,
+}
+
+impl RecordBatchBuilder {
+    pub fn new() -> Self {
+        Self::with_msg_columns(MsgText::default())
+    }
+}
+
+impl Default for RecordBatchBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C: MsgColumns> RecordBatchBuilder<C> {
+    /// A builder that lays out MSG with `msg`, e.g. [`HerokuRouterColumns`].
+    pub fn with_msg_columns(msg: C) -> Self {
+        let params = ParamsBuilder::new(None, StringBuilder::new(), StringBuilder::new());
+        let sd = StructBuilder::new(
+            vec![
+                Field::new("id", DataType::Utf8, false),
+                Field::new("params", params_type(), false),
+            ],
+            vec![Box::new(StringBuilder::new()), Box::new(params)],
+        );
+
+        Self {
+            facility: UInt8Builder::new(),
+            severity: UInt8Builder::new(),
+            version: UInt8Builder::new(),
+            timestamp: TimestampNanosecondBuilder::new().with_timezone("UTC"),
+            hostname: DictionaryBuilder::new(),
+            app_name: DictionaryBuilder::new(),
+            proc_id: DictionaryBuilder::new(),
+            msg_id: DictionaryBuilder::new(),
+            structured_data: ListBuilder::new(sd),
+            msg,
+        }
+    }
+
+    /// The number of messages appended since the last [`finish`](Self::finish).
+    pub fn len(&self) -> usize {
+        self.facility.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn append<T, M>(&mut self, message: &Message<'_, T, Vec<StructuredData<'_>>, M>)
+    where
+        T: UnixTimestamp,
+        C: AppendMsg<M>,
+    {
+        self.facility.append_value(message.facility);
+        self.severity.append_value(message.severity);
+        self.version.append_value(message.version);
+        self.timestamp.append_option(
+            message
+                .timestamp
+                .unix_nanos()
+                .and_then(|nanos| i64::try_from(nanos).ok()),
+        );
+        self.hostname.append_option(message.hostname);
+        self.app_name.append_option(message.app_name);
+        self.proc_id.append_option(message.proc_id);
+        self.msg_id.append_option(message.msg_id);
+
+        let sds = self.structured_data.values();
+        for sd in &message.structured_data {
+            sds.field_builder::<StringBuilder>(0)
+                .expect("SD id builder")
+                .append_value(sd.id);
+            let params = sds
+                .field_builder::<ParamsBuilder>(1)
+                .expect("SD params builder");
+            for param in &sd.params {
+                params.keys().append_value(param.name);
+                params.values().append_value(&param.value);
+            }
+            params
+                .append(true)
+                .expect("SD params have as many keys as values");
+            sds.append(true);
+        }
+        self.structured_data.append(true);
+
+        self.msg.append(&message.msg);
+    }
+
+    /// Builds the messages appended so far into a batch and resets the builder.
+    pub fn finish(&mut self) -> Result<RecordBatch, ArrowError> {
+        let mut columns = vec![
+            column("facility", false, Arc::new(self.facility.finish())),
+            column("severity", false, Arc::new(self.severity.finish())),
+            column("version", false, Arc::new(self.version.finish())),
+            column("timestamp", true, Arc::new(self.timestamp.finish())),
+            column("hostname", true, Arc::new(self.hostname.finish())),
+            column("app_name", true, Arc::new(self.app_name.finish())),
+            column("proc_id", true, Arc::new(self.proc_id.finish())),
+            column("msg_id", true, Arc::new(self.msg_id.finish())),
+            column(
+                "structured_data",
+                false,
+                Arc::new(self.structured_data.finish()),
+            ),
+        ];
+        columns.extend(self.msg.finish());
+
+        let (fields, arrays): (Vec<_>, Vec<_>) = columns.into_iter().unzip();
+        RecordBatch::try_new(Arc::new(Schema::new(fields)), arrays)
+    }
+}
+
+fn column(name: &str, nullable: bool, array: ArrayRef) -> (Field, ArrayRef) {
+    (Field::new(name, array.data_type().clone(), nullable), array)
+}
+
+/// The type `MapBuilder` gives SD params with its default field names.
+fn params_type() -> DataType {
+    let entries = Fields::from(vec![
+        Field::new("keys", DataType::Utf8, false),
+        Field::new("values", DataType::Utf8, true),
+    ]);
+    DataType::Map(
+        Arc::new(Field::new("entries", DataType::Struct(entries), false)),
+        false,
+    )
+}
+
+/// Writes `batches` to `writer` as one Parquet file, with the default
+/// writer properties. There must be at least one batch, and all of them must
+/// have the same schema.
+#[cfg(feature = "parquet")]
+pub fn write_parquet<W, I>(writer: W, batches: I) -> Result<(), parquet::errors::ParquetError>
+where
+    W: std::io::Write + Send,
+    I: IntoIterator<Item = RecordBatch>,
+{
+    let mut batches = batches.into_iter().peekable();
+    let schema = batches.peek().map(RecordBatch::schema).ok_or_else(|| {
+        parquet::errors::ParquetError::General("parquet: no record batch to write".into())
+    })?;
+
+    let mut writer = parquet::arrow::ArrowWriter::try_new(writer, schema, None)?;
+    for batch in batches {
+        writer.write(&batch)?;
+    }
+    writer.close().map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{msg::Raw, SdParam};
+    use arrow_array::{Array, DictionaryArray, ListArray, MapArray, StringArray, StructArray};
+
+    fn message<'a>(
+        structured_data: Vec<StructuredData<'a>>,
+        msg: &'a str,
+    ) -> Message<'a, Option<&'a str>, Vec<StructuredData<'a>>, Raw<'a>> {
+        Message {
+            facility: 4,
+            severity: 2,
+            version: 1,
+            timestamp: Some("2003-10-11T22:14:15.003Z"),
+            hostname: Some("mymachine.example.com"),
+            app_name: Some("su"),
+            proc_id: None,
+            msg_id: Some("ID47"),
+            structured_data,
+            msg: msg.into(),
+        }
+    }
+
+    #[test]
+    fn structured_data_column() {
+        let sd = StructuredData {
+            id: "exampleSDID@32473",
+            params: vec![
+                SdParam {
+                    name: "iut",
+                    value: "3".into(),
+                },
+                SdParam {
+                    name: "eventSource",
+                    value: "Application".into(),
+                },
+            ],
+        };
+        let mut builder = RecordBatchBuilder::new();
+        builder.append(&message(vec![sd], "first"));
+        builder.append(&message(vec![], "second"));
+
+        let batch = builder.finish().expect("building batch");
+        let sds = batch
+            .column_by_name("structured_data")
+            .and_then(|c| c.as_any().downcast_ref::<ListArray>())
+            .expect("SD column");
+
+        assert_eq!(sds.value_length(0), 1);
+        assert_eq!(sds.value_length(1), 0);
+        let first = sds.value(0);
+        let first = first
+            .as_any()
+            .downcast_ref::<StructArray>()
+            .expect("SD struct");
+        let ids = first.column(0).as_any().downcast_ref::<StringArray>();
+        assert_eq!(ids.map(|ids| ids.value(0)), Some("exampleSDID@32473"));
+        let params = first
+            .column(1)
+            .as_any()
+            .downcast_ref::<MapArray>()
+            .expect("SD params");
+        assert_eq!(params.value_length(0), 2);
+        let values = params.values().as_any().downcast_ref::<StringArray>();
+        assert_eq!(values.map(|values| values.value(1)), Some("Application"));
+    }
+
+    #[test]
+    fn dictionary_columns() {
+        let mut builder = RecordBatchBuilder::new();
+        builder.append(&message(vec![], "first"));
+        builder.append(&message(vec![], "second"));
+
+        let batch = builder.finish().expect("building batch");
+        let hostnames = batch
+            .column_by_name("hostname")
+            .and_then(|c| c.as_any().downcast_ref::<DictionaryArray<Int32Type>>())
+            .expect("hostname column");
+
+        assert_eq!(hostnames.len(), 2);
+        assert_eq!(hostnames.values().len(), 1);
+        assert!(builder.is_empty());
+    }
+}
@@ -0,0 +1,219 @@
+//! A tokio codec of syslog streams, for `FramedRead` and `FramedWrite`:
+//! `FramedRead::new(tcp, SyslogCodec::default())`.
+
+use crate::{
+    format::FormatPart,
+    server::{framing::FrameDecoder, Framing},
+    Error, Message, ParseMsg, ParsePart,
+};
+use bytes::{Buf, BufMut, BytesMut};
+use std::{fmt::Write, io};
+use tokio::io::AsyncRead;
+use tokio_util::codec::{Decoder, Encoder, FramedRead};
+
+/// A frame decoded by [`SyslogCodec`], owning its text.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Frame {
+    pub text: String,
+}
+
+impl Frame {
+    pub fn parse<'a, T, S, M>(&'a self) -> Result<Message<'a, T, S, M>, Error<'a>>
+    where
+        T: ParsePart<'a>,
+        S: ParsePart<'a>,
+        M: ParseMsg<'a>,
+    {
+        Message::parse(&self.text)
+    }
+}
+
+/// Decodes syslog streams into [`Frame`]s and encodes messages as RFC 5424.
+///
+/// Decoding detects the framing as
+/// [`FrameReader`](crate::server::FrameReader) does, and like it yields
+/// oversized frames and invalid octet counts as errors after which decoding
+/// goes on. Encoding uses octet counting unless told otherwise.
+#[derive(Debug)]
+pub struct SyslogCodec {
+    decoder: FrameDecoder,
+    framing: Framing,
+}
+
+impl Default for SyslogCodec {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SyslogCodec {
+    pub fn new() -> Self {
+        Self {
+            decoder: FrameDecoder::new(crate::server::DEFAULT_MAX_MESSAGE_SIZE),
+            framing: Framing::OctetCounting,
+        }
+    }
+
+    /// The longest frame decoded, defaults to 8192 bytes.
+    pub fn max_frame_size(mut self, size: usize) -> Self {
+        self.decoder = FrameDecoder::new(size);
+        self
+    }
+
+    /// The framing of encoded messages, defaults to octet counting.
+    pub fn framing(mut self, framing: Framing) -> Self {
+        self.framing = framing;
+        self
+    }
+
+    fn decode_frame(
+        &mut self,
+        buf: &mut BytesMut,
+        eof: bool,
+    ) -> Option<Result<Frame, Error<'static>>> {
+        loop {
+            let (consumed, frame) = self.decoder.decode(&buf[..], eof);
+            let frame = frame.map(|frame| {
+                frame.map(|range| Frame {
+                    text: String::from_utf8_lossy(&buf[range]).into_owned(),
+                })
+            });
+            buf.advance(consumed);
+
+            match frame {
+                Some(frame) => return Some(frame),
+                None if consumed > 0 => continue,
+                None => return None,
+            }
+        }
+    }
+}
+
+impl Decoder for SyslogCodec {
+    type Item = Result<Frame, Error<'static>>;
+    type Error = io::Error;
+
+    fn decode(&mut self, buf: &mut BytesMut) -> io::Result<Option<Self::Item>> {
+        Ok(self.decode_frame(buf, false))
+    }
+
+    fn decode_eof(&mut self, buf: &mut BytesMut) -> io::Result<Option<Self::Item>> {
+        Ok(self.decode_frame(buf, true))
+    }
+}
+
+impl<T, S, M> Encoder<&Message<'_, T, S, M>> for SyslogCodec
+where
+    T: FormatPart,
+    S: FormatPart,
+    M: AsRef<str>,
+{
+    type Error = io::Error;
+
+    fn encode(&mut self, message: &Message<'_, T, S, M>, buf: &mut BytesMut) -> io::Result<()> {
+        let text = message.to_string();
+        match self.framing {
+            Framing::OctetCounting => {
+                write!(buf, "{} ", text.len()).map_err(io::Error::other)?;
+                buf.put_slice(text.as_bytes());
+            }
+            Framing::NonTransparent => {
+                // A newline would end the frame early.
+                buf.put_slice(text.replace('\n', " ").as_bytes());
+                buf.put_u8(b'\n');
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<T, S, M> Encoder<Message<'_, T, S, M>> for SyslogCodec
+where
+    T: FormatPart,
+    S: FormatPart,
+    M: AsRef<str>,
+{
+    type Error = io::Error;
+
+    fn encode(&mut self, message: Message<'_, T, S, M>, buf: &mut BytesMut) -> io::Result<()> {
+        self.encode(&message, buf)
+    }
+}
+
+/// A stream of the frames of `reader`, decoded with the default
+/// [`SyslogCodec`].
+pub fn frames<R: AsyncRead>(reader: R) -> FramedRead<R, SyslogCodec> {
+    FramedRead::new(reader, SyslogCodec::default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::msg::Raw;
+
+    fn decode_all(input: &[u8]) -> Vec<Result<String, Error<'static>>> {
+        let mut codec = SyslogCodec::new().max_frame_size(16);
+        let mut buf = BytesMut::new();
+        let mut frames = Vec::new();
+        // Feed a byte at a time to exercise partial frames.
+        for byte in input {
+            buf.put_u8(*byte);
+            while let Some(frame) = codec.decode(&mut buf).expect("decoding") {
+                frames.push(frame.map(|frame| frame.text));
+            }
+        }
+        while let Some(frame) = codec.decode_eof(&mut buf).expect("decoding") {
+            frames.push(frame.map(|frame| frame.text));
+        }
+        frames
+    }
+
+    #[test]
+    fn decode() {
+        assert_eq!(
+            decode_all(b"5 <1>1 3 abc20 0123456789abcdefghij2 de"),
+            vec![
+                Ok("<1>1 ".into()),
+                Ok("abc".into()),
+                Err(Error::Custom(
+                    "framing: frame of 20 bytes exceeds the maximum of 16".into()
+                )),
+                Ok("de".into())
+            ]
+        );
+        assert_eq!(
+            decode_all(b"<1>1 a\n<1>1 b"),
+            vec![Ok("<1>1 a".into()), Ok("<1>1 b".into())]
+        );
+    }
+
+    #[test]
+    fn encode() {
+        let message: Message = Message {
+            facility: 4,
+            severity: 2,
+            version: 1,
+            timestamp: None,
+            hostname: Some("host"),
+            app_name: Some("app"),
+            proc_id: None,
+            msg_id: None,
+            structured_data: vec![],
+            msg: Raw::from("one\ntwo"),
+        };
+        let mut buf = BytesMut::new();
+
+        SyslogCodec::new()
+            .encode(&message, &mut buf)
+            .expect("encoding");
+        SyslogCodec::new()
+            .framing(Framing::NonTransparent)
+            .encode(message, &mut buf)
+            .expect("encoding");
+
+        assert_eq!(
+            &buf[..],
+            &b"30 <34>1 - host app - - - one\ntwo<34>1 - host app - - - one two\n"[..]
+        );
+    }
+}
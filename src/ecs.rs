@@ -0,0 +1,163 @@
+//! Mapping of parsed messages to the [Elastic Common Schema](https://www.elastic.co/guide/en/ecs/current/index.html).
+
+use crate::{
+    parser::{
+        datetime::{self, UnixTimestamp},
+        structured_data::NestedStructuredData,
+        StructuredData,
+    },
+    Message,
+};
+use serde::{Serialize, Serializer};
+
+/// The ECS version the documents follow.
+const ECS_VERSION: &str = "8.11.0";
+
+/// Serializes a message as an ECS document:
+///
+/// - the timestamp as `@timestamp`, normalized to UTC,
+/// - MSG as `message`, and the raw message as `event.original` when given
+///   with [`Ecs::original`],
+/// - facility and severity as `log.syslog.facility.code/name` and
+///   `log.syslog.severity.code/name`, with the severity name as `log.level`,
+/// - the header fields as `log.syslog.*`, and also as `host.hostname`,
+///   `process.name` and `process.pid` (when PROCID is a number),
+/// - SD as `log.syslog.structured_data.<id>.<name>`.
+///
+/// Built by [`Message::to_ecs`].
+pub struct Ecs<'m, 'a, T, M> {
+    message: &'m Message<'a, T, Vec<StructuredData<'a>>, M>,
+    original: Option<&'m str>,
+}
+
+impl<'m, 'a, T, M> Ecs<'m, 'a, T, M> {
+    pub fn new(message: &'m Message<'a, T, Vec<StructuredData<'a>>, M>) -> Self {
+        Self {
+            message,
+            original: None,
+        }
+    }
+
+    /// Sets `event.original`, the message as it was received.
+    pub fn original(mut self, original: &'m str) -> Self {
+        self.original = Some(original);
+        self
+    }
+}
+
+#[derive(Serialize)]
+struct Document<'m> {
+    #[serde(rename = "@timestamp", skip_serializing_if = "Option::is_none")]
+    timestamp: Option<String>,
+    message: &'m str,
+    log: Log<'m>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    host: Option<Host<'m>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    process: Option<Process<'m>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    event: Option<Event<'m>>,
+    ecs: Version,
+}
+
+#[derive(Serialize)]
+struct Log<'m> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    level: Option<&'static str>,
+    syslog: Syslog<'m>,
+}
+
+#[derive(Serialize)]
+struct Syslog<'m> {
+    facility: Code,
+    severity: Code,
+    priority: u16,
+    version: u8,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hostname: Option<&'m str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    appname: Option<&'m str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    procid: Option<&'m str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    msgid: Option<&'m str>,
+    #[serde(skip_serializing_if = "NestedStructuredData::is_empty")]
+    structured_data: NestedStructuredData<'m, 'm>,
+}
+
+#[derive(Serialize)]
+struct Code {
+    code: u8,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<&'static str>,
+}
+
+#[derive(Serialize)]
+struct Host<'m> {
+    hostname: &'m str,
+}
+
+#[derive(Serialize)]
+struct Process<'m> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<&'m str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pid: Option<u32>,
+}
+
+#[derive(Serialize)]
+struct Event<'m> {
+    original: &'m str,
+}
+
+#[derive(Serialize)]
+struct Version {
+    version: &'static str,
+}
+
+impl<T, M> Serialize for Ecs<'_, '_, T, M>
+where
+    T: UnixTimestamp,
+    M: AsRef<str>,
+{
+    fn serialize<Z: Serializer>(&self, serializer: Z) -> Result<Z::Ok, Z::Error> {
+        let message = self.message;
+        let pid = message.proc_id.and_then(|pid| pid.parse().ok());
+
+        let document = Document {
+            timestamp: message.timestamp.unix_nanos().map(datetime::format_rfc3339),
+            message: message.msg.as_ref(),
+            log: Log {
+                level: message.severity_name(),
+                syslog: Syslog {
+                    facility: Code {
+                        code: message.facility,
+                        name: message.facility_name(),
+                    },
+                    severity: Code {
+                        code: message.severity,
+                        name: message.severity_name(),
+                    },
+                    priority: u16::from(message.facility) * 8 + u16::from(message.severity),
+                    version: message.version,
+                    hostname: message.hostname,
+                    appname: message.app_name,
+                    procid: message.proc_id,
+                    msgid: message.msg_id,
+                    structured_data: NestedStructuredData(&message.structured_data),
+                },
+            },
+            host: message.hostname.map(|hostname| Host { hostname }),
+            process: (message.app_name.is_some() || pid.is_some()).then_some(Process {
+                name: message.app_name,
+                pid,
+            }),
+            event: self.original.map(|original| Event { original }),
+            ecs: Version {
+                version: ECS_VERSION,
+            },
+        };
+
+        document.serialize(serializer)
+    }
+}
@@ -0,0 +1,288 @@
+//! Formatting of messages as RFC 5424, the inverse of parsing. A message is
+//! formatted through its `Display` implementation, as long as its TIMESTAMP
+//! and SD implement [`FormatPart`] and its MSG `AsRef<str>`:
+//! `<34>1 2003-10-11T22:14:15.003Z host app - ID47 - msg`.
+
+use crate::{
+    parser::{datetime, Skip, StructuredData},
+    Message,
+};
+use std::{borrow::Cow, fmt};
+
+/// A TIMESTAMP or SD that can be written back in RFC 5424 form.
+pub trait FormatPart {
+    /// Writes the part, `-` for a missing one.
+    fn format(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result;
+}
+
+/// Kept as written, bar the characters a header field can't hold.
+impl FormatPart for Option<&str> {
+    fn format(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&header_field(*self, usize::MAX))
+    }
+}
+
+/// Microseconds since the epoch, written in UTC.
+impl FormatPart for Option<u64> {
+    fn format(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Some(micros) => {
+                // RFC 5424 allows at most 6 digits of TIME-SECFRAC.
+                let mut timestamp = datetime::format_rfc3339(i128::from(*micros) * 1_000);
+                timestamp.replace_range(timestamp.len() - 4..timestamp.len() - 1, "");
+                f.write_str(&timestamp)
+            }
+            None => f.write_str("-"),
+        }
+    }
+}
+
+#[cfg(feature = "chrono-timestamp")]
+impl FormatPart for Option<crate::parser::DateTime> {
+    fn format(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Some(dt) => {
+                use chrono::{Datelike, Timelike};
+
+                write!(
+                    f,
+                    "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+                    dt.year(),
+                    dt.month(),
+                    dt.day(),
+                    dt.hour(),
+                    dt.minute(),
+                    dt.second()
+                )?;
+                // Nanoseconds are cut to the 6 digits RFC 5424 allows.
+                match dt.timestamp_subsec_micros() {
+                    0 => {}
+                    micros if micros % 1_000 == 0 => write!(f, ".{:03}", micros / 1_000)?,
+                    micros => write!(f, ".{:06}", micros)?,
+                }
+                match dt.offset().local_minus_utc() {
+                    0 => f.write_str("Z"),
+                    offset => write!(
+                        f,
+                        "{}{:02}:{:02}",
+                        if offset < 0 { '-' } else { '+' },
+                        offset.abs() / 3600,
+                        offset.abs() % 3600 / 60
+                    ),
+                }
+            }
+            None => f.write_str("-"),
+        }
+    }
+}
+
+impl FormatPart for Skip {
+    fn format(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("-")
+    }
+}
+
+impl FormatPart for Vec<Skip> {
+    fn format(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("-")
+    }
+}
+
+/// Param values are written as they are, escaped the way the parser leaves
+/// them; see [`escape_param_value`] for values built otherwise. SD-IDs and
+/// param names are written through [`sanitize_sd_name`].
+impl FormatPart for Vec<StructuredData<'_>> {
+    fn format(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return f.write_str("-");
+        }
+        for sd in self {
+            write!(f, "[{}", sanitize_sd_name(sd.id))?;
+            for param in &sd.params {
+                write!(f, " {}=\"{}\"", sanitize_sd_name(param.name), param.value)?;
+            }
+            f.write_str("]")?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes the message as RFC 5424, with version 1 whatever the message was
+/// parsed from. Missing header fields are written as `-`, and an empty MSG is
+/// left out along with the space before it.
+///
+/// Header fields are cut to the lengths RFC 5424 allows, and the characters
+/// they can't hold (spaces, control and non-ASCII characters) are replaced by
+/// `_`, so that a field never spills into the next one.
+impl<T, S, M> fmt::Display for Message<'_, T, S, M>
+where
+    T: FormatPart,
+    S: FormatPart,
+    M: AsRef<str>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "<{}>1 ",
+            u16::from(self.facility) * 8 + u16::from(self.severity)
+        )?;
+        self.timestamp.format(f)?;
+        write!(
+            f,
+            " {} {} {} {} ",
+            header_field(self.hostname, 255),
+            header_field(self.app_name, 48),
+            header_field(self.proc_id, 128),
+            header_field(self.msg_id, 32)
+        )?;
+        self.structured_data.format(f)?;
+
+        match self.msg.as_ref() {
+            "" => Ok(()),
+            msg => write!(f, " {}", msg),
+        }
+    }
+}
+
+/// Escapes `"`, `\` and `]` with a backslash, as RFC 5424 requires of
+/// SD-PARAM values.
+pub fn escape_param_value(value: &str) -> Cow<'_, str> {
+    if !value.contains(['"', '\\', ']']) {
+        return Cow::Borrowed(value);
+    }
+    let mut escaped = String::with_capacity(value.len() + 2);
+    for c in value.chars() {
+        if matches!(c, '"' | '\\' | ']') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    Cow::Owned(escaped)
+}
+
+/// Makes `name` a valid SD-ID or SD-NAME: at most 32 printable ASCII
+/// characters but `=`, `]` and `"`, others being replaced by `_`.
+pub fn sanitize_sd_name(name: &str) -> Cow<'_, str> {
+    match name {
+        "" => Cow::Borrowed("_"),
+        _ => sanitize(name, 32, |c| {
+            c.is_ascii_graphic() && !matches!(c, '=' | ']' | '"')
+        }),
+    }
+}
+
+/// A header field as written, `-` if missing or empty.
+fn header_field(field: Option<&str>, max: usize) -> Cow<'_, str> {
+    match field {
+        Some("") | None => Cow::Borrowed("-"),
+        Some(field) => sanitize(field, max, |c| c.is_ascii_graphic()),
+    }
+}
+
+/// Cuts `value` to `max` characters, replacing the invalid ones by `_`.
+pub(crate) fn sanitize(value: &str, max: usize, valid: fn(char) -> bool) -> Cow<'_, str> {
+    if value.len() <= max && value.chars().all(valid) {
+        return Cow::Borrowed(value);
+    }
+    Cow::Owned(
+        value
+            .chars()
+            .take(max)
+            .map(|c| if valid(c) { c } else { '_' })
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::msg::Raw;
+
+    #[test]
+    fn round_trip() {
+        let msg = r#"<165>1 2003-10-11T22:14:15.003Z mymachine.example.com evntslog - ID47 [exampleSDID@32473 iut="3" eventSource="Application"][meta x="1"] An application event"#;
+        let message: Message = Message::parse(msg).expect("parsing message");
+
+        assert_eq!(message.to_string(), msg);
+    }
+
+    #[test]
+    fn nil_fields() {
+        let message = Message {
+            facility: 1,
+            severity: 5,
+            version: 0,
+            timestamp: Some(1_456_029_177_853_123_u64),
+            hostname: None,
+            app_name: Some(""),
+            proc_id: None,
+            msg_id: None,
+            structured_data: Skip,
+            msg: Raw::from(""),
+        };
+
+        assert_eq!(
+            message.to_string(),
+            "<13>1 2016-02-21T04:32:57.853123Z - - - - -"
+        );
+    }
+
+    #[test]
+    fn escaped_param_values() {
+        let value = escape_param_value(r#"C:\my "logs"]"#);
+        assert_eq!(value, r#"C:\\my \"logs\"\]"#);
+        assert!(matches!(
+            escape_param_value("plain value"),
+            Cow::Borrowed(_)
+        ));
+
+        let msg = format!(r#"<14>1 - - - - - [a file="{}"] msg"#, value);
+        let message: Message = Message::parse(&msg).expect("parsing message");
+        assert_eq!(message.structured_data[0].params[0].value, value);
+        assert_eq!(message.to_string(), msg);
+    }
+
+    #[test]
+    fn hostile_fields() {
+        let long = "p".repeat(200);
+        let message = Message {
+            facility: 1,
+            severity: 6,
+            version: 1,
+            timestamp: Some("2024-01-01 00:00:00"),
+            hostname: Some("my host"),
+            app_name: Some("my app\té"),
+            proc_id: Some(long.as_str()),
+            msg_id: Some(""),
+            structured_data: vec![StructuredData::from((
+                "a b]",
+                vec![
+                    ("user name", "x").into(),
+                    (r#"k="v""#, "y").into(),
+                    ("", "z").into(),
+                ],
+            ))],
+            msg: "hi there",
+        };
+
+        let text = message.to_string();
+        let parsed: Message = Message::parse(&text).expect("parsing message");
+        assert_eq!(parsed.timestamp, Some("2024-01-01_00:00:00"));
+        assert_eq!(parsed.hostname, Some("my_host"));
+        assert_eq!(parsed.app_name, Some("my_app__"));
+        assert_eq!(parsed.proc_id, Some(&long[..128]));
+        assert_eq!(parsed.msg_id, None);
+        assert_eq!(
+            parsed.structured_data,
+            vec![StructuredData::from((
+                "a_b_",
+                vec![
+                    ("user_name", "x").into(),
+                    ("k__v_", "y").into(),
+                    ("_", "z").into()
+                ],
+            ))]
+        );
+        assert_eq!(parsed.msg.msg, "hi there");
+    }
+}
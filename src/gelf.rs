@@ -0,0 +1,209 @@
+//! Encoding of parsed messages as [GELF 1.1](https://go2docs.graylog.org/current/getting_in_log_data/gelf.html)
+//! JSON, optionally compressed and chunked for UDP.
+
+use crate::{
+    parser::{datetime::UnixTimestamp, StructuredData},
+    Message,
+};
+use flate2::{
+    write::{GzEncoder, ZlibEncoder},
+    Compression as Level,
+};
+use serde::ser::{Serialize, SerializeMap, Serializer};
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    io::{self, Write},
+    time::SystemTime,
+};
+
+/// The most chunks a GELF message may be split into.
+const MAX_CHUNKS: usize = 128;
+const CHUNK_HEADER_LEN: usize = 12;
+
+/// Serializes a message as a GELF object:
+///
+/// - `hostname` as `host` (`-` when missing),
+/// - the first line of MSG as `short_message`, and all of it as
+///   `full_message` when it has more lines,
+/// - the timestamp as `timestamp`, in seconds since the epoch,
+/// - severity as `level`,
+/// - facility, APP-NAME, PROCID and MSGID as `_facility`, `_app_name`,
+///   `_proc_id` and `_msg_id`,
+/// - every SD param as `_sdid_param`, with characters GELF does not allow in
+///   field names replaced by `_`.
+pub struct Gelf<'m, 'a, T, M> {
+    message: &'m Message<'a, T, Vec<StructuredData<'a>>, M>,
+}
+
+impl<'m, 'a, T, M> Gelf<'m, 'a, T, M> {
+    pub fn new(message: &'m Message<'a, T, Vec<StructuredData<'a>>, M>) -> Self {
+        Self { message }
+    }
+}
+
+impl<T, M> Serialize for Gelf<'_, '_, T, M>
+where
+    T: UnixTimestamp,
+    M: AsRef<str>,
+{
+    fn serialize<Z: Serializer>(&self, serializer: Z) -> Result<Z::Ok, Z::Error> {
+        let message = self.message;
+        let msg = message.msg.as_ref();
+        let short_message = msg.lines().next().unwrap_or("");
+
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("version", "1.1")?;
+        map.serialize_entry("host", message.hostname.unwrap_or("-"))?;
+        map.serialize_entry("short_message", short_message)?;
+        if short_message.len() < msg.trim_end().len() {
+            map.serialize_entry("full_message", msg)?;
+        }
+        if let Some(nanos) = message.timestamp.unix_nanos() {
+            map.serialize_entry("timestamp", &(nanos as f64 / 1e9))?;
+        }
+        map.serialize_entry("level", &message.severity)?;
+        map.serialize_entry("_facility", &message.facility)?;
+
+        let header = [
+            ("_app_name", message.app_name),
+            ("_proc_id", message.proc_id),
+            ("_msg_id", message.msg_id),
+        ];
+        for (key, value) in header {
+            if let Some(value) = value {
+                map.serialize_entry(key, value)?;
+            }
+        }
+
+        for sd in &message.structured_data {
+            for param in &sd.params {
+                let key = format!("_{}_{}", sd.id, param.name)
+                    .chars()
+                    .map(|c| match c {
+                        'a'..='z' | 'A'..='Z' | '0'..='9' | '_' | '.' | '-' => c,
+                        _ => '_',
+                    })
+                    .collect::<String>();
+                map.serialize_entry(&key, &param.value)?;
+            }
+        }
+
+        map.end()
+    }
+}
+
+/// Encodes `message` as GELF JSON, see [`Gelf`].
+pub fn to_vec<T, M>(message: &Message<'_, T, Vec<StructuredData<'_>>, M>) -> Vec<u8>
+where
+    T: UnixTimestamp,
+    M: AsRef<str>,
+{
+    serde_json::to_vec(&Gelf::new(message)).expect("GELF fields are always valid JSON")
+}
+
+/// The compression applied to GELF UDP payloads.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum Compression {
+    None,
+    Gzip,
+    Zlib,
+}
+
+/// Encodes `message` for a GELF UDP input: compressed with `compression`,
+/// and split into chunks when it does not fit in `max_datagram_size` bytes.
+/// Fails when more than 128 chunks would be needed.
+pub fn udp_datagrams<T, M>(
+    message: &Message<'_, T, Vec<StructuredData<'_>>, M>,
+    compression: Compression,
+    max_datagram_size: usize,
+) -> io::Result<Vec<Vec<u8>>>
+where
+    T: UnixTimestamp,
+    M: AsRef<str>,
+{
+    let payload = to_vec(message);
+    let payload = match compression {
+        Compression::None => payload,
+        Compression::Gzip => {
+            let mut encoder = GzEncoder::new(Vec::new(), Level::default());
+            encoder.write_all(&payload)?;
+            encoder.finish()?
+        }
+        Compression::Zlib => {
+            let mut encoder = ZlibEncoder::new(Vec::new(), Level::default());
+            encoder.write_all(&payload)?;
+            encoder.finish()?
+        }
+    };
+
+    chunks(payload, max_datagram_size)
+}
+
+/// Splits `payload` into GELF chunks of at most `max_datagram_size` bytes,
+/// each starting with the chunk magic bytes, a message id shared by all the
+/// chunks and their sequence number and count.
+fn chunks(payload: Vec<u8>, max_datagram_size: usize) -> io::Result<Vec<Vec<u8>>> {
+    if payload.len() <= max_datagram_size {
+        return Ok(vec![payload]);
+    }
+
+    let invalid = |e: &str| io::Error::new(io::ErrorKind::InvalidInput, e);
+    let chunk_size = max_datagram_size
+        .checked_sub(CHUNK_HEADER_LEN)
+        .filter(|size| *size > 0)
+        .ok_or_else(|| invalid("gelf: datagram size too small for a chunk header"))?;
+    let count = payload.len().div_ceil(chunk_size);
+    if count > MAX_CHUNKS {
+        return Err(invalid("gelf: message needs more than 128 chunks"));
+    }
+
+    let mut hasher = DefaultHasher::new();
+    payload.hash(&mut hasher);
+    SystemTime::now().hash(&mut hasher);
+    let id = hasher.finish().to_be_bytes();
+
+    Ok(payload
+        .chunks(chunk_size)
+        .enumerate()
+        .map(|(sequence, chunk)| {
+            let mut datagram = Vec::with_capacity(CHUNK_HEADER_LEN + chunk.len());
+            datagram.extend_from_slice(&[0x1e, 0x0f]);
+            datagram.extend_from_slice(&id);
+            datagram.extend_from_slice(&[sequence as u8, count as u8]);
+            datagram.extend_from_slice(chunk);
+            datagram
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn single_chunk() {
+        assert_eq!(
+            chunks(vec![1, 2, 3], 3).expect("chunking"),
+            vec![vec![1, 2, 3]]
+        );
+    }
+
+    #[test]
+    fn split_chunks() {
+        let chunks = chunks((0..30).collect(), 22).expect("chunking");
+
+        assert_eq!(chunks.len(), 3);
+        for (sequence, chunk) in chunks.iter().enumerate() {
+            assert_eq!(&chunk[..2], &[0x1e, 0x0f]);
+            assert_eq!(&chunk[2..10], &chunks[0][2..10]);
+            assert_eq!(&chunk[10..12], &[sequence as u8, 3]);
+        }
+        assert_eq!(chunks[2][12..], [20, 21, 22, 23, 24, 25, 26, 27, 28, 29]);
+    }
+
+    #[test]
+    fn too_many_chunks() {
+        assert!(chunks(vec![0; 129], 13).is_err());
+    }
+}
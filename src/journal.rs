@@ -0,0 +1,316 @@
+//! Conversion between syslog messages and the systemd journal
+//! [export format](https://systemd.io/JOURNAL_EXPORT_FORMATS/).
+
+use crate::{
+    parser::{msg::Raw, StructuredData},
+    Error, Message,
+};
+use std::io::{self, Write};
+
+/// A journal entry read by [`JournalReader`], with its fields in the order
+/// they appeared. Values are bytes since the export format is binary safe.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct JournalEntry<'a> {
+    pub fields: Vec<(&'a str, &'a [u8])>,
+}
+
+impl<'a> JournalEntry<'a> {
+    /// The value of the first `key` field.
+    pub fn get(&self, key: &str) -> Option<&'a [u8]> {
+        self.fields
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map(|(_, value)| *value)
+    }
+
+    /// The value of the first `key` field, if it is UTF-8.
+    pub fn get_str(&self, key: &str) -> Option<&'a str> {
+        self.get(key)
+            .and_then(|value| std::str::from_utf8(value).ok())
+    }
+
+    /// Maps the entry to a syslog message:
+    ///
+    /// - `PRIORITY` and `SYSLOG_FACILITY` to the severity (default info) and
+    ///   facility (default user),
+    /// - `__REALTIME_TIMESTAMP` to the timestamp, in microseconds since the epoch,
+    /// - `_HOSTNAME`, `SYSLOG_IDENTIFIER`, `_PID` (or `SYSLOG_PID`) and
+    ///   `MESSAGE_ID` to the header fields,
+    /// - `MESSAGE` to MSG, which is required and must be UTF-8.
+    pub fn to_message(
+        &self,
+    ) -> Result<Message<'a, Option<u64>, Vec<StructuredData<'a>>, Raw<'a>>, Error<'a>> {
+        let number = |key: &str, default: u8| match self.get_str(key) {
+            Some(value) => value
+                .parse()
+                .map_err(|_| Error::Custom(format!("journal: invalid `{}` `{}`", key, value))),
+            None => Ok(default),
+        };
+        let msg = self
+            .get_str("MESSAGE")
+            .ok_or_else(|| Error::Custom("journal: missing UTF-8 `MESSAGE`".into()))?;
+        let timestamp = match self.get_str("__REALTIME_TIMESTAMP") {
+            Some(value) => Some(value.parse().map_err(|_| {
+                Error::Custom(format!(
+                    "journal: invalid `__REALTIME_TIMESTAMP` `{}`",
+                    value
+                ))
+            })?),
+            None => None,
+        };
+
+        Ok(Message {
+            facility: number("SYSLOG_FACILITY", 1)?,
+            severity: number("PRIORITY", 6)?,
+            version: 1,
+            timestamp,
+            hostname: self.get_str("_HOSTNAME"),
+            app_name: self.get_str("SYSLOG_IDENTIFIER"),
+            proc_id: self.get_str("_PID").or_else(|| self.get_str("SYSLOG_PID")),
+            msg_id: self.get_str("MESSAGE_ID"),
+            structured_data: vec![],
+            msg: msg.into(),
+        })
+    }
+}
+
+/// Iterates over the entries of a journal export stream. Values may be
+/// written as `KEY=value` lines or, when they span several lines or are not
+/// text, as `KEY`, a little endian 64 bit size and the value itself. Stops
+/// after the first error.
+pub struct JournalReader<'a> {
+    rem: &'a [u8],
+}
+
+impl<'a> JournalReader<'a> {
+    pub fn new(input: &'a [u8]) -> Self {
+        Self { rem: input }
+    }
+
+    fn read_entry(&mut self) -> Result<JournalEntry<'a>, Error<'a>> {
+        let mut fields = Vec::new();
+
+        while let Some(end) = self.rem.iter().position(|b| *b == b'\n') {
+            let line = &self.rem[..end];
+            self.rem = &self.rem[end + 1..];
+            if line.is_empty() {
+                return Ok(JournalEntry { fields });
+            }
+
+            let field = match line.iter().position(|b| *b == b'=') {
+                Some(eq) => (field_name(&line[..eq])?, &line[eq + 1..]),
+                None => (field_name(line)?, self.read_sized()?),
+            };
+            fields.push(field);
+        }
+
+        if !self.rem.is_empty() {
+            return Err(Error::Custom("journal: truncated field".into()));
+        }
+
+        Ok(JournalEntry { fields })
+    }
+
+    fn read_sized(&mut self) -> Result<&'a [u8], Error<'a>> {
+        let truncated = || Error::Custom("journal: truncated binary field".into());
+
+        let size = self.rem.get(..8).ok_or_else(truncated)?;
+        let size = u64::from_le_bytes(size.try_into().expect("8 bytes"));
+        let size = usize::try_from(size).map_err(|_| truncated())?;
+        let rem = &self.rem[8..];
+        if rem.len() <= size || rem[size] != b'\n' {
+            return Err(truncated());
+        }
+
+        self.rem = &rem[size + 1..];
+        Ok(&rem[..size])
+    }
+}
+
+fn field_name(name: &[u8]) -> Result<&str, Error<'_>> {
+    std::str::from_utf8(name)
+        .map_err(|_| Error::Custom(format!("journal: invalid field name `{:?}`", name)))
+}
+
+impl<'a> Iterator for JournalReader<'a> {
+    type Item = Result<JournalEntry<'a>, Error<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.rem.is_empty() {
+            return None;
+        }
+
+        match self.read_entry() {
+            Err(err) => {
+                self.rem = &[];
+                Some(Err(err))
+            }
+            Ok(entry) => Some(Ok(entry)),
+        }
+    }
+}
+
+/// A TIMESTAMP that can be written to the journal export format.
+pub trait JournalTimestamp {
+    /// The field and value to write, if any.
+    fn journal_field(&self) -> Option<(&'static str, String)>;
+}
+
+/// Kept as written, the way journald stores timestamps of syslog messages.
+impl JournalTimestamp for Option<&str> {
+    fn journal_field(&self) -> Option<(&'static str, String)> {
+        self.map(|timestamp| ("SYSLOG_TIMESTAMP", timestamp.to_string()))
+    }
+}
+
+/// Microseconds since the epoch, as produced by [`JournalEntry::to_message`].
+impl JournalTimestamp for Option<u64> {
+    fn journal_field(&self) -> Option<(&'static str, String)> {
+        self.map(|micros| ("__REALTIME_TIMESTAMP", micros.to_string()))
+    }
+}
+
+#[cfg(feature = "chrono-timestamp")]
+impl JournalTimestamp for Option<crate::parser::DateTime> {
+    fn journal_field(&self) -> Option<(&'static str, String)> {
+        self.map(|dt| {
+            let micros = dt.timestamp() * 1_000_000 + i64::from(dt.timestamp_subsec_micros());
+            ("__REALTIME_TIMESTAMP", micros.to_string())
+        })
+    }
+}
+
+impl JournalTimestamp for crate::parser::Skip {
+    fn journal_field(&self) -> Option<(&'static str, String)> {
+        None
+    }
+}
+
+/// Writes `message` as one journal export entry, the inverse of
+/// [`JournalEntry::to_message`]. SD params become extra fields named
+/// `SD_<ID>_<NAME>`, in upper case with anything but letters, digits and `_`
+/// replaced by `_` and cut to the 64 characters journald allows, so that they
+/// can't override the fields above or the trusted `_` ones.
+pub fn write_export<W, T, M>(
+    writer: &mut W,
+    message: &Message<'_, T, Vec<StructuredData<'_>>, M>,
+) -> io::Result<()>
+where
+    W: Write,
+    T: JournalTimestamp,
+    M: AsRef<str>,
+{
+    if let Some((key, value)) = message.timestamp.journal_field() {
+        write_field(writer, key, value.as_bytes())?;
+    }
+    write_field(writer, "PRIORITY", message.severity.to_string().as_bytes())?;
+    write_field(
+        writer,
+        "SYSLOG_FACILITY",
+        message.facility.to_string().as_bytes(),
+    )?;
+
+    let header = [
+        ("_HOSTNAME", message.hostname),
+        ("SYSLOG_IDENTIFIER", message.app_name),
+        ("_PID", message.proc_id),
+        ("MESSAGE_ID", message.msg_id),
+    ];
+    for (key, value) in header {
+        if let Some(value) = value {
+            write_field(writer, key, value.as_bytes())?;
+        }
+    }
+
+    for sd in &message.structured_data {
+        for param in &sd.params {
+            let key = format!("SD_{}_{}", sd.id, param.name)
+                .chars()
+                .take(MAX_FIELD_NAME_LEN)
+                .map(|c| match c.to_ascii_uppercase() {
+                    c @ ('A'..='Z' | '0'..='9') => c,
+                    _ => '_',
+                })
+                .collect::<String>();
+            write_field(writer, &key, param.value.as_bytes())?;
+        }
+    }
+
+    write_field(writer, "MESSAGE", message.msg.as_ref().as_bytes())?;
+    writer.write_all(b"\n")
+}
+
+/// The longest field name journald accepts.
+const MAX_FIELD_NAME_LEN: usize = 64;
+
+fn write_field<W: Write>(writer: &mut W, key: &str, value: &[u8]) -> io::Result<()> {
+    if value
+        .iter()
+        .any(|b| *b == b'\n' || (*b < b' ' && *b != b'\t'))
+    {
+        writer.write_all(key.as_bytes())?;
+        writer.write_all(b"\n")?;
+        writer.write_all(&(value.len() as u64).to_le_bytes())?;
+    } else {
+        writer.write_all(key.as_bytes())?;
+        writer.write_all(b"=")?;
+    }
+    writer.write_all(value)?;
+    writer.write_all(b"\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_export() {
+        let mut input = b"__REALTIME_TIMESTAMP=1700000000000000\nPRIORITY=3\nMESSAGE\n".to_vec();
+        input.extend_from_slice(&12u64.to_le_bytes());
+        input.extend_from_slice(b"first\nsecond\n\nMESSAGE=next\n\n");
+
+        let entries = JournalReader::new(&input).collect::<Result<Vec<_>, _>>();
+
+        assert_eq!(
+            entries,
+            Ok(vec![
+                JournalEntry {
+                    fields: vec![
+                        ("__REALTIME_TIMESTAMP", b"1700000000000000".as_slice()),
+                        ("PRIORITY", b"3".as_slice()),
+                        ("MESSAGE", b"first\nsecond".as_slice()),
+                    ]
+                },
+                JournalEntry {
+                    fields: vec![("MESSAGE", b"next".as_slice())]
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn truncated_binary_field() {
+        let mut input = b"MESSAGE\n".to_vec();
+        input.extend_from_slice(&20u64.to_le_bytes());
+        input.extend_from_slice(b"short\n");
+
+        let mut reader = JournalReader::new(&input);
+
+        assert_eq!(
+            reader.next(),
+            Some(Err(Error::Custom("journal: truncated binary field".into())))
+        );
+        assert_eq!(reader.next(), None);
+    }
+
+    #[test]
+    fn write_multiline_field() {
+        let mut out = Vec::new();
+        write_field(&mut out, "MESSAGE", b"a\nb").expect("writing field");
+
+        let mut expected = b"MESSAGE\n".to_vec();
+        expected.extend_from_slice(&3u64.to_le_bytes());
+        expected.extend_from_slice(b"a\nb\n");
+        assert_eq!(out, expected);
+    }
+}
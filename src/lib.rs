@@ -1,41 +1,66 @@
+#[cfg(feature = "arrow")]
+pub mod arrow;
+#[cfg(feature = "codec")]
+pub mod codec;
+#[cfg(feature = "serde")]
+pub mod ecs;
 mod error;
+pub mod format;
+#[cfg(feature = "gelf")]
+pub mod gelf;
+pub mod journal;
+#[cfg(feature = "log")]
+pub mod log;
+pub mod logplex;
+#[cfg(any(feature = "server", feature = "relp"))]
+mod net;
+#[cfg(feature = "serde")]
+pub mod otel;
 pub mod parser;
+#[cfg(feature = "relp")]
+pub mod relp;
+#[cfg(feature = "sender")]
+pub mod sender;
+#[cfg(feature = "server")]
+pub mod server;
+#[cfg(any(feature = "log", feature = "tracing"))]
+pub mod sink;
+#[cfg(feature = "tracing")]
+pub mod tracing;
 
 pub use error::Error;
+pub use parser::{Parser, ParserIter};
+#[cfg(feature = "derive")]
+pub use rsyslog_derive::{ParseMsg, ParsePart};
 pub(crate) type NomRes<T, U> = nom::IResult<T, U, nom::error::VerboseError<T>>;
 
-#[cfg(not(feature = "serde-serialize"))]
+use std::marker::PhantomData;
+
 pub trait ParsePart<'a> {
     fn parse(msg: &'a str) -> Result<(&'a str, Self), Error<'a>>
     where
         Self: Sized;
 }
-#[cfg(feature = "serde-serialize")]
-pub trait ParsePart<'a>: serde::Serialize {
-    fn parse(msg: &'a str) -> Result<(&'a str, Self), Error<'a>>
-    where
-        Self: Sized;
-}
 
 #[derive(Debug, Eq, PartialEq, Clone)]
-#[cfg_attr(feature = "serde-serialize", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Message<
     'a,
     T = Option<&'a str>,
     S = Vec<parser::StructuredData<'a>>,
     M = parser::msg::Raw<'a>,
-> where
-    T: ParsePart<'a>,
-    S: ParsePart<'a>,
-    M: ParseMsg<'a>,
-{
+> {
     pub facility: u8,
     pub severity: u8,
     pub version: u8,
     pub timestamp: T,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub hostname: Option<&'a str>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub app_name: Option<&'a str>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub proc_id: Option<&'a str>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub msg_id: Option<&'a str>,
     pub structured_data: S,
     pub msg: M,
@@ -61,13 +86,108 @@ where
         MessageIter {
             rem: msg,
             found_error: false,
-            t: std::marker::PhantomData,
-            s: std::marker::PhantomData,
-            m: std::marker::PhantomData,
+            t: PhantomData,
+            s: PhantomData,
+            m: PhantomData,
         }
     }
 }
 
+impl<'a, T, S, M> Message<'a, T, S, M> {
+    /// Replaces MSG with the result of `f`, keeping the rest of the message.
+    pub fn map_msg<N>(self, f: impl FnOnce(M) -> N) -> Message<'a, T, S, N> {
+        Message {
+            facility: self.facility,
+            severity: self.severity,
+            version: self.version,
+            timestamp: self.timestamp,
+            hostname: self.hostname,
+            app_name: self.app_name,
+            proc_id: self.proc_id,
+            msg_id: self.msg_id,
+            structured_data: self.structured_data,
+            msg: f(self.msg),
+        }
+    }
+
+    /// The keyword of the facility (`kern`, `user`, ..., `local7`).
+    pub fn facility_name(&self) -> Option<&'static str> {
+        const NAMES: [&str; 24] = [
+            "kern",
+            "user",
+            "mail",
+            "daemon",
+            "auth",
+            "syslog",
+            "lpr",
+            "news",
+            "uucp",
+            "cron",
+            "authpriv",
+            "ftp",
+            "ntp",
+            "security",
+            "console",
+            "solaris-cron",
+            "local0",
+            "local1",
+            "local2",
+            "local3",
+            "local4",
+            "local5",
+            "local6",
+            "local7",
+        ];
+        NAMES.get(usize::from(self.facility)).copied()
+    }
+
+    /// The RFC 5424 name of the severity (`Emergency`, ..., `Debug`).
+    pub fn severity_name(&self) -> Option<&'static str> {
+        const NAMES: [&str; 8] = [
+            "Emergency",
+            "Alert",
+            "Critical",
+            "Error",
+            "Warning",
+            "Notice",
+            "Informational",
+            "Debug",
+        ];
+        NAMES.get(usize::from(self.severity)).copied()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'a, T, M> Message<'a, T, Vec<parser::StructuredData<'a>>, M> {
+    /// Maps the message to an Elastic Common Schema document, see [`ecs::Ecs`].
+    pub fn to_ecs(&self) -> ecs::Ecs<'_, 'a, T, M> {
+        ecs::Ecs::new(self)
+    }
+
+    /// Maps the message to an OpenTelemetry `LogRecord`, see [`otel::OtelLogRecord`].
+    pub fn to_otel_log_record(&self) -> otel::OtelLogRecord<'_, 'a, T, M> {
+        otel::OtelLogRecord::new(self)
+    }
+}
+
+impl<'a, T, S, M> Message<'a, T, S, M>
+where
+    T: ParsePart<'a>,
+    S: ParsePart<'a>,
+{
+    /// Parses `msg` using the given MSG parser instance instead of a `ParseMsg` type.
+    ///
+    /// See [`Parser`] to configure the TIMESTAMP and SD parsers as well.
+    pub fn parse_with<P>(msg: &'a str, msg_parser: &P) -> Result<Message<'a, T, S, M>, Error<'a>>
+    where
+        P: MsgParser<'a, Output = M>,
+    {
+        parser::parse_with(msg, &PhantomData::<T>, &PhantomData::<S>, msg_parser)
+            .map(|tuple| tuple.1)
+            .map_err(|e| e.into_detailed_with(msg))
+    }
+}
+
 pub struct MessageIter<'a, T, S, M>
 where
     T: ParsePart<'a>,
@@ -76,9 +196,9 @@ where
 {
     rem: &'a str,
     found_error: bool,
-    t: std::marker::PhantomData<T>,
-    s: std::marker::PhantomData<S>,
-    m: std::marker::PhantomData<M>,
+    t: PhantomData<T>,
+    s: PhantomData<S>,
+    m: PhantomData<M>,
 }
 
 impl<'a, T, S, M> Iterator for MessageIter<'a, T, S, M>
@@ -110,6 +230,52 @@ where
     }
 }
 
+/// Conversion of a single `key=value` value, used by the `ParseMsg` derive for
+/// logfmt-like payloads.
+pub trait ParseValue<'a>: Sized {
+    fn parse_value(value: &'a str) -> Result<Self, Error<'a>>;
+
+    /// The value to use when the key is absent, `None` if the key is required.
+    fn missing() -> Option<Self> {
+        None
+    }
+}
+
+impl<'a> ParseValue<'a> for &'a str {
+    fn parse_value(value: &'a str) -> Result<Self, Error<'a>> {
+        Ok(value)
+    }
+}
+
+impl<'a, T: ParseValue<'a>> ParseValue<'a> for Option<T> {
+    fn parse_value(value: &'a str) -> Result<Self, Error<'a>> {
+        match value {
+            "" => Ok(None),
+            value => T::parse_value(value).map(Some),
+        }
+    }
+
+    fn missing() -> Option<Self> {
+        Some(None)
+    }
+}
+
+macro_rules! parse_value_from_str {
+    ($($ty:ty),+) => {
+        $(
+            impl<'a> ParseValue<'a> for $ty {
+                fn parse_value(value: &'a str) -> Result<Self, Error<'a>> {
+                    value
+                        .parse()
+                        .map_err(|e| Error::Custom(format!("invalid value `{}`: {}", value, e)))
+                }
+            }
+        )+
+    };
+}
+
+parse_value_from_str!(u8, u16, u32, u64, i8, i16, i32, i64, f32, f64, bool);
+
 pub struct Originator<'a> {
     pub hostname: Option<&'a str>,
     pub app_name: Option<&'a str>,
@@ -122,3 +288,49 @@ pub trait ParseMsg<'a> {
     where
         Self: Sized;
 }
+
+/// A TIMESTAMP or SD parser that carries configuration.
+///
+/// Every `ParsePart` type `P` is available as a stateless `PartParser` through
+/// `PhantomData<P>`.
+pub trait PartParser<'a> {
+    type Output;
+
+    fn parse(&self, part: &'a str) -> Result<(&'a str, Self::Output), Error<'a>>;
+}
+
+/// A MSG parser that carries configuration, such as [`parser::msg::Regex`].
+///
+/// Every `ParseMsg` type `M` is available as a stateless `MsgParser` through
+/// `PhantomData<M>`.
+pub trait MsgParser<'a> {
+    type Output;
+
+    fn parse(
+        &self,
+        msg: &'a str,
+        originator: &Originator<'a>,
+    ) -> Result<(&'a str, Self::Output), Error<'a>>;
+}
+
+impl<'a, P: ParsePart<'a>> PartParser<'a> for PhantomData<P> {
+    type Output = P;
+
+    fn parse(&self, part: &'a str) -> Result<(&'a str, P), Error<'a>> {
+        P::parse(part)
+    }
+}
+
+impl<'a, M: ParseMsg<'a>> MsgParser<'a> for PhantomData<M> {
+    type Output = M;
+
+    fn parse(&self, msg: &'a str, originator: &Originator<'a>) -> Result<(&'a str, M), Error<'a>> {
+        M::parse(msg, originator)
+    }
+}
+
+impl<'a, M: ParseMsg<'a>> ParseMsg<'a> for Box<M> {
+    fn parse(msg: &'a str, originator: &Originator<'a>) -> Result<(&'a str, Self), Error<'a>> {
+        M::parse(msg, originator).map(|(rem, msg)| (rem, Box::new(msg)))
+    }
+}
@@ -0,0 +1,130 @@
+//! A backend of the [`log`](::log) facade, writing records as RFC 5424
+//! messages through the crate's formatter, such as
+//!
+//! ```text
+//! <12>1 2024-01-01T00:00:00.000000Z host app 42 - [log@32473 target="app::db" file="src/db.rs" line="42"] slow query
+//! ```
+
+pub use crate::sink::Sink;
+
+use crate::{
+    format::escape_param_value,
+    parser::{SdParam, StructuredData},
+    sink, Message,
+};
+use ::log::{Level, LevelFilter, Log, Metadata, Record, SetLoggerError};
+
+/// The SD-ID of the element holding the target, file and line of a record.
+pub const SD_ID: &str = "log@32473";
+
+/// The syslog severity of `level`, `Trace` sharing Debug's.
+pub fn severity(level: Level) -> u8 {
+    match level {
+        Level::Error => 3,
+        Level::Warn => 4,
+        Level::Info => 6,
+        Level::Debug | Level::Trace => 7,
+    }
+}
+
+/// Writes log records to a [`Sink`] as RFC 5424 messages, the record's target,
+/// file and line being the params of a [`SD_ID`] element.
+#[derive(Debug)]
+pub struct SyslogLogger {
+    sink: Sink,
+    level: LevelFilter,
+    facility: u8,
+    hostname: Option<String>,
+    app_name: Option<String>,
+    proc_id: String,
+}
+
+impl SyslogLogger {
+    /// A logger of every level, with the name of the host as HOSTNAME, the
+    /// process ID as PROCID and the name of the executable as APP-NAME.
+    pub fn new(sink: Sink) -> Self {
+        Self {
+            sink,
+            level: LevelFilter::Trace,
+            facility: 1,
+            hostname: sink::hostname(),
+            app_name: sink::app_name(),
+            proc_id: std::process::id().to_string(),
+        }
+    }
+
+    /// Records of lower levels are ignored.
+    pub fn level(mut self, level: LevelFilter) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Defaults to 1, user-level messages.
+    pub fn facility(mut self, facility: u8) -> Self {
+        self.facility = facility;
+        self
+    }
+
+    /// Defaults to the name of the host.
+    pub fn hostname(mut self, hostname: impl Into<String>) -> Self {
+        self.hostname = Some(hostname.into());
+        self
+    }
+
+    pub fn app_name(mut self, app_name: impl Into<String>) -> Self {
+        self.app_name = Some(app_name.into());
+        self
+    }
+
+    /// Installs the logger as the global one, with its level as the maximum.
+    pub fn init(self) -> Result<(), SetLoggerError> {
+        ::log::set_max_level(self.level);
+        ::log::set_boxed_logger(Box::new(self))
+    }
+
+    /// Formats `record` as it is written, whatever its level.
+    pub fn format(&self, record: &Record<'_>) -> String {
+        let target = escape_param_value(record.target());
+        let file = record.file().map(escape_param_value);
+        let line = record.line().map(|line| line.to_string());
+
+        let mut params = vec![SdParam::from(("target", &*target))];
+        if let Some(file) = &file {
+            params.push(("file", &**file).into());
+        }
+        if let Some(line) = &line {
+            params.push(("line", line.as_str()).into());
+        }
+        let msg = record.args().to_string();
+
+        Message {
+            facility: self.facility,
+            severity: severity(record.level()),
+            version: 1,
+            timestamp: sink::now(),
+            hostname: self.hostname.as_deref(),
+            app_name: self.app_name.as_deref(),
+            proc_id: Some(&self.proc_id),
+            msg_id: None,
+            structured_data: vec![StructuredData::from((SD_ID, params))],
+            msg: msg.as_str(),
+        }
+        .to_string()
+    }
+}
+
+impl Log for SyslogLogger {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        metadata.level() <= self.level
+    }
+
+    fn log(&self, record: &Record<'_>) {
+        if self.enabled(record.metadata()) {
+            self.sink.write(self.format(record));
+        }
+    }
+
+    fn flush(&self) {
+        self.sink.flush();
+    }
+}
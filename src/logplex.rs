@@ -0,0 +1,261 @@
+//! Validation of the requests Heroku's logplex posts to HTTPS log drains, see
+//! [HTTPS drains](https://devcenter.heroku.com/articles/log-drains#https-drains).
+//!
+//! This module doesn't depend on an HTTP library: [`LogplexRequest::new`]
+//! takes a header lookup and the body. The `http` feature adds
+//! [`LogplexRequest::from_http`] for `http::Request` (as used by hyper), and the
+//! `axum` feature an extractor, [`Logplex`].
+
+use crate::{
+    parser::{msg::Heroku, Skip},
+    Error, Message, ParseMsg, ParsePart,
+};
+
+/// The content type of drain requests.
+pub const CONTENT_TYPE: &str = "application/logplex-1";
+
+/// A message of a drain: logplex frames have no SD, and the MSG is dispatched
+/// to the Heroku parsers.
+pub type LogplexMessage<'a> = Message<'a, Option<&'a str>, Skip, Heroku<'a>>;
+
+/// A drain request whose headers and body were checked to agree with each
+/// other. The drain token is not checked against anything; compare it with
+/// the one of the drain to reject requests for other drains.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct LogplexRequest<'a> {
+    /// `Logplex-Frame-Id`, the same for retries of a request.
+    pub frame_id: &'a str,
+    /// `Logplex-Drain-Token`, such as `d.8bf7d4f4-…`.
+    pub drain_token: &'a str,
+    /// The octet counted frames of the body, without their trailing newline.
+    pub frames: Vec<&'a str>,
+}
+
+impl<'a> LogplexRequest<'a> {
+    /// Validates a request, looking its headers up with `header` (which should
+    /// ignore case, as HTTP libraries do) and splitting `body` into frames.
+    /// Fails on another content type, a missing header or a
+    /// `Logplex-Msg-Count` that doesn't match the number of frames.
+    pub fn new<H>(header: H, body: &'a [u8]) -> Result<Self, Error<'a>>
+    where
+        H: Fn(&str) -> Option<&'a str>,
+    {
+        let required = |name| {
+            header(name).ok_or_else(|| Error::Custom(format!("logplex: missing `{}`", name)))
+        };
+        let content_type = required("Content-Type")?;
+        let media_type = content_type.split(';').next().unwrap_or_default().trim();
+        if !media_type.eq_ignore_ascii_case(CONTENT_TYPE) {
+            return Err(Error::Custom(format!(
+                "logplex: unexpected content type `{}`",
+                content_type
+            )));
+        }
+        let msg_count = required("Logplex-Msg-Count")?;
+        let msg_count = msg_count.trim().parse::<usize>().map_err(|_| {
+            Error::Custom(format!(
+                "logplex: invalid `Logplex-Msg-Count` `{}`",
+                msg_count
+            ))
+        })?;
+        let frame_id = required("Logplex-Frame-Id")?;
+        let drain_token = required("Logplex-Drain-Token")?;
+
+        let frames = split_frames(body)?;
+        if frames.len() != msg_count {
+            return Err(Error::Custom(format!(
+                "logplex: `Logplex-Msg-Count` is {} but the body has {} frames",
+                msg_count,
+                frames.len()
+            )));
+        }
+
+        Ok(Self {
+            frame_id,
+            drain_token,
+            frames,
+        })
+    }
+
+    /// Parses the frames as [`LogplexMessage`]s.
+    pub fn messages(&self) -> impl Iterator<Item = Result<LogplexMessage<'a>, Error<'a>>> + '_ {
+        self.parse()
+    }
+
+    /// Parses the frames with other parsers than [`LogplexMessage`]'s.
+    pub fn parse<T, S, M>(
+        &self,
+    ) -> impl Iterator<Item = Result<Message<'a, T, S, M>, Error<'a>>> + '_
+    where
+        T: ParsePart<'a>,
+        S: ParsePart<'a>,
+        M: ParseMsg<'a>,
+    {
+        self.frames.iter().map(|frame| Message::parse(frame))
+    }
+}
+
+#[cfg(feature = "http")]
+impl<'a> LogplexRequest<'a> {
+    /// Validates an `http::Request` whose body was read, such as a hyper
+    /// request collected into `Bytes`.
+    pub fn from_http<B: AsRef<[u8]>>(request: &'a http::Request<B>) -> Result<Self, Error<'a>> {
+        Self::new(
+            |name| request.headers().get(name)?.to_str().ok(),
+            request.body().as_ref(),
+        )
+    }
+}
+
+/// Splits an octet counted body, `83 <40>1 2012-11-30T06:45:29+00:00 host app
+/// web.3 - State changed from starting to up\n`. Logplex counts the newline
+/// ending each frame, which is removed.
+fn split_frames(body: &[u8]) -> Result<Vec<&str>, Error<'static>> {
+    let mut frames = Vec::new();
+    let mut rem = body;
+    loop {
+        while let [b'\r' | b'\n' | b' ', rest @ ..] = rem {
+            rem = rest;
+        }
+        if rem.is_empty() {
+            return Ok(frames);
+        }
+
+        let digits = rem.iter().take_while(|b| b.is_ascii_digit()).count();
+        let count = match rem.get(digits) {
+            Some(b' ') => std::str::from_utf8(&rem[..digits])
+                .ok()
+                .and_then(|count| count.parse::<usize>().ok()),
+            _ => None,
+        };
+        let count = count.ok_or_else(|| {
+            let header: String = String::from_utf8_lossy(rem).chars().take(20).collect();
+            Error::Custom(format!("logplex: invalid octet count at `{}`", header))
+        })?;
+
+        let frame = rem[digits + 1..].get(..count).ok_or_else(|| {
+            Error::Custom(format!(
+                "logplex: body ended {} bytes into a frame of {}",
+                rem.len() - digits - 1,
+                count
+            ))
+        })?;
+        let frame = std::str::from_utf8(frame).map_err(|_| {
+            Error::Custom(format!("logplex: frame {} is not UTF-8", frames.len() + 1))
+        })?;
+        frames.push(frame.strip_suffix('\n').unwrap_or(frame));
+        rem = &rem[digits + 1 + count..];
+    }
+}
+
+#[cfg(feature = "axum")]
+pub use extract::Logplex;
+
+#[cfg(feature = "axum")]
+mod extract {
+    use super::LogplexRequest;
+    use axum::{
+        body::Bytes,
+        extract::{FromRequest, Request},
+        http::StatusCode,
+    };
+    use std::ops::Range;
+
+    /// An axum extractor of drain requests, rejecting invalid ones with
+    /// `400 Bad Request`.
+    #[derive(Debug, Clone)]
+    pub struct Logplex {
+        frame_id: String,
+        drain_token: String,
+        body: String,
+        /// The frames of the validated request, as ranges of `body`.
+        frames: Vec<Range<usize>>,
+    }
+
+    impl Logplex {
+        /// The validated request, borrowing from the extractor.
+        pub fn request(&self) -> LogplexRequest<'_> {
+            LogplexRequest {
+                frame_id: &self.frame_id,
+                drain_token: &self.drain_token,
+                frames: self
+                    .frames
+                    .iter()
+                    .map(|frame| &self.body[frame.clone()])
+                    .collect(),
+            }
+        }
+    }
+
+    impl<S: Send + Sync> FromRequest<S> for Logplex {
+        type Rejection = (StatusCode, String);
+
+        async fn from_request(request: Request, state: &S) -> Result<Self, Self::Rejection> {
+            let bad_request = |err: crate::Error<'_>| (StatusCode::BAD_REQUEST, err.to_string());
+            let headers = request.headers().clone();
+            let body = Bytes::from_request(request, state)
+                .await
+                .map_err(|err| (err.status(), err.body_text()))?;
+
+            let validated = LogplexRequest::new(|name| headers.get(name)?.to_str().ok(), &body)
+                .map_err(bad_request)?;
+            let frame_id = validated.frame_id.to_string();
+            let drain_token = validated.drain_token.to_string();
+            let frames = validated
+                .frames
+                .iter()
+                .map(|frame| {
+                    let start = frame.as_ptr() as usize - body.as_ptr() as usize;
+                    start..start + frame.len()
+                })
+                .collect();
+
+            // The frames being UTF-8 and the rest octet counts and
+            // whitespace, a validated body is UTF-8 as a whole.
+            let body = String::from_utf8(Vec::from(body)).map_err(|_| {
+                bad_request(crate::Error::Custom("logplex: body is not UTF-8".into()))
+            })?;
+
+            Ok(Self {
+                frame_id,
+                drain_token,
+                body,
+                frames,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames() {
+        assert_eq!(
+            split_frames(b"4 abc\n3 def\r\n2 gh"),
+            Ok(vec!["abc", "def", "gh"])
+        );
+        assert_eq!(split_frames(b""), Ok(vec![]));
+    }
+
+    #[test]
+    fn invalid_frames() {
+        assert_eq!(
+            split_frames(b"4 abc\nx4 abc"),
+            Err(Error::Custom(
+                "logplex: invalid octet count at `x4 abc`".into()
+            ))
+        );
+        assert_eq!(
+            split_frames(b"10 abc"),
+            Err(Error::Custom(
+                "logplex: body ended 3 bytes into a frame of 10".into()
+            ))
+        );
+        assert_eq!(
+            split_frames(b"2 \xff\n"),
+            Err(Error::Custom("logplex: frame 1 is not UTF-8".into()))
+        );
+    }
+}
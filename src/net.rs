@@ -0,0 +1,148 @@
+//! Helpers shared by the listeners of the receivers.
+
+use std::{
+    io,
+    sync::{Arc, Condvar, Mutex, PoisonError},
+    thread,
+    time::Duration,
+};
+
+/// How long a connection may stay silent before it is closed, by default.
+pub(crate) const DEFAULT_READ_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// How many connections are read at once, by default.
+pub(crate) const DEFAULT_MAX_CONNECTIONS: usize = 1024;
+
+/// Accepts connections with `accept` until it fails for good, reading each
+/// one on its own thread with `serve`. Once `max_connections` are open, the
+/// next one is only accepted when another closes.
+pub(crate) fn serve_connections<C, A, S>(
+    max_connections: usize,
+    mut accept: A,
+    serve: S,
+) -> io::Result<()>
+where
+    C: Send + 'static,
+    A: FnMut() -> io::Result<C>,
+    S: Fn(C) + Send + Sync + 'static,
+{
+    let serve = Arc::new(serve);
+    let open = Arc::new((Mutex::new(0_usize), Condvar::new()));
+    loop {
+        {
+            let (count, closed) = &*open;
+            let count = count.lock().unwrap_or_else(PoisonError::into_inner);
+            let _count = closed
+                .wait_while(count, |count| *count >= max_connections.max(1))
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+        let connection = match accept() {
+            Ok(connection) => connection,
+            Err(err) if accept_can_continue(&err) => continue,
+            Err(err) => return Err(err),
+        };
+
+        let slot = Slot::take(Arc::clone(&open));
+        let serve = Arc::clone(&serve);
+        thread::spawn(move || {
+            let _slot = slot;
+            serve(connection);
+        });
+    }
+}
+
+/// An open connection, counted until dropped, even by a panicking handler.
+struct Slot(Arc<(Mutex<usize>, Condvar)>);
+
+impl Slot {
+    fn take(open: Arc<(Mutex<usize>, Condvar)>) -> Self {
+        *open.0.lock().unwrap_or_else(PoisonError::into_inner) += 1;
+        Self(open)
+    }
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        let (count, closed) = &*self.0;
+        *count.lock().unwrap_or_else(PoisonError::into_inner) -= 1;
+        closed.notify_one();
+    }
+}
+
+/// Whether accepting connections can go on after `err`, which is only fatal
+/// when the listener itself is broken. When out of file descriptors or
+/// memory, this waits a little first so that connections can close.
+pub(crate) fn accept_can_continue(err: &io::Error) -> bool {
+    match err.kind() {
+        io::ErrorKind::ConnectionAborted
+        | io::ErrorKind::ConnectionReset
+        | io::ErrorKind::Interrupted
+        | io::ErrorKind::TimedOut
+        | io::ErrorKind::WouldBlock => true,
+        _ if out_of_resources(err) => {
+            thread::sleep(Duration::from_millis(100));
+            true
+        }
+        _ => false,
+    }
+}
+
+#[cfg(unix)]
+fn out_of_resources(err: &io::Error) -> bool {
+    matches!(
+        err.raw_os_error(),
+        Some(libc::EMFILE | libc::ENFILE | libc::ENOBUFS | libc::ENOMEM)
+    )
+}
+
+#[cfg(not(unix))]
+fn out_of_resources(err: &io::Error) -> bool {
+    err.kind() == io::ErrorKind::OutOfMemory
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn connections_are_capped() {
+        use std::sync::mpsc;
+
+        let (accepted, rx) = mpsc::channel();
+        let (release, released) = mpsc::channel::<()>();
+        let released = Arc::new(Mutex::new(released));
+        let mut next = 0;
+        thread::spawn(move || {
+            serve_connections(
+                2,
+                || {
+                    next += 1;
+                    // Ends the loop once the test is over.
+                    accepted.send(next).map_err(io::Error::other)?;
+                    Ok(next)
+                },
+                move |_| {
+                    let _ = released.lock().expect("locking").recv();
+                },
+            )
+        });
+
+        assert_eq!(rx.recv().ok(), Some(1));
+        assert_eq!(rx.recv().ok(), Some(2));
+        assert!(rx.recv_timeout(Duration::from_millis(100)).is_err());
+        release.send(()).expect("closing a connection");
+        assert_eq!(rx.recv_timeout(Duration::from_secs(5)).ok(), Some(3));
+    }
+
+    #[test]
+    fn accept_errors() {
+        assert!(accept_can_continue(
+            &io::ErrorKind::ConnectionAborted.into()
+        ));
+        #[cfg(unix)]
+        assert!(accept_can_continue(&io::Error::from_raw_os_error(
+            libc::EMFILE
+        )));
+        assert!(!accept_can_continue(&io::ErrorKind::InvalidInput.into()));
+    }
+}
@@ -0,0 +1,103 @@
+//! Mapping of parsed messages to the [OpenTelemetry log data model](https://opentelemetry.io/docs/specs/otel/logs/data-model/).
+
+use crate::{
+    parser::{datetime::UnixTimestamp, structured_data::NestedStructuredData, StructuredData},
+    Message,
+};
+use serde::{Serialize, Serializer};
+
+/// The `SeverityNumber` of each syslog severity, from `FATAL4` for Emergency
+/// down to `DEBUG` for Debug.
+const SEVERITY_NUMBERS: [u8; 8] = [24, 23, 22, 17, 13, 10, 9, 5];
+
+/// Serializes a message as an OpenTelemetry `LogRecord`:
+///
+/// - the timestamp as `Timestamp`, in nanoseconds since the epoch,
+/// - severity as `SeverityNumber` and `SeverityText`,
+/// - MSG as `Body`,
+/// - facility, version, APP-NAME, PROCID, MSGID and SD (as nested
+///   `structured_data.<id>.<name>` maps) as `Attributes`, using the names of
+///   the collector's syslog receiver,
+/// - HOSTNAME as the `host.name` of `Resource`.
+///
+/// Built by [`Message::to_otel_log_record`].
+pub struct OtelLogRecord<'m, 'a, T, M> {
+    message: &'m Message<'a, T, Vec<StructuredData<'a>>, M>,
+}
+
+impl<'m, 'a, T, M> OtelLogRecord<'m, 'a, T, M> {
+    pub fn new(message: &'m Message<'a, T, Vec<StructuredData<'a>>, M>) -> Self {
+        Self { message }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct Record<'m> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timestamp: Option<u64>,
+    severity_number: u8,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    severity_text: Option<&'static str>,
+    body: &'m str,
+    attributes: Attributes<'m>,
+    resource: Resource<'m>,
+}
+
+#[derive(Serialize)]
+struct Attributes<'m> {
+    facility: u8,
+    priority: u16,
+    version: u8,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    appname: Option<&'m str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    proc_id: Option<&'m str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    msg_id: Option<&'m str>,
+    #[serde(skip_serializing_if = "NestedStructuredData::is_empty")]
+    structured_data: NestedStructuredData<'m, 'm>,
+}
+
+#[derive(Serialize)]
+struct Resource<'m> {
+    #[serde(rename = "host.name", skip_serializing_if = "Option::is_none")]
+    host_name: Option<&'m str>,
+}
+
+impl<T, M> Serialize for OtelLogRecord<'_, '_, T, M>
+where
+    T: UnixTimestamp,
+    M: AsRef<str>,
+{
+    fn serialize<Z: Serializer>(&self, serializer: Z) -> Result<Z::Ok, Z::Error> {
+        let message = self.message;
+
+        let record = Record {
+            timestamp: message
+                .timestamp
+                .unix_nanos()
+                .and_then(|nanos| u64::try_from(nanos).ok()),
+            severity_number: SEVERITY_NUMBERS
+                .get(usize::from(message.severity))
+                .copied()
+                .unwrap_or_default(),
+            severity_text: message.severity_name(),
+            body: message.msg.as_ref(),
+            attributes: Attributes {
+                facility: message.facility,
+                priority: u16::from(message.facility) * 8 + u16::from(message.severity),
+                version: message.version,
+                appname: message.app_name,
+                proc_id: message.proc_id,
+                msg_id: message.msg_id,
+                structured_data: NestedStructuredData(&message.structured_data),
+            },
+            resource: Resource {
+                host_name: message.hostname,
+            },
+        };
+
+        record.serialize(serializer)
+    }
+}
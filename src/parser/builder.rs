@@ -0,0 +1,132 @@
+use super::{msg::Raw, Parsed, StructuredData};
+use crate::{Error, MsgParser, PartParser};
+use std::marker::PhantomData;
+
+/// A syslog parser built from TIMESTAMP, SD and MSG parser instances, for
+/// parsers that need configuration. Build it once and reuse it:
+///
+/// ```
+/// use rsyslog::{parser::msg::Logfmt, Parser};
+///
+/// let parser = Parser::new().msg(Logfmt::with_keys(&["status"]));
+/// let message = parser.parse("<1>1 - - - - - - at=info status=200").unwrap();
+///
+/// assert_eq!(message.msg.get("status"), Some("200"));
+/// ```
+///
+/// By default it parses the same way as the default `Message`, and any
+/// `ParsePart`/`ParseMsg` type `P` can be plugged in as `PhantomData::<P>`.
+#[derive(Debug, Clone, Copy)]
+pub struct Parser<
+    T = PhantomData<Option<&'static str>>,
+    S = PhantomData<Vec<StructuredData<'static>>>,
+    M = PhantomData<Raw<'static>>,
+> {
+    timestamp: T,
+    structured_data: S,
+    msg: M,
+}
+
+impl Parser {
+    pub fn new() -> Self {
+        Self {
+            timestamp: PhantomData,
+            structured_data: PhantomData,
+            msg: PhantomData,
+        }
+    }
+}
+
+impl Default for Parser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, S, M> Parser<T, S, M> {
+    pub fn timestamp<U>(self, timestamp: U) -> Parser<U, S, M> {
+        Parser {
+            timestamp,
+            structured_data: self.structured_data,
+            msg: self.msg,
+        }
+    }
+
+    pub fn structured_data<U>(self, structured_data: U) -> Parser<T, U, M> {
+        Parser {
+            timestamp: self.timestamp,
+            structured_data,
+            msg: self.msg,
+        }
+    }
+
+    pub fn msg<U>(self, msg: U) -> Parser<T, S, U> {
+        Parser {
+            timestamp: self.timestamp,
+            structured_data: self.structured_data,
+            msg,
+        }
+    }
+
+    pub fn parse<'a>(&self, msg: &'a str) -> Result<Parsed<'a, T, S, M>, Error<'a>>
+    where
+        T: PartParser<'a>,
+        S: PartParser<'a>,
+        M: MsgParser<'a>,
+    {
+        self.parse_with_rem(msg).map(|tuple| tuple.1)
+    }
+
+    pub fn parse_with_rem<'a>(
+        &self,
+        msg: &'a str,
+    ) -> Result<(&'a str, Parsed<'a, T, S, M>), Error<'a>>
+    where
+        T: PartParser<'a>,
+        S: PartParser<'a>,
+        M: MsgParser<'a>,
+    {
+        super::parse_with(msg, &self.timestamp, &self.structured_data, &self.msg)
+            .map_err(|e| e.into_detailed_with(msg))
+    }
+
+    pub fn iter<'a>(&self, msg: &'a str) -> ParserIter<'a, '_, T, S, M> {
+        ParserIter {
+            rem: msg,
+            parser: self,
+        }
+    }
+}
+
+/// Iterates over the messages of an input with a [`Parser`], see [`Parser::iter`].
+pub struct ParserIter<'a, 'p, T, S, M> {
+    rem: &'a str,
+    parser: &'p Parser<T, S, M>,
+}
+
+impl<'a, T, S, M> Iterator for ParserIter<'a, '_, T, S, M>
+where
+    T: PartParser<'a>,
+    S: PartParser<'a>,
+    M: MsgParser<'a>,
+{
+    type Item = Result<Parsed<'a, T, S, M>, Error<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.rem.is_empty() {
+            return None;
+        }
+
+        match self.parser.parse_with_rem(self.rem) {
+            Err(err) => {
+                self.rem = "";
+                Some(Err(err))
+            }
+            Ok((rem, msg)) => {
+                self.rem = rem;
+
+                Some(Ok(msg))
+            }
+        }
+    }
+}
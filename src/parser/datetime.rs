@@ -27,3 +27,201 @@ impl<'a> ParsePart<'a> for Option<&'a str> {
         }
     }
 }
+
+/// A TIMESTAMP that can be converted to a point in time, for the output
+/// mappings (GELF, ECS, OpenTelemetry, Arrow).
+pub trait UnixTimestamp {
+    /// Nanoseconds since the epoch.
+    fn unix_nanos(&self) -> Option<i128>;
+}
+
+/// Only RFC 3339 timestamps are converted.
+impl UnixTimestamp for Option<&str> {
+    fn unix_nanos(&self) -> Option<i128> {
+        self.and_then(rfc3339_unix_nanos)
+    }
+}
+
+/// Microseconds since the epoch, as produced by
+/// [`JournalEntry::to_message`](crate::journal::JournalEntry::to_message).
+impl UnixTimestamp for Option<u64> {
+    fn unix_nanos(&self) -> Option<i128> {
+        self.map(|micros| i128::from(micros) * 1_000)
+    }
+}
+
+#[cfg(feature = "chrono-timestamp")]
+impl UnixTimestamp for Option<crate::parser::DateTime> {
+    fn unix_nanos(&self) -> Option<i128> {
+        self.map(|dt| {
+            i128::from(dt.timestamp()) * 1_000_000_000 + i128::from(dt.timestamp_subsec_nanos())
+        })
+    }
+}
+
+impl UnixTimestamp for crate::parser::Skip {
+    fn unix_nanos(&self) -> Option<i128> {
+        None
+    }
+}
+
+/// Converts `2016-02-21T04:32:57.853+02:00` to nanoseconds since the epoch.
+fn rfc3339_unix_nanos(timestamp: &str) -> Option<i128> {
+    let number = |s: &str| -> Option<i64> {
+        if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        s.parse().ok()
+    };
+
+    let (date, time) = timestamp.split_once(['T', 't'])?;
+    let mut date = date.splitn(3, '-');
+    let (year, month, day) = (
+        number(date.next()?)?,
+        number(date.next()?)?,
+        number(date.next()?)?,
+    );
+    if !(0..=9999).contains(&year) || !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+
+    let (time, offset) = time.split_at(time.find(['Z', 'z', '+', '-'])?);
+    let offset = match offset {
+        "Z" | "z" => 0,
+        _ => {
+            let (hours, minutes) = offset[1..].split_once(':')?;
+            let (hours, minutes) = (number(hours)?, number(minutes)?);
+            if hours > 23 || minutes > 59 {
+                return None;
+            }
+            let seconds = hours.checked_mul(3600)?.checked_add(minutes * 60)?;
+            if offset.starts_with('-') {
+                -seconds
+            } else {
+                seconds
+            }
+        }
+    };
+
+    let (time, nanos) = match time.split_once('.') {
+        Some((time, fraction)) => {
+            // Checked before slicing, which would panic inside a multibyte
+            // character.
+            if !fraction.bytes().all(|b| b.is_ascii_digit()) {
+                return None;
+            }
+            let digits = &fraction[..fraction.len().min(9)];
+            let scale = 10_i64.pow(9 - digits.len() as u32);
+            (time, number(digits)? * scale)
+        }
+        None => (time, 0),
+    };
+    let mut time = time.splitn(3, ':');
+    let (hour, minute, second) = (
+        number(time.next()?)?,
+        number(time.next()?)?,
+        number(time.next()?)?,
+    );
+    // 60 for leap seconds.
+    if hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+
+    let seconds = days_from_civil(year, month, day)
+        .checked_mul(86_400)?
+        .checked_add(hour * 3600 + minute * 60 + second)?
+        .checked_sub(offset)?;
+    Some(i128::from(seconds) * 1_000_000_000 + i128::from(nanos))
+}
+
+/// Formats nanoseconds since the epoch as `2016-02-21T04:32:57.853000000Z`.
+pub fn format_rfc3339(unix_nanos: i128) -> String {
+    let seconds = unix_nanos.div_euclid(1_000_000_000) as i64;
+    let nanos = unix_nanos.rem_euclid(1_000_000_000);
+    let (year, month, day) = civil_from_days(seconds.div_euclid(86_400));
+    let time = seconds.rem_euclid(86_400);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:09}Z",
+        year,
+        month,
+        day,
+        time / 3600,
+        time % 3600 / 60,
+        time % 60,
+        nanos
+    )
+}
+
+// Conversions between proleptic Gregorian dates and days since the epoch, from
+// http://howardhinnant.github.io/date_algorithms.html
+
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146_097 + day_of_era - 719_468
+}
+
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rfc3339() {
+        assert_eq!(
+            rfc3339_unix_nanos("2016-02-21T04:32:57.5Z"),
+            Some(1_456_029_177_500_000_000)
+        );
+        assert_eq!(
+            rfc3339_unix_nanos("2016-02-21T06:32:57+02:00"),
+            Some(1_456_029_177_000_000_000)
+        );
+        assert_eq!(
+            rfc3339_unix_nanos("1969-12-31T23:59:59Z"),
+            Some(-1_000_000_000)
+        );
+        assert_eq!(rfc3339_unix_nanos("Feb 21 04:32:57"), None);
+    }
+
+    #[test]
+    fn rfc3339_invalid() {
+        // A multibyte character across the 9th byte of the fraction.
+        assert_eq!(rfc3339_unix_nanos("2016-02-21T04:32:57.12345678éZ"), None);
+        assert_eq!(
+            rfc3339_unix_nanos("2016-02-21T04:32:57+9999999999999999:00"),
+            None
+        );
+        assert_eq!(rfc3339_unix_nanos("2016-02-21T04:32:57+24:00"), None);
+        assert_eq!(rfc3339_unix_nanos("2016-02-21T04:32:57-00:60"), None);
+        assert_eq!(rfc3339_unix_nanos("2016-02-21T99:32:57Z"), None);
+        assert_eq!(rfc3339_unix_nanos("9999999999999999-02-21T04:32:57Z"), None);
+    }
+
+    #[test]
+    fn format() {
+        assert_eq!(
+            format_rfc3339(1_456_029_177_853_000_000),
+            "2016-02-21T04:32:57.853000000Z"
+        );
+        assert_eq!(format_rfc3339(-1), "1969-12-31T23:59:59.999999999Z");
+    }
+}
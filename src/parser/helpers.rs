@@ -1,4 +1,13 @@
-use nom::{error::VerboseError, number::complete as number};
+use crate::{Error, NomRes, ParseValue};
+use nom::{
+    branch::alt,
+    bytes::complete::{tag, take_till, take_till1, take_until},
+    character::complete::{space0, space1},
+    error::VerboseError,
+    multi::separated_list0,
+    number::complete as number,
+    sequence::{delimited, separated_pair},
+};
 
 pub fn parse_u8(part: &str) -> Result<u8, nom::Err<VerboseError<&str>>> {
     let (_, part) = number::double(part)?;
@@ -19,3 +28,78 @@ pub fn parse_u64(part: &str) -> Result<u64, nom::Err<VerboseError<&str>>> {
     let (_, part) = number::double(part)?;
     Ok(part as u64)
 }
+
+/// Splits `msg` at the first line ending, returning the remainder (which
+/// still starts with the line ending) and the line itself.
+pub fn split_line(msg: &str) -> (&str, &str) {
+    let end = msg.find('\n').unwrap_or(msg.len());
+    let line = &msg[..end];
+    match line.strip_suffix('\r') {
+        Some(line) => (&msg[line.len()..], line),
+        None => (&msg[end..], line),
+    }
+}
+
+/// Splits a line of space separated `key=value` pairs, where values may be
+/// double quoted. `context` prefixes the error when the line has anything else.
+pub fn parse_pairs<'a>(line: &'a str, context: &str) -> Result<Vec<(&'a str, &'a str)>, Error<'a>> {
+    let (trailing, pairs) = parse_pairs_inner(line)?;
+    if !trailing.is_empty() {
+        return Err(Error::Custom(format!(
+            "{}: unexpected input `{}`",
+            context, trailing
+        )));
+    }
+
+    Ok(pairs)
+}
+
+pub fn required<'a>(
+    context: &str,
+    key: &str,
+    value: Option<&'a str>,
+) -> Result<&'a str, Error<'a>> {
+    value.ok_or_else(|| Error::Custom(format!("{}: missing required key `{}`", context, key)))
+}
+
+/// Converts the value found for `key` (if any) with [`ParseValue`], after
+/// removing `suffix` from it, e.g. the `ms` of `service=97ms`.
+pub fn parse_field<'a, T: ParseValue<'a>>(
+    context: &str,
+    key: &str,
+    value: Option<&'a str>,
+    suffix: Option<&str>,
+) -> Result<T, Error<'a>> {
+    match value {
+        Some(value) => {
+            let value = suffix
+                .and_then(|suffix| value.strip_suffix(suffix))
+                .unwrap_or(value);
+            T::parse_value(value).map_err(|e| match e {
+                Error::Custom(e) => Error::Custom(format!("{}: key `{}`: {}", context, key, e)),
+                e => e,
+            })
+        }
+        None => T::missing()
+            .ok_or_else(|| Error::Custom(format!("{}: missing required key `{}`", context, key))),
+    }
+}
+
+fn parse_pairs_inner(part: &str) -> NomRes<&str, Vec<(&str, &str)>> {
+    let (rem, _) = space0(part)?;
+    let (rem, pairs) = separated_list0(space1, parse_pair)(rem)?;
+    let (rem, _) = space0(rem)?;
+
+    Ok((rem, pairs))
+}
+
+fn parse_pair(part: &str) -> NomRes<&str, (&str, &str)> {
+    separated_pair(
+        take_till1(|c| c == '=' || c == ' '),
+        tag("="),
+        alt((
+            delimited(tag("\""), take_until("\""), tag("\"")),
+            take_till(|c| c == ' '),
+        )),
+    )(part)
+}
@@ -1,3 +1,4 @@
+mod builder;
 pub mod datetime;
 pub mod helpers;
 pub mod msg;
@@ -6,22 +7,46 @@ pub mod structured_data;
 
 #[cfg(feature = "chrono-timestamp")]
 pub type DateTime = chrono::DateTime<chrono::FixedOffset>;
+pub use builder::{Parser, ParserIter};
 pub use skip::Skip;
 pub use structured_data::{SdParam, StructuredData};
 
-use crate::{Error, Message, NomRes, Originator, ParseMsg, ParsePart};
+use crate::{Error, Message, MsgParser, NomRes, Originator, ParseMsg, ParsePart, PartParser};
 use nom::{
     bytes::complete::{tag, take_until},
     character::complete::{digit1, space0},
 };
+use std::marker::PhantomData;
+
+/// The `Message` produced by the given TIMESTAMP, SD and MSG parser instances.
+pub type Parsed<'a, T, S, M> = Message<
+    'a,
+    <T as PartParser<'a>>::Output,
+    <S as PartParser<'a>>::Output,
+    <M as MsgParser<'a>>::Output,
+>;
 
 pub(crate) fn parse<'a, T: ParsePart<'a>, S: ParsePart<'a>, M: ParseMsg<'a>>(
     msg: &'a str,
 ) -> Result<(&'a str, Message<'a, T, S, M>), Error<'a>> {
+    parse_with(msg, &PhantomData::<T>, &PhantomData::<S>, &PhantomData::<M>)
+}
+
+pub(crate) fn parse_with<'a, T, S, M>(
+    msg: &'a str,
+    timestamp_parser: &T,
+    structured_data_parser: &S,
+    msg_parser: &M,
+) -> Result<(&'a str, Parsed<'a, T, S, M>), Error<'a>>
+where
+    T: PartParser<'a>,
+    S: PartParser<'a>,
+    M: MsgParser<'a>,
+{
     let (rem, pri) = parse_pri(msg)?;
     let (rem, version) = parse_version(rem)?;
     let (rem, _) = space0(rem)?;
-    let (rem, timestamp) = T::parse(rem)?;
+    let (rem, timestamp) = timestamp_parser.parse(rem)?;
     let (rem, _) = space0(rem)?;
     let (rem, hostname) = parse_part(rem)?;
     let (rem, _) = space0(rem)?;
@@ -31,7 +56,7 @@ pub(crate) fn parse<'a, T: ParsePart<'a>, S: ParsePart<'a>, M: ParseMsg<'a>>(
     let (rem, _) = space0(rem)?;
     let (rem, msg_id) = parse_part(rem)?;
     let (rem, _) = space0(rem)?;
-    let (rem, structured_data) = S::parse(rem)?;
+    let (rem, structured_data) = structured_data_parser.parse(rem)?;
     let (rem, _) = space0(rem)?;
 
     let partial_msg = Originator {
@@ -41,7 +66,7 @@ pub(crate) fn parse<'a, T: ParsePart<'a>, S: ParsePart<'a>, M: ParseMsg<'a>>(
         msg_id,
     };
 
-    let (rem, msg) = M::parse(rem, &partial_msg)?;
+    let (rem, msg) = msg_parser.parse(rem, &partial_msg)?;
 
     let message = crate::Message {
         facility: pri >> 3,
@@ -59,7 +84,7 @@ pub(crate) fn parse<'a, T: ParsePart<'a>, S: ParsePart<'a>, M: ParseMsg<'a>>(
     Ok((rem, message))
 }
 
-fn parse_pri(part: &str) -> NomRes<&str, u8> {
+pub(crate) fn parse_pri(part: &str) -> NomRes<&str, u8> {
     let (rem, _) = take_until("<")(part)?;
     let (rem, _) = tag("<")(rem)?;
 
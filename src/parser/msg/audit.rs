@@ -0,0 +1,327 @@
+use crate::{Error, Message, NomRes, Originator, ParseMsg};
+use nom::{
+    branch::alt,
+    bytes::complete::{tag, take_till, take_till1, take_until},
+    character::complete::{digit1, line_ending, not_line_ending, space0, space1},
+    combinator::{map, opt, rest},
+    sequence::{delimited, separated_pair, terminated},
+};
+use std::borrow::Cow;
+
+/// The fields auditd hex encodes when their value contains spaces, quotes or
+/// control characters. Their values are only quoted when left as they are.
+const ENCODED_KEYS: &[&str] = &[
+    "acct",
+    "cmd",
+    "comm",
+    "cwd",
+    "data",
+    "dir",
+    "exe",
+    "file",
+    "key",
+    "name",
+    "new-disk",
+    "ocomm",
+    "old-disk",
+    "path",
+    "proctitle",
+    "vm",
+    "watch",
+];
+
+/// An audit record, as logged by auditd or the kernel:
+/// `type=SYSCALL msg=audit(1364481363.243:24287): arch=c000003e syscall=2 comm="cat"`.
+///
+/// Hex encoded values (such as `proctitle=636174002F6574632F706173737764`) are
+/// decoded, with the NUL bytes separating arguments replaced by spaces, and
+/// quotes are removed from the other values. Text that isn't `key=value`, like
+/// the `avc:  denied  { read } for` of AVC records, is skipped.
+#[derive(Debug, Eq, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Audit<'a> {
+    pub record_type: &'a str,
+    pub id: AuditId,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub fields: Vec<(&'a str, Cow<'a, str>)>,
+}
+
+/// The `audit(seconds.millis:serial)` stamp shared by the records of an event.
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AuditId {
+    pub seconds: u64,
+    pub millis: u16,
+    pub serial: u64,
+}
+
+/// The records of one audit event, see [`AuditEvents`].
+#[derive(Debug, Eq, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AuditEvent<'a> {
+    pub id: AuditId,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub records: Vec<Audit<'a>>,
+}
+
+impl<'a> ParseMsg<'a> for Audit<'a> {
+    fn parse(msg: &'a str, _: &Originator<'a>) -> Result<(&'a str, Self), Error<'a>> {
+        let (rem, line) = alt((terminated(not_line_ending, line_ending), rest))(msg)?;
+        let invalid = || Error::Custom(format!("audit: invalid record `{}`", line));
+        let (fields, (record_type, seconds, millis, serial)) =
+            parse_header(line).map_err(|_| invalid())?;
+        let id = AuditId {
+            seconds: seconds.parse().map_err(|_| invalid())?,
+            millis: millis.parse().map_err(|_| invalid())?,
+            serial: serial.parse().map_err(|_| invalid())?,
+        };
+
+        let mut audit = Self {
+            record_type,
+            id,
+            fields: Vec::new(),
+        };
+        let mut fields = fields;
+        while !fields.is_empty() {
+            match parse_field(fields) {
+                Ok((next, (key, value, quoted))) => {
+                    audit.fields.push((key, decode(key, value, quoted)));
+                    fields = next;
+                }
+                Err(_) => {
+                    let (next, _) = skip_word(fields)?;
+                    fields = next;
+                }
+            }
+        }
+
+        Ok((rem, audit))
+    }
+}
+
+impl<'a> Audit<'a> {
+    /// The value of the first `key` field.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map(|(_, value)| value.as_ref())
+    }
+}
+
+/// Parses `type=TYPE msg=audit(...): `, also accepting the kernel's
+/// `audit: type=1300 audit(...): ` form.
+fn parse_header(line: &str) -> NomRes<&str, (&str, &str, &str, &str)> {
+    let (rem, _) = opt(tag("audit: "))(line)?;
+    let (rem, _) = tag("type=")(rem)?;
+    let (rem, record_type) = take_till1(|c| c == ' ')(rem)?;
+    let (rem, _) = space1(rem)?;
+    let (rem, _) = opt(tag("msg="))(rem)?;
+    let (rem, _) = tag("audit(")(rem)?;
+    let (rem, seconds) = digit1(rem)?;
+    let (rem, _) = tag(".")(rem)?;
+    let (rem, millis) = digit1(rem)?;
+    let (rem, _) = tag(":")(rem)?;
+    let (rem, serial) = digit1(rem)?;
+    let (rem, _) = tag("):")(rem)?;
+    let (rem, _) = space0(rem)?;
+
+    Ok((rem, (record_type, seconds, millis, serial)))
+}
+
+/// Parses `key=value`, telling whether the value was quoted.
+fn parse_field(part: &str) -> NomRes<&str, (&str, &str, bool)> {
+    let (rem, (key, (value, quoted))) = separated_pair(
+        take_till1(|c| c == '=' || c == ' '),
+        tag("="),
+        alt((
+            map(delimited(tag("\""), take_until("\""), tag("\"")), |v| {
+                (v, true)
+            }),
+            map(delimited(tag("'"), take_until("'"), tag("'")), |v| {
+                (v, true)
+            }),
+            map(take_till(|c| c == ' '), |v| (v, false)),
+        )),
+    )(part)?;
+    let (rem, _) = space0(rem)?;
+
+    Ok((rem, (key, value, quoted)))
+}
+
+fn skip_word(part: &str) -> NomRes<&str, &str> {
+    let (rem, word) = take_till(|c| c == ' ')(part)?;
+    let (rem, _) = space0(rem)?;
+
+    Ok((rem, word))
+}
+
+/// Decodes unquoted hex values of [`ENCODED_KEYS`], leaving quoted values,
+/// `(null)` and the like untouched.
+fn decode<'a>(key: &str, value: &'a str, quoted: bool) -> Cow<'a, str> {
+    let pairs = value.as_bytes().chunks_exact(2);
+    let is_hex = !quoted
+        && !value.is_empty()
+        && pairs.remainder().is_empty()
+        && value.bytes().all(|b| b.is_ascii_hexdigit());
+    if !is_hex || !ENCODED_KEYS.contains(&key) {
+        return Cow::Borrowed(value);
+    }
+
+    let digit = |b: u8| char::from(b).to_digit(16).unwrap_or_default() as u8;
+    let bytes = pairs
+        .map(|pair| digit(pair[0]) << 4 | digit(pair[1]))
+        .map(|b| if b == 0 { b' ' } else { b })
+        .collect::<Vec<_>>();
+
+    Cow::Owned(String::from_utf8_lossy(&bytes).trim_end().to_string())
+}
+
+/// Groups the consecutive audit records yielded by a message iterator (such
+/// as `MessageIter`) that share an [`AuditId`] into one event, keeping the
+/// syslog header of the first record. An `EOE` record ends its event without
+/// being part of it; otherwise an event ends when a record of another event
+/// shows up. Errors are passed through.
+pub struct AuditEvents<'a, I, T, S> {
+    iter: I,
+    pending: Option<Message<'a, T, S, AuditEvent<'a>>>,
+}
+
+impl<'a, I, T, S> AuditEvents<'a, I, T, S> {
+    pub fn new(iter: I) -> Self {
+        Self {
+            iter,
+            pending: None,
+        }
+    }
+}
+
+impl<'a, I, T, S> Iterator for AuditEvents<'a, I, T, S>
+where
+    I: Iterator<Item = Result<Message<'a, T, S, Audit<'a>>, Error<'a>>>,
+{
+    type Item = Result<Message<'a, T, S, AuditEvent<'a>>, Error<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let message = match self.iter.next() {
+                Some(Ok(message)) => message,
+                Some(Err(err)) => return Some(Err(err)),
+                None => return self.pending.take().map(Ok),
+            };
+
+            let id = message.msg.id;
+            if message.msg.record_type == "EOE" {
+                match self.pending.take() {
+                    Some(event) => return Some(Ok(event)),
+                    None => continue,
+                }
+            }
+
+            let event = message.map_msg(|record| AuditEvent {
+                id,
+                records: vec![record],
+            });
+            match self.pending.take() {
+                Some(mut pending) if pending.msg.id == id => {
+                    pending.msg.records.extend(event.msg.records);
+                    self.pending = Some(pending);
+                }
+                Some(pending) => {
+                    self.pending = Some(event);
+                    return Some(Ok(pending));
+                }
+                None => self.pending = Some(event),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ORIGINATOR: Originator = Originator {
+        hostname: None,
+        app_name: None,
+        proc_id: None,
+        msg_id: None,
+    };
+
+    #[test]
+    fn syscall() {
+        let msg = concat!(
+            r#"type=SYSCALL msg=audit(1364481363.243:24287): arch=c000003e syscall=2 "#,
+            r#"success=no exit=-13 comm="cat" exe=2F7573722F62696E2F6361 key=(null)"#,
+            "\nnext"
+        );
+        let (rem, audit) = Audit::parse(msg, &ORIGINATOR).expect("parsing audit record");
+
+        assert_eq!(rem, "next");
+        assert_eq!(audit.record_type, "SYSCALL");
+        assert_eq!(
+            audit.id,
+            AuditId {
+                seconds: 1364481363,
+                millis: 243,
+                serial: 24287
+            }
+        );
+        assert_eq!(audit.get("arch"), Some("c000003e"));
+        assert_eq!(audit.get("exit"), Some("-13"));
+        assert_eq!(audit.get("comm"), Some("cat"));
+        assert_eq!(audit.get("exe"), Some("/usr/bin/ca"));
+        assert_eq!(audit.get("key"), Some("(null)"));
+    }
+
+    #[test]
+    fn kernel_avc() {
+        let msg = concat!(
+            r#"audit: type=1400 audit(1364481363.243:24288): avc:  denied  { read } for  "#,
+            r#"pid=1 comm=2F62696E2F6361742066696C65 msg='op=open res=failed'"#
+        );
+        let (_, audit) = Audit::parse(msg, &ORIGINATOR).expect("parsing audit record");
+
+        assert_eq!(audit.record_type, "1400");
+        assert_eq!(
+            audit.fields,
+            vec![
+                ("pid", "1".into()),
+                ("comm", "/bin/cat file".into()),
+                ("msg", "op=open res=failed".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn quoted_hex_values() {
+        let msg = concat!(
+            r#"type=SYSCALL msg=audit(1364481363.243:24287): comm="bc" exe="/usr/bin/bc" "#,
+            r#"key="cafe" proctitle=6263"#
+        );
+        let (_, audit) = Audit::parse(msg, &ORIGINATOR).expect("parsing audit record");
+
+        assert_eq!(audit.get("comm"), Some("bc"));
+        assert_eq!(audit.get("exe"), Some("/usr/bin/bc"));
+        assert_eq!(audit.get("key"), Some("cafe"));
+        assert_eq!(audit.get("proctitle"), Some("bc"));
+    }
+
+    #[test]
+    fn proctitle() {
+        let msg = "type=PROCTITLE msg=audit(1.001:2): proctitle=636174002F6574632F706173737764";
+        let (_, audit) = Audit::parse(msg, &ORIGINATOR).expect("parsing audit record");
+
+        assert_eq!(audit.get("proctitle"), Some("cat /etc/passwd"));
+    }
+
+    #[test]
+    fn invalid_audit() {
+        assert_eq!(
+            Audit::parse("type=SYSCALL pid=1", &ORIGINATOR),
+            Err(Error::Custom(
+                "audit: invalid record `type=SYSCALL pid=1`".into()
+            ))
+        );
+    }
+}
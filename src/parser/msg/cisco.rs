@@ -0,0 +1,160 @@
+use crate::{Error, Originator, ParseMsg};
+use nom::{
+    branch::alt,
+    character::complete::{line_ending, not_line_ending},
+    combinator::rest,
+    sequence::terminated,
+};
+use std::borrow::Cow;
+
+/// A Cisco IOS, NX-OS or ASA message, such as
+/// `000123: *Mar  1 18:46:11.123 UTC: %LINK-3-UPDOWN: Interface Gi0/1, changed state to down`
+/// or `%ASA-6-302013: Built inbound TCP connection 1`.
+///
+/// The sequence number and timestamp IOS may put before the `%` are optional,
+/// and the timestamp is kept as written, including the `*` or `.` marking an
+/// unsynchronized clock. The facility may itself contain dashes
+/// (`%PM-SP-4-ERR_DISABLE`).
+#[derive(Debug, Eq, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CiscoMnemonic<'a> {
+    pub sequence: Option<u64>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub timestamp: Option<&'a str>,
+    pub facility: &'a str,
+    /// The vendor severity, on the same 0 (emergency) to 7 (debug) scale as
+    /// `Message::severity`.
+    pub severity: u8,
+    pub mnemonic: &'a str,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub text: Cow<'a, str>,
+}
+
+impl<'a> ParseMsg<'a> for CiscoMnemonic<'a> {
+    fn parse(msg: &'a str, _: &Originator<'a>) -> Result<(&'a str, Self), Error<'a>> {
+        let (rem, line) = alt((terminated(not_line_ending, line_ending), rest))(msg)?;
+        let invalid = || Error::Custom(format!("cisco: invalid message `{}`", line));
+
+        let start = line.find('%').ok_or_else(invalid)?;
+        let (prefix, line) = line.split_at(start);
+        let (code, text) = line[1..].split_once(':').ok_or_else(invalid)?;
+
+        let mut parts = code.rsplitn(3, '-');
+        let mnemonic = parts.next().filter(|m| !m.is_empty()).ok_or_else(invalid)?;
+        let severity = parts
+            .next()
+            .and_then(|s| s.parse().ok())
+            .filter(|s| *s <= 7)
+            .ok_or_else(invalid)?;
+        let facility = parts.next().filter(|f| !f.is_empty()).ok_or_else(invalid)?;
+
+        let (sequence, timestamp) = parse_prefix(prefix);
+
+        let cisco = Self {
+            sequence,
+            timestamp,
+            facility,
+            severity,
+            mnemonic,
+            text: Cow::Borrowed(text.trim_start()),
+        };
+
+        Ok((rem, cisco))
+    }
+}
+
+/// Splits `000123: *Mar  1 18:46:11.123 UTC: ` into its sequence number and
+/// timestamp.
+fn parse_prefix(prefix: &str) -> (Option<u64>, Option<&str>) {
+    let prefix = prefix.trim();
+    let prefix = prefix.strip_suffix(':').unwrap_or(prefix);
+
+    let (sequence, timestamp) = match prefix.split_once(':') {
+        Some((sequence, timestamp)) if sequence.bytes().all(|b| b.is_ascii_digit()) => {
+            (sequence.parse().ok(), timestamp.trim_start())
+        }
+        _ => match prefix.parse() {
+            Ok(sequence) => (Some(sequence), ""),
+            Err(_) => (None, prefix),
+        },
+    };
+
+    (sequence, Some(timestamp).filter(|t| !t.is_empty()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ORIGINATOR: Originator = Originator {
+        hostname: None,
+        app_name: None,
+        proc_id: None,
+        msg_id: None,
+    };
+
+    #[test]
+    fn ios() {
+        let msg = concat!(
+            "000123: *Mar  1 18:46:11.123 UTC: %LINK-3-UPDOWN: Interface GigabitEthernet0/1, ",
+            "changed state to down\nnext"
+        );
+
+        assert_eq!(
+            CiscoMnemonic::parse(msg, &ORIGINATOR),
+            Ok((
+                "next",
+                CiscoMnemonic {
+                    sequence: Some(123),
+                    timestamp: Some("*Mar  1 18:46:11.123 UTC"),
+                    facility: "LINK",
+                    severity: 3,
+                    mnemonic: "UPDOWN",
+                    text: "Interface GigabitEthernet0/1, changed state to down".into()
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn asa() {
+        let msg = "%ASA-6-302013: Built inbound TCP connection 1 for outside:10.0.0.1/80";
+
+        assert_eq!(
+            CiscoMnemonic::parse(msg, &ORIGINATOR),
+            Ok((
+                "",
+                CiscoMnemonic {
+                    sequence: None,
+                    timestamp: None,
+                    facility: "ASA",
+                    severity: 6,
+                    mnemonic: "302013",
+                    text: "Built inbound TCP connection 1 for outside:10.0.0.1/80".into()
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn dashed_facility() {
+        let (_, cisco) =
+            CiscoMnemonic::parse("Jan  1 00:00:01: %PM-SP-4-ERR_DISABLE: bpdu", &ORIGINATOR)
+                .expect("parsing cisco message");
+
+        assert_eq!(cisco.sequence, None);
+        assert_eq!(cisco.timestamp, Some("Jan  1 00:00:01"));
+        assert_eq!(cisco.facility, "PM-SP");
+        assert_eq!(cisco.severity, 4);
+    }
+
+    #[test]
+    fn invalid_severity() {
+        assert_eq!(
+            CiscoMnemonic::parse("%SYS-9-CONFIG_I: Configured", &ORIGINATOR),
+            Err(Error::Custom(
+                "cisco: invalid message `%SYS-9-CONFIG_I: Configured`".into()
+            ))
+        );
+    }
+}
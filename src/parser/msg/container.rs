@@ -0,0 +1,107 @@
+use crate::{Error, Message};
+use std::borrow::Cow;
+
+/// The stream a container wrote a log line to.
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum LogStream {
+    Stdout,
+    Stderr,
+}
+
+impl LogStream {
+    pub(crate) fn parse(stream: &str) -> Option<Self> {
+        match stream {
+            "stdout" => Some(Self::Stdout),
+            "stderr" => Some(Self::Stderr),
+            _ => None,
+        }
+    }
+}
+
+/// A container runtime log line that may be a fragment of a longer record,
+/// implemented by [`Cri`](super::Cri) and [`DockerJson`](super::DockerJson).
+pub trait ContainerLog<'a> {
+    /// The timestamp the container runtime recorded for the line.
+    fn timestamp(&self) -> &'a str;
+    fn stream(&self) -> LogStream;
+    /// Whether more fragments of the same record follow.
+    fn is_partial(&self) -> bool;
+    fn into_log(self) -> Cow<'a, str>;
+}
+
+/// A complete container log record, possibly reassembled from partial lines.
+#[derive(Debug, Eq, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ContainerRecord<'a> {
+    /// The timestamp of the first fragment.
+    pub timestamp: &'a str,
+    pub stream: LogStream,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub log: Cow<'a, str>,
+}
+
+/// Joins the partial container log lines yielded by a message iterator (such
+/// as `MessageIter`) into complete records, keeping the syslog header of the
+/// first fragment. Fragments are tracked per stream, so interleaved stdout and
+/// stderr records are reassembled independently. Errors are passed through and
+/// fragments still pending when the iterator ends are yielded as they are.
+pub struct Reassemble<'a, I, T, S> {
+    iter: I,
+    stdout: Option<Message<'a, T, S, ContainerRecord<'a>>>,
+    stderr: Option<Message<'a, T, S, ContainerRecord<'a>>>,
+}
+
+impl<'a, I, T, S> Reassemble<'a, I, T, S> {
+    pub fn new(iter: I) -> Self {
+        Self {
+            iter,
+            stdout: None,
+            stderr: None,
+        }
+    }
+}
+
+impl<'a, I, T, S, C> Iterator for Reassemble<'a, I, T, S>
+where
+    I: Iterator<Item = Result<Message<'a, T, S, C>, Error<'a>>>,
+    C: ContainerLog<'a>,
+{
+    type Item = Result<Message<'a, T, S, ContainerRecord<'a>>, Error<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let message = match self.iter.next() {
+                Some(Ok(message)) => message,
+                Some(Err(err)) => return Some(Err(err)),
+                None => return self.stdout.take().or_else(|| self.stderr.take()).map(Ok),
+            };
+
+            let partial = message.msg.is_partial();
+            let stream = message.msg.stream();
+            let pending = match stream {
+                LogStream::Stdout => &mut self.stdout,
+                LogStream::Stderr => &mut self.stderr,
+            };
+
+            let record = match pending.take() {
+                Some(mut record) => {
+                    record.msg.log.to_mut().push_str(&message.msg.into_log());
+                    record
+                }
+                None => message.map_msg(|msg| ContainerRecord {
+                    timestamp: msg.timestamp(),
+                    stream,
+                    log: msg.into_log(),
+                }),
+            };
+
+            if partial {
+                *pending = Some(record);
+            } else {
+                return Some(Ok(record));
+            }
+        }
+    }
+}
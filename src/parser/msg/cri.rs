@@ -0,0 +1,106 @@
+use super::{ContainerLog, LogStream};
+use crate::{Error, Originator, ParseMsg};
+use nom::{
+    branch::alt,
+    character::complete::{line_ending, not_line_ending},
+    combinator::rest,
+    sequence::terminated,
+};
+use std::borrow::Cow;
+
+/// A log line in the CRI (containerd, CRI-O) format:
+/// `2024-01-01T00:00:00.000Z stdout F message`, where the `P` tag instead of
+/// `F` marks a partial line.
+#[derive(Debug, Eq, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Cri<'a> {
+    pub timestamp: &'a str,
+    pub stream: LogStream,
+    pub partial: bool,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub log: Cow<'a, str>,
+}
+
+impl<'a> ParseMsg<'a> for Cri<'a> {
+    fn parse(msg: &'a str, _: &Originator<'a>) -> Result<(&'a str, Self), Error<'a>> {
+        let (rem, line) = alt((terminated(not_line_ending, line_ending), rest))(msg)?;
+        let invalid = || Error::Custom(format!("cri: invalid log line `{}`", line));
+
+        let mut parts = line.splitn(4, ' ');
+        let timestamp = parts.next().filter(|t| !t.is_empty()).ok_or_else(invalid)?;
+        let stream = parts
+            .next()
+            .and_then(LogStream::parse)
+            .ok_or_else(invalid)?;
+        let partial = match parts.next() {
+            Some("P") => true,
+            Some("F") => false,
+            _ => return Err(invalid()),
+        };
+        let log = Cow::Borrowed(parts.next().unwrap_or(""));
+
+        let cri = Self {
+            timestamp,
+            stream,
+            partial,
+            log,
+        };
+
+        Ok((rem, cri))
+    }
+}
+
+impl<'a> ContainerLog<'a> for Cri<'a> {
+    fn timestamp(&self) -> &'a str {
+        self.timestamp
+    }
+
+    fn stream(&self) -> LogStream {
+        self.stream
+    }
+
+    fn is_partial(&self) -> bool {
+        self.partial
+    }
+
+    fn into_log(self) -> Cow<'a, str> {
+        self.log
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ORIGINATOR: Originator = Originator {
+        hostname: None,
+        app_name: None,
+        proc_id: None,
+        msg_id: None,
+    };
+
+    #[test]
+    fn cri() {
+        assert_eq!(
+            Cri::parse(
+                "2024-01-01T00:00:00.000Z stderr P half a li\nrest",
+                &ORIGINATOR
+            ),
+            Ok((
+                "rest",
+                Cri {
+                    timestamp: "2024-01-01T00:00:00.000Z",
+                    stream: LogStream::Stderr,
+                    partial: true,
+                    log: "half a li".into()
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn invalid_cri() {
+        assert!(Cri::parse("2024-01-01T00:00:00.000Z stdin F x", &ORIGINATOR).is_err());
+        assert!(Cri::parse("2024-01-01T00:00:00.000Z stdout X x", &ORIGINATOR).is_err());
+    }
+}
@@ -0,0 +1,379 @@
+use super::{ContainerLog, LogStream};
+use crate::{Error, Originator, ParseMsg};
+use std::borrow::Cow;
+
+/// A log line written by Docker's json-file logging driver:
+/// `{"log":"message\n","stream":"stdout","time":"2024-01-01T00:00:00.000000000Z"}`.
+///
+/// Docker splits long lines into several records, all but the last of which
+/// lack the trailing newline, so those are flagged as `partial`. The trailing
+/// newline is not part of `log`, which is only allocated when it contains JSON
+/// escapes. Other keys (such as `attrs`) are ignored.
+#[derive(Debug, Eq, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DockerJson<'a> {
+    pub timestamp: &'a str,
+    pub stream: LogStream,
+    pub partial: bool,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub log: Cow<'a, str>,
+}
+
+impl<'a> ParseMsg<'a> for DockerJson<'a> {
+    fn parse(msg: &'a str, _: &Originator<'a>) -> Result<(&'a str, Self), Error<'a>> {
+        let mut json = Json { input: msg };
+        let mut log = None;
+        let mut stream = None;
+        let mut timestamp = None;
+
+        json.expect('{')?;
+        if !json.eat('}') {
+            loop {
+                let key = json.string()?;
+                json.expect(':')?;
+                match key.as_ref() {
+                    "log" => log = Some(json.string()?),
+                    "stream" => stream = Some(json.string()?),
+                    "time" => timestamp = Some(json.string()?),
+                    _ => json.skip_value()?,
+                }
+                if json.eat('}') {
+                    break;
+                }
+                json.expect(',')?;
+            }
+        }
+
+        let missing = |key| Error::Custom(format!("docker json: missing `{}`", key));
+        let log = log.ok_or_else(|| missing("log"))?;
+        let stream = stream.ok_or_else(|| missing("stream"))?;
+        let stream = LogStream::parse(&stream)
+            .ok_or_else(|| Error::Custom(format!("docker json: invalid stream `{}`", stream)))?;
+        let timestamp = match timestamp.ok_or_else(|| missing("time"))? {
+            Cow::Borrowed(timestamp) => timestamp,
+            Cow::Owned(_) => return Err(json.error("escaped `time`")),
+        };
+
+        let partial = !log.ends_with('\n');
+        let log = match log {
+            Cow::Borrowed(log) => Cow::Borrowed(log.strip_suffix('\n').unwrap_or(log)),
+            Cow::Owned(mut log) => {
+                if !partial {
+                    log.pop();
+                }
+                Cow::Owned(log)
+            }
+        };
+
+        let rem = json.input.trim_start_matches([' ', '\t']);
+        let rem = rem
+            .strip_prefix("\r\n")
+            .or_else(|| rem.strip_prefix('\n'))
+            .unwrap_or(rem);
+
+        Ok((
+            rem,
+            Self {
+                timestamp,
+                stream,
+                partial,
+                log,
+            },
+        ))
+    }
+}
+
+impl<'a> ContainerLog<'a> for DockerJson<'a> {
+    fn timestamp(&self) -> &'a str {
+        self.timestamp
+    }
+
+    fn stream(&self) -> LogStream {
+        self.stream
+    }
+
+    fn is_partial(&self) -> bool {
+        self.partial
+    }
+
+    fn into_log(self) -> Cow<'a, str> {
+        self.log
+    }
+}
+
+/// Just enough of a JSON reader for flat docker log objects.
+struct Json<'a> {
+    input: &'a str,
+}
+
+impl<'a> Json<'a> {
+    fn error(&self, expected: &str) -> Error<'a> {
+        let at: String = self.input.chars().take(20).collect();
+        Error::Custom(format!("docker json: expected {} at `{}`", expected, at))
+    }
+
+    fn skip_whitespace(&mut self) {
+        self.input = self.input.trim_start_matches([' ', '\t', '\r', '\n']);
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        self.skip_whitespace();
+        match self.input.strip_prefix(c) {
+            Some(rest) => {
+                self.input = rest;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), Error<'a>> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("`{}`", c)))
+        }
+    }
+
+    /// A string, strictly: control characters must be escaped and `\u`
+    /// escapes have four hex digits, surrogates coming in valid pairs.
+    fn string(&mut self) -> Result<Cow<'a, str>, Error<'a>> {
+        self.expect('"')?;
+
+        let input = self.input;
+        let end = input
+            .find(|c| c == '"' || c == '\\' || c < ' ')
+            .ok_or_else(|| self.error("a closing `\"`"))?;
+        if input[end..].starts_with('"') {
+            self.input = &input[end + 1..];
+            return Ok(Cow::Borrowed(&input[..end]));
+        }
+
+        let mut decoded = String::from(&input[..end]);
+        let mut chars = input[end..].char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.input = &input[end + i + 1..];
+                    return Ok(Cow::Owned(decoded));
+                }
+                '\\' => {
+                    let escaped = match chars.next().map(|(_, c)| c) {
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('/') => '/',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('u') => self.unicode_escape(&mut chars)?,
+                        _ => return Err(self.error("a valid escape")),
+                    };
+                    decoded.push(escaped);
+                }
+                c if c < ' ' => return Err(self.error("an escaped control character")),
+                c => decoded.push(c),
+            }
+        }
+
+        Err(self.error("a closing `\"`"))
+    }
+
+    /// The character of a `\u` escape whose `\u` was read, with the low
+    /// surrogate following a high one.
+    fn unicode_escape(&self, chars: &mut std::str::CharIndices<'_>) -> Result<char, Error<'a>> {
+        let hex = |chars: &mut std::str::CharIndices<'_>| -> Result<u32, Error<'a>> {
+            let mut code = 0;
+            for _ in 0..4 {
+                let digit = chars.next().and_then(|(_, c)| c.to_digit(16));
+                code = code * 16 + digit.ok_or_else(|| self.error("four hex digits"))?;
+            }
+            Ok(code)
+        };
+
+        let high = hex(chars)?;
+        let code = match high {
+            0xD800..=0xDBFF => {
+                match (chars.next(), chars.next()) {
+                    (Some((_, '\\')), Some((_, 'u'))) => {}
+                    _ => return Err(self.error("a low surrogate")),
+                }
+                let low = hex(chars)?;
+                if !(0xDC00..=0xDFFF).contains(&low) {
+                    return Err(self.error("a low surrogate"));
+                }
+                0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+            }
+            0xDC00..=0xDFFF => return Err(self.error("a high surrogate first")),
+            code => code,
+        };
+
+        char::from_u32(code).ok_or_else(|| self.error("a valid \\u escape"))
+    }
+
+    fn skip_value(&mut self) -> Result<(), Error<'a>> {
+        self.skip_whitespace();
+        match self.input.chars().next() {
+            Some('"') => self.string().map(|_| ()),
+            Some(open @ ('{' | '[')) => {
+                let close = if open == '{' { '}' } else { ']' };
+                self.expect(open)?;
+                if self.eat(close) {
+                    return Ok(());
+                }
+                loop {
+                    if open == '{' {
+                        self.string()?;
+                        self.expect(':')?;
+                    }
+                    self.skip_value()?;
+                    if self.eat(close) {
+                        return Ok(());
+                    }
+                    self.expect(',')?;
+                }
+            }
+            _ => {
+                for literal in ["true", "false", "null"] {
+                    if let Some(rest) = self.input.strip_prefix(literal) {
+                        self.input = rest;
+                        return Ok(());
+                    }
+                }
+                self.number()
+            }
+        }
+    }
+
+    /// A number, `-?(0|[1-9][0-9]*)(\.[0-9]+)?([eE][+-]?[0-9]+)?`.
+    fn number(&mut self) -> Result<(), Error<'a>> {
+        let digits = |input: &'a str| input.trim_start_matches(|c: char| c.is_ascii_digit());
+
+        let mut rest = self.input.strip_prefix('-').unwrap_or(self.input);
+        rest = match rest.strip_prefix('0') {
+            Some(rest) => rest,
+            None if rest.starts_with(|c: char| c.is_ascii_digit()) => digits(rest),
+            None => return Err(self.error("a value")),
+        };
+        if let Some(fraction) = rest.strip_prefix('.') {
+            rest = digits(fraction);
+            if rest.len() == fraction.len() {
+                return Err(self.error("a fraction"));
+            }
+        }
+        if let Some(exponent) = rest.strip_prefix(['e', 'E']) {
+            let exponent = exponent.strip_prefix(['+', '-']).unwrap_or(exponent);
+            rest = digits(exponent);
+            if rest.len() == exponent.len() {
+                return Err(self.error("an exponent"));
+            }
+        }
+        self.input = rest;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ORIGINATOR: Originator = Originator {
+        hostname: None,
+        app_name: None,
+        proc_id: None,
+        msg_id: None,
+    };
+
+    #[test]
+    fn docker_json() {
+        let msg = concat!(
+            r#"{"log":"GET /ok 200\n","stream":"stdout","#,
+            r#""attrs":{"tag":"web","x":[1,{"y":"}"}]},"time":"2024-01-01T00:00:00.000000000Z"}"#,
+            "\nnext"
+        );
+
+        assert_eq!(
+            DockerJson::parse(msg, &ORIGINATOR),
+            Ok((
+                "next",
+                DockerJson {
+                    timestamp: "2024-01-01T00:00:00.000000000Z",
+                    stream: LogStream::Stdout,
+                    partial: false,
+                    log: Cow::Borrowed("GET /ok 200")
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn docker_json_escapes() {
+        let msg = r#"{"log":"say \"hi\" é😀\t","stream":"stderr","time":"t"}"#;
+        let (_, docker) = DockerJson::parse(msg, &ORIGINATOR).expect("parsing json");
+
+        assert!(docker.partial);
+        assert_eq!(docker.stream, LogStream::Stderr);
+        assert_eq!(docker.log, "say \"hi\" é😀\t");
+    }
+
+    #[test]
+    fn docker_json_missing_key() {
+        assert_eq!(
+            DockerJson::parse(r#"{"log":"x\n","time":"t"}"#, &ORIGINATOR),
+            Err(Error::Custom("docker json: missing `stream`".into()))
+        );
+    }
+
+    #[test]
+    fn docker_json_unicode_escapes() {
+        let msg = r#"{"log":"\u00e9\u00E9 \ud83d\ude00\n","stream":"stdout","time":"t"}"#;
+        let (_, docker) = DockerJson::parse(msg, &ORIGINATOR).expect("parsing json");
+
+        assert!(!docker.partial);
+        assert_eq!(docker.log, "éé 😀");
+    }
+
+    #[test]
+    fn docker_json_invalid_escapes() {
+        for log in [
+            r#"\u12"#,
+            r#"\u+0ab"#,
+            r#"\u00g0"#,
+            r#"\ud83d"#,
+            r#"\ud83d x"#,
+            r#"\ud83d\u0041"#,
+            r#"\ude00"#,
+            r#"\x"#,
+            "tab\tin",
+            "a\u{1}\\n",
+        ] {
+            let msg = format!(r#"{{"log":"{}","stream":"stdout","time":"t"}}"#, log);
+            assert!(DockerJson::parse(&msg, &ORIGINATOR).is_err(), "{}", log);
+        }
+    }
+
+    #[test]
+    fn docker_json_invalid_values() {
+        for value in [
+            "garbage", "tru", "nul", "01", "-", "1.", ".5", "1e", "1e+", "+1", "1 2",
+        ] {
+            let msg = format!(
+                r#"{{"log":"x\n","foo":{},"stream":"stdout","time":"t"}}"#,
+                value
+            );
+            assert!(DockerJson::parse(&msg, &ORIGINATOR).is_err(), "{}", value);
+        }
+
+        for value in [
+            "true", "false", "null", "0", "-12", "1.5e-3", "2E10", "[1,null]",
+        ] {
+            let msg = format!(
+                r#"{{"log":"x\n","foo":{},"stream":"stdout","time":"t"}}"#,
+                value
+            );
+            assert!(DockerJson::parse(&msg, &ORIGINATOR).is_ok(), "{}", value);
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use super::{
+    HerokuAddonMetrics, HerokuDynoState, HerokuRouter, HerokuRuntimeError, HerokuRuntimeMetrics,
+    LineRaw,
+};
+use crate::{Error, Originator, ParseMsg};
+
+/// Any message found in a Heroku log drain, dispatched on the originator:
+///
+/// * `heroku router` lines are parsed as [`HerokuRouter`],
+/// * `heroku <dyno>` lines as [`HerokuDynoState`], [`HerokuRuntimeError`] or
+///   [`HerokuRuntimeMetrics`], whichever matches,
+/// * `app heroku-postgres` and `app heroku-redis` lines as [`HerokuAddonMetrics`],
+///
+/// and everything else (including app output) as a single [`LineRaw`] line.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Heroku<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    Router(Box<HerokuRouter<'a>>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    DynoState(HerokuDynoState<'a>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    RuntimeError(HerokuRuntimeError<'a>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    RuntimeMetrics(HerokuRuntimeMetrics<'a>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    AddonMetrics(HerokuAddonMetrics<'a>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    Other(LineRaw<'a>),
+}
+
+impl<'a> ParseMsg<'a> for Heroku<'a> {
+    fn parse(msg: &'a str, originator: &Originator<'a>) -> Result<(&'a str, Self), Error<'a>> {
+        match (originator.app_name, originator.proc_id) {
+            (Some("heroku"), Some("router")) => into(HerokuRouter::parse(msg, originator)),
+            (Some("heroku"), Some(_)) => into(HerokuDynoState::parse(msg, originator))
+                .or_else(|_| into(HerokuRuntimeError::parse(msg, originator)))
+                .or_else(|_| into(HerokuRuntimeMetrics::parse(msg, originator)))
+                .or_else(|_| into(LineRaw::parse(msg, originator))),
+            (Some("app"), Some("heroku-postgres" | "heroku-redis")) => {
+                into(HerokuAddonMetrics::parse(msg, originator))
+                    .or_else(|_| into(LineRaw::parse(msg, originator)))
+            }
+            _ => into(LineRaw::parse(msg, originator)),
+        }
+    }
+}
+
+fn into<'a, T: Into<Heroku<'a>>>(
+    res: Result<(&'a str, T), Error<'a>>,
+) -> Result<(&'a str, Heroku<'a>), Error<'a>> {
+    res.map(|(rem, msg)| (rem, msg.into()))
+}
+
+impl<'a> From<HerokuRouter<'a>> for Heroku<'a> {
+    fn from(msg: HerokuRouter<'a>) -> Self {
+        Self::Router(Box::new(msg))
+    }
+}
+
+impl<'a> From<HerokuDynoState<'a>> for Heroku<'a> {
+    fn from(msg: HerokuDynoState<'a>) -> Self {
+        Self::DynoState(msg)
+    }
+}
+
+impl<'a> From<HerokuRuntimeError<'a>> for Heroku<'a> {
+    fn from(msg: HerokuRuntimeError<'a>) -> Self {
+        Self::RuntimeError(msg)
+    }
+}
+
+impl<'a> From<HerokuRuntimeMetrics<'a>> for Heroku<'a> {
+    fn from(msg: HerokuRuntimeMetrics<'a>) -> Self {
+        Self::RuntimeMetrics(msg)
+    }
+}
+
+impl<'a> From<HerokuAddonMetrics<'a>> for Heroku<'a> {
+    fn from(msg: HerokuAddonMetrics<'a>) -> Self {
+        Self::AddonMetrics(msg)
+    }
+}
+
+impl<'a> From<LineRaw<'a>> for Heroku<'a> {
+    fn from(msg: LineRaw<'a>) -> Self {
+        Self::Other(msg)
+    }
+}
@@ -0,0 +1,135 @@
+use super::HerokuErrorCode;
+use crate::{Error, NomRes, Originator, ParseMsg};
+use nom::{
+    bytes::complete::{tag, take_until},
+    character::complete::{not_line_ending, space1},
+    combinator::opt,
+    sequence::preceded,
+};
+use std::borrow::Cow;
+
+/// A dyno state transition, e.g. `State changed from up to down`.
+#[derive(Debug, Eq, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HerokuDynoState<'a> {
+    pub from: &'a str,
+    pub to: &'a str,
+}
+
+impl<'a> ParseMsg<'a> for HerokuDynoState<'a> {
+    fn parse(msg: &'a str, _: &Originator) -> Result<(&'a str, Self), Error<'a>> {
+        let (rem, line) = not_line_ending(msg)?;
+        let (_, (from, to)) = parse_state_change(line)?;
+
+        Ok((rem, Self { from, to }))
+    }
+}
+
+fn parse_state_change(part: &str) -> NomRes<&str, (&str, &str)> {
+    let (rem, _) = tag("State changed from ")(part)?;
+    let (to, from) = take_until(" to ")(rem)?;
+    let (to, _) = tag(" to ")(to)?;
+
+    Ok(("", (from, to.trim_end())))
+}
+
+/// A dyno runtime error, e.g. `Error R14 (Memory quota exceeded)`.
+///
+/// Anything after the `->` that some errors carry is kept in `detail`.
+#[derive(Debug, Eq, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HerokuRuntimeError<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub code: HerokuErrorCode<'a>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub desc: Cow<'a, str>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub detail: Option<Cow<'a, str>>,
+}
+
+impl<'a> ParseMsg<'a> for HerokuRuntimeError<'a> {
+    fn parse(msg: &'a str, _: &Originator) -> Result<(&'a str, Self), Error<'a>> {
+        let (rem, line) = not_line_ending(msg)?;
+        let (_, (code, desc, detail)) = parse_runtime_error(line)?;
+
+        Ok((
+            rem,
+            Self {
+                code: code.into(),
+                desc: Cow::Borrowed(desc),
+                detail: detail.map(Cow::Borrowed),
+            },
+        ))
+    }
+}
+
+fn parse_runtime_error(part: &str) -> NomRes<&str, (&str, &str, Option<&str>)> {
+    let (rem, _) = tag("Error ")(part)?;
+    let (rem, code) = take_until(" ")(rem)?;
+    let (rem, _) = space1(rem)?;
+    let (rem, _) = tag("(")(rem)?;
+    let (rem, desc) = take_until(")")(rem)?;
+    let (rem, _) = tag(")")(rem)?;
+    let (rem, detail) = opt(preceded(tag(" -> "), not_line_ending))(rem)?;
+
+    Ok((rem, (code, desc, detail)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ORIGINATOR: Originator = Originator {
+        hostname: None,
+        app_name: Some("heroku"),
+        proc_id: Some("web.1"),
+        msg_id: None,
+    };
+
+    #[test]
+    fn dyno_state() {
+        assert_eq!(
+            HerokuDynoState::parse("State changed from up to down", &ORIGINATOR),
+            Ok((
+                "",
+                HerokuDynoState {
+                    from: "up",
+                    to: "down"
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn runtime_error() {
+        assert_eq!(
+            HerokuRuntimeError::parse("Error R14 (Memory quota exceeded)\n", &ORIGINATOR),
+            Ok((
+                "\n",
+                HerokuRuntimeError {
+                    code: HerokuErrorCode::R14,
+                    desc: "Memory quota exceeded".into(),
+                    detail: None
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn runtime_error_with_detail() {
+        let msg = concat!(
+            "Error R10 (Boot timeout) -> ",
+            "Web process failed to bind to $PORT within 60 seconds of launch"
+        );
+        assert_eq!(
+            HerokuRuntimeError::parse(msg, &ORIGINATOR).map(|(_, e)| e),
+            Ok(HerokuRuntimeError {
+                code: HerokuErrorCode::R10,
+                desc: "Boot timeout".into(),
+                detail: Some(
+                    "Web process failed to bind to $PORT within 60 seconds of launch".into()
+                )
+            })
+        );
+    }
+}
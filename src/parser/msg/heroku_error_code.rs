@@ -0,0 +1,138 @@
+use crate::{Error, ParseValue};
+
+macro_rules! heroku_error_codes {
+    ($($code:ident => ($desc:literal, $platform:literal),)+) => {
+        /// Heroku router (`H`), runtime (`R`) and logging (`L`) error codes, as documented in
+        /// <https://devcenter.heroku.com/articles/error-codes>.
+        ///
+        /// Codes that are not known to this crate are kept verbatim in `Unknown`.
+        #[derive(Debug, Eq, PartialEq, Clone, Copy, Hash)]
+        pub enum HerokuErrorCode<'a> {
+            $($code,)+
+            Unknown(&'a str),
+        }
+
+        impl<'a> HerokuErrorCode<'a> {
+            /// The code as it appears in the log line, e.g. `H12`.
+            pub fn code(&self) -> &'a str {
+                match self {
+                    $(Self::$code => stringify!($code),)+
+                    Self::Unknown(code) => code,
+                }
+            }
+
+            /// Heroku's short description of the error, `None` for unknown codes.
+            pub fn description(&self) -> Option<&'static str> {
+                match self {
+                    $(Self::$code => Some($desc),)+
+                    Self::Unknown(_) => None,
+                }
+            }
+
+            /// Whether the error originates in the Heroku platform itself rather
+            /// than in the app (or the clients talking to it).
+            pub fn is_platform_error(&self) -> bool {
+                match self {
+                    $(Self::$code => $platform,)+
+                    Self::Unknown(_) => false,
+                }
+            }
+        }
+
+        /// Serialized as the code, e.g. `"H12"`.
+        #[cfg(feature = "serde")]
+        impl serde::Serialize for HerokuErrorCode<'_> {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(self.code())
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        impl<'de: 'a, 'a> serde::Deserialize<'de> for HerokuErrorCode<'a> {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                <&'a str>::deserialize(deserializer).map(Self::from)
+            }
+        }
+
+        impl<'a> From<&'a str> for HerokuErrorCode<'a> {
+            fn from(code: &'a str) -> Self {
+                match code {
+                    $(stringify!($code) => Self::$code,)+
+                    _ => Self::Unknown(code),
+                }
+            }
+        }
+    };
+}
+
+heroku_error_codes! {
+    H10 => ("App crashed", false),
+    H11 => ("Backlog too deep", false),
+    H12 => ("Request timeout", false),
+    H13 => ("Connection closed without response", false),
+    H14 => ("No web dynos running", false),
+    H15 => ("Idle connection", false),
+    H16 => ("Redirect to herokuapp.com", true),
+    H17 => ("Poorly formatted HTTP response", false),
+    H18 => ("Server Request Interrupted", false),
+    H19 => ("Backend connection timeout", false),
+    H20 => ("App boot timeout", false),
+    H21 => ("Backend connection refused", false),
+    H22 => ("Connection limit reached", false),
+    H23 => ("Endpoint misconfigured", false),
+    H24 => ("Forced close", false),
+    H25 => ("HTTP Restriction", true),
+    H26 => ("Request Error", false),
+    H27 => ("Client Request Interrupted", false),
+    H28 => ("Client Connection Idle", false),
+    H31 => ("Misdirected Request", false),
+    H80 => ("Maintenance mode", false),
+    H81 => ("Blank app", false),
+    H82 => ("Free dyno hour pool exhausted", false),
+    H83 => ("Planned Service Degradation", true),
+    H99 => ("Platform error", true),
+    R10 => ("Boot timeout", false),
+    R12 => ("Exit timeout", false),
+    R13 => ("Attach error", false),
+    R14 => ("Memory quota exceeded", false),
+    R15 => ("Memory quota vastly exceeded", false),
+    R16 => ("Detached", false),
+    R17 => ("Checksum error", false),
+    R99 => ("Platform error", true),
+    L10 => ("Drain buffer overflow", true),
+    L11 => ("Tail buffer overflow", true),
+    L12 => ("Local buffer overflow", true),
+    L13 => ("Local delivery error", true),
+    L14 => ("Certificate validation error", false),
+    L15 => ("Tail buffer temporarily unavailable", true),
+}
+
+impl<'a> ParseValue<'a> for HerokuErrorCode<'a> {
+    fn parse_value(value: &'a str) -> Result<Self, Error<'a>> {
+        Ok(value.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn known_code() {
+        let code = HerokuErrorCode::from("H12");
+        assert_eq!(code, HerokuErrorCode::H12);
+        assert_eq!(code.code(), "H12");
+        assert_eq!(code.description(), Some("Request timeout"));
+        assert!(!code.is_platform_error());
+        assert!(HerokuErrorCode::from("H99").is_platform_error());
+    }
+
+    #[test]
+    fn unknown_code() {
+        let code = HerokuErrorCode::from("H42");
+        assert_eq!(code, HerokuErrorCode::Unknown("H42"));
+        assert_eq!(code.code(), "H42");
+        assert_eq!(code.description(), None);
+        assert!(!code.is_platform_error());
+    }
+}
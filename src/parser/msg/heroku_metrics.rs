@@ -0,0 +1,231 @@
+use crate::{parser::helpers, Error, Originator, ParseMsg};
+use nom::character::complete::not_line_ending;
+use std::borrow::Cow;
+
+/// A single `sample#name=value` measurement, with the unit split off the value
+/// (`sample#memory_total=21.00MB` has a value of `21.0` and a unit of `MB`).
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HerokuSample<'a> {
+    pub name: &'a str,
+    pub value: f64,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub unit: Option<&'a str>,
+}
+
+/// Dyno runtime metrics (log-runtime-metrics), emitted by `heroku web.1` and
+/// friends, e.g. `source=web.1 dyno=heroku.123.abc sample#load_avg_1m=0.00`.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HerokuRuntimeMetrics<'a> {
+    pub source: &'a str,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub dyno: Option<&'a str>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub samples: Vec<HerokuSample<'a>>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub extra: Vec<(&'a str, Cow<'a, str>)>,
+}
+
+impl<'a> ParseMsg<'a> for HerokuRuntimeMetrics<'a> {
+    fn parse(msg: &'a str, _: &Originator) -> Result<(&'a str, Self), Error<'a>> {
+        let (rem, metrics) = parse_metrics(msg, "heroku runtime metrics", "dyno")?;
+
+        Ok((
+            rem,
+            Self {
+                source: metrics.source,
+                dyno: metrics.id,
+                samples: metrics.samples,
+                extra: metrics.extra,
+            },
+        ))
+    }
+}
+
+/// Add-on metrics, emitted by `app heroku-postgres` and `app heroku-redis`,
+/// e.g. `source=HEROKU_POSTGRESQL_RED addon=postgresql-curly-1234 sample#db_size=26315671bytes`.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HerokuAddonMetrics<'a> {
+    pub source: &'a str,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub addon: Option<&'a str>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub samples: Vec<HerokuSample<'a>>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub extra: Vec<(&'a str, Cow<'a, str>)>,
+}
+
+impl<'a> ParseMsg<'a> for HerokuAddonMetrics<'a> {
+    fn parse(msg: &'a str, _: &Originator) -> Result<(&'a str, Self), Error<'a>> {
+        let (rem, metrics) = parse_metrics(msg, "heroku addon metrics", "addon")?;
+
+        Ok((
+            rem,
+            Self {
+                source: metrics.source,
+                addon: metrics.id,
+                samples: metrics.samples,
+                extra: metrics.extra,
+            },
+        ))
+    }
+}
+
+/// The fields shared by the metrics logs, `id_key` (`dyno` or `addon`) naming
+/// what the samples are of.
+struct Metrics<'a> {
+    source: &'a str,
+    id: Option<&'a str>,
+    samples: Vec<HerokuSample<'a>>,
+    extra: Vec<(&'a str, Cow<'a, str>)>,
+}
+
+fn parse_metrics<'a>(
+    msg: &'a str,
+    context: &str,
+    id_key: &str,
+) -> Result<(&'a str, Metrics<'a>), Error<'a>> {
+    let (rem, line) = not_line_ending(msg)?;
+
+    let mut source = None;
+    let mut id = None;
+    let mut samples = Vec::new();
+    let mut extra = Vec::new();
+    for (key, value) in helpers::parse_pairs(line, context)? {
+        match key {
+            "source" => source = Some(value),
+            _ if key == id_key => id = Some(value),
+            _ => match key.strip_prefix("sample#") {
+                Some(name) => samples.push(parse_sample(context, name, value)?),
+                None => extra.push((key, Cow::Borrowed(value))),
+            },
+        }
+    }
+    let source = helpers::required(context, "source", source)?;
+    if samples.is_empty() {
+        return Err(Error::Custom(format!("{}: no samples found", context)));
+    }
+
+    let metrics = Metrics {
+        source,
+        id,
+        samples,
+        extra,
+    };
+
+    Ok((rem, metrics))
+}
+
+fn parse_sample<'a>(
+    context: &str,
+    name: &'a str,
+    value: &'a str,
+) -> Result<HerokuSample<'a>, Error<'a>> {
+    let split = value
+        .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-'))
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+
+    let value = number.parse().map_err(|_| {
+        Error::Custom(format!(
+            "{}: invalid value `{}` for sample `{}`",
+            context, value, name
+        ))
+    })?;
+
+    Ok(HerokuSample {
+        name,
+        value,
+        unit: Some(unit).filter(|u| !u.is_empty()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn runtime_metrics() {
+        let msg = concat!(
+            "source=web.1 dyno=heroku.2808254.d97d0ea7-cf3d-411b-b453-d2943a50b456 ",
+            "sample#memory_total=21.00MB sample#memory_pgpgin=348836pages"
+        );
+        let originator = Originator {
+            hostname: None,
+            app_name: Some("heroku"),
+            proc_id: Some("web.1"),
+            msg_id: None,
+        };
+
+        assert_eq!(
+            HerokuRuntimeMetrics::parse(msg, &originator),
+            Ok((
+                "",
+                HerokuRuntimeMetrics {
+                    source: "web.1",
+                    dyno: Some("heroku.2808254.d97d0ea7-cf3d-411b-b453-d2943a50b456"),
+                    samples: vec![
+                        HerokuSample {
+                            name: "memory_total",
+                            value: 21.0,
+                            unit: Some("MB")
+                        },
+                        HerokuSample {
+                            name: "memory_pgpgin",
+                            value: 348836.0,
+                            unit: Some("pages")
+                        }
+                    ],
+                    extra: vec![]
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn addon_metrics() {
+        let msg = concat!(
+            "source=HEROKU_POSTGRESQL_RED addon=postgresql-curly-12345 ",
+            "sample#current_transaction=1873 sample#db_size=26315671bytes ",
+            "sample#active-connections=12 sample#read-iops=0"
+        );
+        let originator = Originator {
+            hostname: None,
+            app_name: Some("app"),
+            proc_id: Some("heroku-postgres"),
+            msg_id: None,
+        };
+
+        let (_, metrics) = HerokuAddonMetrics::parse(msg, &originator).expect("parsing metrics");
+        assert_eq!(metrics.source, "HEROKU_POSTGRESQL_RED");
+        assert_eq!(metrics.addon, Some("postgresql-curly-12345"));
+        assert_eq!(
+            metrics.samples[1],
+            HerokuSample {
+                name: "db_size",
+                value: 26315671.0,
+                unit: Some("bytes")
+            }
+        );
+        assert_eq!(metrics.samples.len(), 4);
+    }
+
+    #[test]
+    fn metrics_without_samples() {
+        let originator = Originator {
+            hostname: None,
+            app_name: Some("heroku"),
+            proc_id: Some("web.1"),
+            msg_id: None,
+        };
+
+        assert_eq!(
+            HerokuRuntimeMetrics::parse("source=web.1 dyno=heroku.1", &originator),
+            Err(Error::Custom(
+                "heroku runtime metrics: no samples found".into()
+            ))
+        );
+    }
+}
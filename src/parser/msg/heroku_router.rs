@@ -1,18 +1,19 @@
-use crate::{parser::helpers, Error, NomRes, Originator, ParseMsg};
-use nom::{
-    branch::alt,
-    bytes::complete::{tag, take_until},
-    combinator::rest,
-    sequence::tuple,
-};
+use super::HerokuErrorCode;
+use crate::{parser::helpers, Error, Originator, ParseMsg};
+use nom::character::complete::not_line_ending;
+use std::borrow::Cow;
 
 #[derive(Debug, Eq, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct HerokuRouter<'a> {
     pub at: &'a str,
-    pub code: Option<&'a str>,
-    pub desc: Option<&'a str>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub code: Option<HerokuErrorCode<'a>>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub desc: Option<Cow<'a, str>>,
     pub method: &'a str,
-    pub path: &'a str,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub path: Cow<'a, str>,
     pub host: &'a str,
     pub request_id: &'a str,
     pub fwd: &'a str,
@@ -22,75 +23,91 @@ pub struct HerokuRouter<'a> {
     pub status: u16,
     pub bytes: Option<u64>,
     pub protocol: &'a str,
+    /// Any key/value pairs that the router emitted but are not known fields,
+    /// in the order they appeared (e.g. `tls_version`).
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub extra: Vec<(&'a str, Cow<'a, str>)>,
+}
+
+#[derive(Default)]
+struct Fields<'a> {
+    at: Option<&'a str>,
+    code: Option<&'a str>,
+    desc: Option<&'a str>,
+    method: Option<&'a str>,
+    path: Option<&'a str>,
+    host: Option<&'a str>,
+    request_id: Option<&'a str>,
+    fwd: Option<&'a str>,
+    dyno: Option<&'a str>,
+    connect: Option<&'a str>,
+    service: Option<&'a str>,
+    status: Option<&'a str>,
+    bytes: Option<&'a str>,
+    protocol: Option<&'a str>,
+    extra: Vec<(&'a str, Cow<'a, str>)>,
 }
 
 impl<'a> ParseMsg<'a> for HerokuRouter<'a> {
     fn parse(msg: &'a str, _: &Originator) -> Result<(&'a str, Self), Error<'a>> {
-        let (rem, at) = parse_word(msg, "at=", " ")?;
-        let (rem, code, desc) = match at {
-            "error" => {
-                let (rem, code) = parse_word(rem, "code=", " ")?;
-                let (rem, desc) = parse_word(rem, "desc=\"", "\" ")?;
-                (rem, Some(code), Some(desc))
-            }
-            _ => (rem, None, None),
-        };
-        let (rem, method) = parse_word(rem, "method=", " ")?;
-        let (rem, path) = parse_word(rem, "path=\"", "\" ")?;
-        let (rem, host) = parse_word(rem, "host=", " ")?;
-        let (rem, request_id) = parse_word(rem, "request_id=", " ")?;
-        let (rem, fwd) = parse_word(rem, "fwd=\"", "\" ")?;
-        let (rem, dyno) = parse_word(rem, "dyno=", " ")?;
-        let (rem, connect) = parse_word(rem, "connect=", "ms ")?;
-        let (rem, service) = parse_word(rem, "service=", "ms ")?;
-        let (rem, status) = parse_word(rem, "status=", " ")?;
-        let (rem, bytes) = parse_word(rem, "bytes=", " ")?;
-        let bytes = match bytes {
-            "" => None,
-            b => Some(b),
-        };
-        let (rem, protocol) = parse_end_word(rem, "protocol=")?;
+        let (rem, line) = not_line_ending(msg)?;
+        let pairs = helpers::parse_pairs(line, "heroku router")?;
+
+        let mut fields = Fields::default();
+        for (key, value) in pairs {
+            let slot = match key {
+                "at" => &mut fields.at,
+                "code" => &mut fields.code,
+                "desc" => &mut fields.desc,
+                "method" => &mut fields.method,
+                "path" => &mut fields.path,
+                "host" => &mut fields.host,
+                "request_id" => &mut fields.request_id,
+                "fwd" => &mut fields.fwd,
+                "dyno" => &mut fields.dyno,
+                "connect" => &mut fields.connect,
+                "service" => &mut fields.service,
+                "status" => &mut fields.status,
+                "bytes" => &mut fields.bytes,
+                "protocol" => &mut fields.protocol,
+                _ => {
+                    fields.extra.push((key, Cow::Borrowed(value)));
+                    continue;
+                }
+            };
+            *slot = Some(value);
+        }
 
         let router = Self {
-            at,
-            code,
-            desc,
-            method,
-            path,
-            host,
-            request_id,
-            fwd,
-            dyno,
-            connect: helpers::parse_u32(connect)?,
-            service: helpers::parse_u32(service)?,
-            status: helpers::parse_u16(status)?,
-            bytes: bytes.map(helpers::parse_u64).transpose()?,
-            protocol,
+            at: required("at", fields.at)?,
+            code: fields.code.map(HerokuErrorCode::from),
+            desc: fields.desc.map(Cow::Borrowed),
+            method: required("method", fields.method)?,
+            path: Cow::Borrowed(required("path", fields.path)?),
+            host: required("host", fields.host)?,
+            request_id: required("request_id", fields.request_id)?,
+            fwd: required("fwd", fields.fwd)?,
+            dyno: required("dyno", fields.dyno)?,
+            connect: helpers::parse_u32(millis(required("connect", fields.connect)?))?,
+            service: helpers::parse_u32(millis(required("service", fields.service)?))?,
+            status: helpers::parse_u16(required("status", fields.status)?)?,
+            bytes: fields
+                .bytes
+                .filter(|b| !b.is_empty())
+                .map(helpers::parse_u64)
+                .transpose()?,
+            protocol: required("protocol", fields.protocol)?,
+            extra: fields.extra,
         };
 
         Ok((rem, router))
     }
 }
 
-pub fn parse_word<'a>(part: &'a str, start: &'a str, stop: &'a str) -> NomRes<&'a str, &'a str> {
-    //TODO: first take until scary here, should be tag instead
-    let (rem, (_, _, el)) = tuple((take_until(start), tag(start), take_until(stop)))(part)?;
-
-    Ok((rem, el))
+fn required<'a>(key: &str, value: Option<&'a str>) -> Result<&'a str, Error<'a>> {
+    helpers::required("heroku router", key, value)
 }
 
-pub fn parse_end_word<'a>(part: &'a str, element: &'a str) -> NomRes<&'a str, &'a str> {
-    let (rem, (_, _, el)) = tuple((
-        take_until(element),
-        tag(element),
-        alt((
-            take_until("\n\r"),
-            take_until("\n\n"),
-            take_until("\n"),
-            take_until(" "),
-            rest,
-        )),
-    ))(part)?;
-
-    Ok((rem, el))
+fn millis(value: &str) -> &str {
+    value.strip_suffix("ms").unwrap_or(value)
 }
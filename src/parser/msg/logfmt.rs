@@ -0,0 +1,62 @@
+use crate::{parser::helpers, Error, MsgParser, Originator};
+use std::borrow::Cow;
+
+/// A MSG parser for logfmt lines (`key=value key2="quoted value"`), optionally
+/// keeping only a set of keys.
+#[derive(Debug, Default, Eq, PartialEq, Clone)]
+pub struct Logfmt {
+    keys: Option<Vec<String>>,
+}
+
+/// The `key=value` pairs of a [`Logfmt`] line, in the order they appeared.
+#[derive(Debug, Eq, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LogfmtPairs<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub pairs: Vec<(&'a str, Cow<'a, str>)>,
+}
+
+impl Logfmt {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only keeps the pairs whose key is one of `keys`.
+    pub fn with_keys(keys: &[&str]) -> Self {
+        Self {
+            keys: Some(keys.iter().map(|key| key.to_string()).collect()),
+        }
+    }
+}
+
+impl<'a> MsgParser<'a> for Logfmt {
+    type Output = LogfmtPairs<'a>;
+
+    fn parse(
+        &self,
+        msg: &'a str,
+        _: &Originator<'a>,
+    ) -> Result<(&'a str, Self::Output), Error<'a>> {
+        let (rem, line) = helpers::split_line(msg);
+        let mut pairs = helpers::parse_pairs(line, "logfmt")?;
+        if let Some(keys) = &self.keys {
+            pairs.retain(|(key, _)| keys.iter().any(|k| k == key));
+        }
+        let pairs = pairs
+            .into_iter()
+            .map(|(key, value)| (key, Cow::Borrowed(value)))
+            .collect();
+
+        Ok((rem, LogfmtPairs { pairs }))
+    }
+}
+
+impl<'a> LogfmtPairs<'a> {
+    /// The value of the first `key` pair.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.pairs
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map(|(_, value)| &**value)
+    }
+}
@@ -1,5 +1,27 @@
+mod audit;
+mod cisco;
+mod container;
+mod cri;
+mod docker_json;
+mod heroku;
+mod heroku_dyno;
+mod heroku_error_code;
+mod heroku_metrics;
 mod heroku_router;
+mod logfmt;
 mod raw;
+mod regex;
 
+pub use self::regex::{Regex, RegexCaptures, RegexType, RegexValue};
+pub use audit::{Audit, AuditEvent, AuditEvents, AuditId};
+pub use cisco::CiscoMnemonic;
+pub use container::{ContainerLog, ContainerRecord, LogStream, Reassemble};
+pub use cri::Cri;
+pub use docker_json::DockerJson;
+pub use heroku::Heroku;
+pub use heroku_dyno::{HerokuDynoState, HerokuRuntimeError};
+pub use heroku_error_code::HerokuErrorCode;
+pub use heroku_metrics::{HerokuAddonMetrics, HerokuRuntimeMetrics, HerokuSample};
 pub use heroku_router::HerokuRouter;
-pub use raw::{LineRaw, Raw};
+pub use logfmt::{Logfmt, LogfmtPairs};
+pub use raw::{LineRaw, Multiline, MultilineRaw, Raw};
@@ -1,14 +1,25 @@
-use crate::{Error, Originator, ParseMsg};
+use crate::{Error, MsgParser, Originator, ParseMsg};
 use nom::combinator::rest;
+use std::borrow::Cow;
 
 #[derive(Debug, Eq, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Raw<'a> {
-    pub msg: &'a str,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub msg: Cow<'a, str>,
 }
 
 impl<'a> From<&'a str> for Raw<'a> {
     fn from(msg: &'a str) -> Self {
-        Self { msg }
+        Self {
+            msg: Cow::Borrowed(msg),
+        }
+    }
+}
+
+impl AsRef<str> for Raw<'_> {
+    fn as_ref(&self) -> &str {
+        &self.msg
     }
 }
 
@@ -21,13 +32,23 @@ impl<'a> ParseMsg<'a> for Raw<'a> {
 }
 
 #[derive(Debug, Eq, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LineRaw<'a> {
-    pub msg: &'a str,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub msg: Cow<'a, str>,
 }
 
 impl<'a> From<&'a str> for LineRaw<'a> {
     fn from(msg: &'a str) -> Self {
-        Self { msg }
+        Self {
+            msg: Cow::Borrowed(msg),
+        }
+    }
+}
+
+impl AsRef<str> for LineRaw<'_> {
+    fn as_ref(&self) -> &str {
+        &self.msg
     }
 }
 
@@ -45,3 +66,164 @@ impl<'a> ParseMsg<'a> for LineRaw<'a> {
         Ok((rem, msg.into()))
     }
 }
+
+/// A MSG that spans every following line up to the next one that starts with
+/// a syslog header (`<PRI>VERSION`, optionally preceded by an octet count), so
+/// that e.g. stack traces logged without headers stay in one message.
+#[derive(Debug, Eq, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MultilineRaw<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub msg: Cow<'a, str>,
+}
+
+impl<'a> From<&'a str> for MultilineRaw<'a> {
+    fn from(msg: &'a str) -> Self {
+        Self {
+            msg: Cow::Borrowed(msg),
+        }
+    }
+}
+
+impl AsRef<str> for MultilineRaw<'_> {
+    fn as_ref(&self) -> &str {
+        &self.msg
+    }
+}
+
+impl<'a> ParseMsg<'a> for MultilineRaw<'a> {
+    fn parse(msg: &'a str, originator: &Originator<'a>) -> Result<(&'a str, Self), Error<'a>> {
+        Multiline::new().parse(msg, originator)
+    }
+}
+
+/// A [`MultilineRaw`] parser that keeps at most `max_lines` lines. Any further
+/// lines up to the next header are dropped, rather than read as messages
+/// without a header.
+#[derive(Debug, Default, Eq, PartialEq, Clone, Copy)]
+pub struct Multiline {
+    max_lines: Option<usize>,
+}
+
+impl Multiline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn max_lines(self, max_lines: usize) -> Self {
+        Self {
+            max_lines: Some(max_lines.max(1)),
+        }
+    }
+}
+
+impl<'a> MsgParser<'a> for Multiline {
+    type Output = MultilineRaw<'a>;
+
+    fn parse(
+        &self,
+        msg: &'a str,
+        _: &Originator<'a>,
+    ) -> Result<(&'a str, Self::Output), Error<'a>> {
+        // The end of the kept lines, once `max_lines` were read.
+        let mut cut = None;
+        let mut start = 0;
+        let mut lines = 0;
+        while let Some(line_end) = msg[start..].find('\n').map(|i| start + i) {
+            lines += 1;
+            if Some(lines) == self.max_lines {
+                cut = cut.or(Some(line_end));
+            }
+            let next = line_end + 1;
+            if next == msg.len() || is_header(&msg[next..]) {
+                let kept = &msg[..cut.unwrap_or(line_end)];
+                return Ok((&msg[next..], kept.strip_suffix('\r').unwrap_or(kept).into()));
+            }
+            start = next;
+        }
+
+        match cut {
+            Some(cut) => {
+                let kept = &msg[..cut];
+                Ok(("", kept.strip_suffix('\r').unwrap_or(kept).into()))
+            }
+            None => Ok(("", msg.into())),
+        }
+    }
+}
+
+fn is_header(line: &str) -> bool {
+    let line = match line.find(|c: char| !c.is_ascii_digit()) {
+        Some(i) if i > 0 && line[i..].starts_with(' ') => &line[i + 1..],
+        _ => line,
+    };
+
+    let pri = match line.strip_prefix('<') {
+        Some(rest) => rest,
+        None => return false,
+    };
+    let digits = pri.find(|c: char| !c.is_ascii_digit()).unwrap_or(pri.len());
+
+    (1..=3).contains(&digits)
+        && pri[digits..].starts_with('>')
+        && pri[digits + 1..].starts_with(|c: char| c.is_ascii_digit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ORIGINATOR: Originator = Originator {
+        hostname: None,
+        app_name: None,
+        proc_id: None,
+        msg_id: None,
+    };
+
+    const TRACE: &str = concat!(
+        "java.lang.IllegalStateException: boom\n",
+        "\tat com.foo.Bar.baz(Bar.java:42)\n",
+        "Caused by: java.io.IOException: closed\n",
+        "\tat com.foo.Io.read(Io.java:7)\n",
+        "<11>1 - host app - - - next message"
+    );
+
+    #[test]
+    fn multiline_raw() {
+        let (rem, msg) = MultilineRaw::parse(TRACE, &ORIGINATOR).expect("parsing msg");
+
+        assert_eq!(rem, "<11>1 - host app - - - next message");
+        assert_eq!(msg.msg.lines().count(), 4);
+        assert!(msg.msg.ends_with("(Io.java:7)"));
+    }
+
+    #[test]
+    fn multiline_max_lines() {
+        let (rem, msg) = Multiline::new()
+            .max_lines(2)
+            .parse(TRACE, &ORIGINATOR)
+            .expect("parsing msg");
+
+        assert_eq!(
+            msg.msg,
+            "java.lang.IllegalStateException: boom\n\tat com.foo.Bar.baz(Bar.java:42)"
+        );
+        assert_eq!(rem, "<11>1 - host app - - - next message");
+
+        // Without a following header, the rest is dropped all the same.
+        let (rem, msg) = Multiline::new()
+            .max_lines(1)
+            .parse("one\ntwo\nthree", &ORIGINATOR)
+            .expect("parsing msg");
+        assert_eq!((rem, msg.msg), ("", "one".into()));
+    }
+
+    #[test]
+    fn header_detection() {
+        assert!(is_header("<11>1 - -"));
+        assert!(is_header("284 <158>1 2021"));
+        assert!(!is_header("<html>"));
+        assert!(!is_header("\tat <init>"));
+        assert!(!is_header("<1234>1"));
+    }
+}
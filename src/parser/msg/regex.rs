@@ -0,0 +1,206 @@
+use crate::{Error, MsgParser, Originator};
+use std::{borrow::Cow, sync::Arc};
+
+/// A MSG parser driven by a regular expression with named capture groups.
+///
+/// The pattern is anchored at the start of MSG and the input it matches is
+/// consumed, so patterns that stop at a line ending can be used with
+/// multiple messages per input. Every named group that participated in the
+/// match ends up in [`RegexCaptures`], as a `&str` unless a [`RegexType`] was
+/// set for it with [`Regex::coerce`].
+#[derive(Debug, Clone)]
+pub struct Regex {
+    regex: ::regex::Regex,
+    names: Vec<Option<Arc<str>>>,
+    types: Vec<RegexType>,
+}
+
+/// The type a capture group is converted to.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum RegexType {
+    Str,
+    Int,
+    Float,
+    Bool,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RegexValue<'a> {
+    Str(#[cfg_attr(feature = "serde", serde(borrow))] Cow<'a, str>),
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+}
+
+/// The named captures of a [`Regex`] match, in the order of the groups.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RegexCaptures<'a> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub msg: Cow<'a, str>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub captures: Vec<(Arc<str>, RegexValue<'a>)>,
+}
+
+impl Regex {
+    pub fn new(pattern: &str) -> Result<Self, ::regex::Error> {
+        let regex = ::regex::Regex::new(&format!("^(?:{})", pattern))?;
+        let names = regex
+            .capture_names()
+            .map(|name| name.map(Arc::from))
+            .collect::<Vec<_>>();
+        let types = vec![RegexType::Str; names.len()];
+
+        Ok(Self {
+            regex,
+            names,
+            types,
+        })
+    }
+
+    /// Converts the `name` group to `ty` when parsing. Fails if the pattern
+    /// has no group called `name`.
+    pub fn coerce(mut self, name: &str, ty: RegexType) -> Result<Self, Error<'static>> {
+        let index = self
+            .names
+            .iter()
+            .position(|n| n.as_deref() == Some(name))
+            .ok_or_else(|| Error::Custom(format!("regex: no capture group named `{}`", name)))?;
+        self.types[index] = ty;
+
+        Ok(self)
+    }
+}
+
+impl<'a> MsgParser<'a> for Regex {
+    type Output = RegexCaptures<'a>;
+
+    fn parse(
+        &self,
+        msg: &'a str,
+        _: &Originator<'a>,
+    ) -> Result<(&'a str, Self::Output), Error<'a>> {
+        let captures = self.regex.captures(msg).ok_or_else(|| {
+            Error::Custom(format!("regex: `{}` does not match", self.regex.as_str()))
+        })?;
+
+        let mut values = Vec::new();
+        for ((name, ty), group) in self.names.iter().zip(&self.types).zip(captures.iter()) {
+            let (name, group) = match (name, group) {
+                (Some(name), Some(group)) => (name, group.as_str()),
+                _ => continue,
+            };
+            values.push((name.clone(), coerce(name, *ty, group)?));
+        }
+
+        let end = captures.get(0).map_or(0, |m| m.end());
+        let (matched, rem) = msg.split_at(end);
+
+        Ok((
+            rem,
+            RegexCaptures {
+                msg: Cow::Borrowed(matched),
+                captures: values,
+            },
+        ))
+    }
+}
+
+fn coerce<'a>(name: &str, ty: RegexType, value: &'a str) -> Result<RegexValue<'a>, Error<'a>> {
+    let invalid = |e: &dyn std::fmt::Display| {
+        Error::Custom(format!(
+            "regex: group `{}`: invalid value `{}`: {}",
+            name, value, e
+        ))
+    };
+
+    match ty {
+        RegexType::Str => Ok(RegexValue::Str(Cow::Borrowed(value))),
+        RegexType::Int => value.parse().map(RegexValue::Int).map_err(|e| invalid(&e)),
+        RegexType::Float => value
+            .parse()
+            .map(RegexValue::Float)
+            .map_err(|e| invalid(&e)),
+        RegexType::Bool => value.parse().map(RegexValue::Bool).map_err(|e| invalid(&e)),
+    }
+}
+
+impl<'a> RegexCaptures<'a> {
+    pub fn get(&self, name: &str) -> Option<&RegexValue<'a>> {
+        self.captures
+            .iter()
+            .find(|(n, _)| &**n == name)
+            .map(|(_, value)| value)
+    }
+
+    /// The `name` capture if it was kept as a string.
+    pub fn get_str(&self, name: &str) -> Option<&str> {
+        match self.get(name) {
+            Some(RegexValue::Str(value)) => Some(&**value),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ORIGINATOR: Originator = Originator {
+        hostname: None,
+        app_name: None,
+        proc_id: None,
+        msg_id: None,
+    };
+
+    #[test]
+    fn named_captures() {
+        let regex =
+            Regex::new(r"(?P<method>[A-Z]+) (?P<path>\S+) (?P<status>\d+)( (?P<ms>\d+)ms)?")
+                .expect("compiling regex")
+                .coerce("status", RegexType::Int)
+                .expect("coercing group");
+
+        let (rem, captures) = regex
+            .parse("GET /v1/ok 200\nnext", &ORIGINATOR)
+            .expect("parsing msg");
+
+        assert_eq!(rem, "\nnext");
+        assert_eq!(captures.msg, "GET /v1/ok 200");
+        assert_eq!(captures.get_str("method"), Some("GET"));
+        assert_eq!(captures.get_str("path"), Some("/v1/ok"));
+        assert_eq!(captures.get("status"), Some(&RegexValue::Int(200)));
+        assert_eq!(captures.get("ms"), None);
+    }
+
+    #[test]
+    fn anchored() {
+        let regex = Regex::new(r"(?P<status>\d+)").expect("compiling regex");
+
+        assert!(regex.parse("status 200", &ORIGINATOR).is_err());
+    }
+
+    #[test]
+    fn invalid_coercion() {
+        let regex = Regex::new(r"(?P<flag>\w+)")
+            .expect("compiling regex")
+            .coerce("flag", RegexType::Bool)
+            .expect("coercing group");
+
+        assert!(matches!(
+            regex.parse("maybe", &ORIGINATOR),
+            Err(Error::Custom(e)) if e.starts_with("regex: group `flag`: invalid value `maybe`")
+        ));
+    }
+
+    #[test]
+    fn unknown_group() {
+        let regex = Regex::new(r"(?P<flag>\w+)").expect("compiling regex");
+
+        assert!(matches!(
+            regex.coerce("status", RegexType::Int),
+            Err(Error::Custom(e)) if e == "regex: no capture group named `status`"
+        ));
+    }
+}
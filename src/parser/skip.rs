@@ -1,7 +1,7 @@
 use crate::{Error, ParsePart};
 
 #[derive(Debug, Eq, PartialEq, Clone)]
-#[cfg_attr(feature = "serde-serialize", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Skip;
 
 impl<'a> ParsePart<'a> for Skip {
@@ -1,13 +1,13 @@
 use crate::{Error, NomRes, ParsePart};
 use nom::{
     branch::alt,
-    bytes::complete::{tag, take_until},
+    bytes::complete::{tag, take_till, take_till1},
     combinator::map,
-    combinator::rest,
-    error::VerboseError,
+    error::{ErrorKind, ParseError, VerboseError},
     multi::{many0, many1},
-    sequence::delimited,
+    sequence::{delimited, pair},
 };
+use std::borrow::Cow;
 
 impl<'a> ParsePart<'a> for Vec<StructuredData<'a>> {
     fn parse(sd: &'a str) -> Result<(&'a str, Self), Error<'a>> {
@@ -18,17 +18,20 @@ impl<'a> ParsePart<'a> for Vec<StructuredData<'a>> {
 }
 
 #[derive(Debug, Eq, PartialEq, Clone)]
-#[cfg_attr(feature = "serde-serialize", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct StructuredData<'a> {
     pub id: &'a str,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub params: Vec<SdParam<'a>>,
 }
 
 #[derive(Debug, Eq, PartialEq, Clone)]
-#[cfg_attr(feature = "serde-serialize", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SdParam<'a> {
     pub name: &'a str,
-    pub value: &'a str,
+    /// The value as written, escapes included.
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub value: Cow<'a, str>,
 }
 
 impl<'a> From<(&'a str, Vec<SdParam<'a>>)> for StructuredData<'a> {
@@ -44,47 +47,93 @@ impl<'a> From<(&'a str, &'a str)> for SdParam<'a> {
     fn from(tuple: (&'a str, &'a str)) -> Self {
         Self {
             name: tuple.0,
-            value: tuple.1,
+            value: Cow::Borrowed(tuple.1),
         }
     }
 }
 
-fn parse_structured_data<'a>(part: &'a str) -> NomRes<&'a str, StructuredData<'a>> {
-    let (rem, data) = delimited::<_, _, _, _, VerboseError<&'a str>, _, _, _>(
-        tag("["),
-        take_until("]"),
-        tag("]"),
-    )(part)?;
+/// Serializes SD elements as nested maps, `{"id": {"name": "value"}}`, for the
+/// output mappings.
+#[cfg(feature = "serde")]
+pub(crate) struct NestedStructuredData<'m, 'a>(pub(crate) &'m [StructuredData<'a>]);
+
+#[cfg(feature = "serde")]
+impl NestedStructuredData<'_, '_> {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for NestedStructuredData<'_, '_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeMap;
 
-    let (_, data): (&'a str, StructuredData) = parse_structured_data_inner(data)?;
+        struct Params<'m, 'a>(&'m [SdParam<'a>]);
 
-    Ok((rem, data))
+        impl serde::Serialize for Params<'_, '_> {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_map(self.0.iter().map(|param| (param.name, &param.value)))
+            }
+        }
+
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for sd in self.0 {
+            map.serialize_entry(sd.id, &Params(&sd.params))?;
+        }
+        map.end()
+    }
 }
 
-fn parse_structured_data_inner(part: &str) -> NomRes<&str, StructuredData> {
+fn parse_structured_data<'a>(part: &'a str) -> NomRes<&'a str, StructuredData<'a>> {
+    use nom::character::complete::space0;
+
+    delimited(
+        tag("["),
+        parse_structured_data_inner,
+        pair(space0, tag("]")),
+    )(part)
+}
+
+fn parse_structured_data_inner(part: &str) -> NomRes<&str, StructuredData<'_>> {
     use nom::character::complete::space0;
 
     let (rem, _) = space0(part)?;
-    let (rem, id) = alt((take_until(" "), rest))(rem)?;
+    let (rem, id) = take_till(|c| c == ' ' || c == ']')(rem)?;
 
     let (rem, sd_params) = many0(parse_structured_elements)(rem)?;
 
     Ok((rem, (id, sd_params).into()))
 }
 
-fn parse_structured_elements(part: &str) -> NomRes<&str, SdParam> {
-    use nom::character::complete::space0;
+/// Parses ` name="value"`, the value being left escaped.
+fn parse_structured_elements(part: &str) -> NomRes<&str, SdParam<'_>> {
+    use nom::character::complete::space1;
 
-    let (rem, _) = space0(part)?;
-    let (rem, key_value) = alt((take_until(" "), rest))(rem)?;
-    let (key_value_rem, key) = take_until("=")(key_value)?;
-    let (value, _) = tag("=")(key_value_rem)?;
-
-    let value: &str = &value[1..value.len() - 1];
+    let (rem, _) = space1(part)?;
+    let (rem, key) = take_till1(|c| c == '=' || c == ' ' || c == ']')(rem)?;
+    let (rem, value) = delimited(tag("=\""), param_value, tag("\""))(rem)?;
 
     Ok((rem, (key, value).into()))
 }
 
+/// Takes up to the first `"` that isn't escaped by a backslash.
+fn param_value(part: &str) -> NomRes<&str, &str> {
+    let mut escaped = false;
+    for (i, c) in part.char_indices() {
+        match c {
+            '"' if !escaped => return Ok((&part[i..], &part[..i])),
+            '\\' => escaped = !escaped,
+            _ => escaped = false,
+        }
+    }
+
+    Err(nom::Err::Error(VerboseError::from_error_kind(
+        part,
+        ErrorKind::TakeUntil,
+    )))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -115,15 +164,15 @@ mod tests {
                 params: vec![
                     SdParam {
                         name: "iut",
-                        value: "3"
+                        value: "3".into()
                     },
                     SdParam {
                         name: "eventSource",
-                        value: "Application"
+                        value: "Application".into()
                     },
                     SdParam {
                         name: "eventID",
-                        value: "1011"
+                        value: "1011".into()
                     },
                 ]
             }]
@@ -157,11 +206,11 @@ mod tests {
                     params: vec![
                         SdParam {
                             name: "key",
-                            value: "value"
+                            value: "value".into()
                         },
                         SdParam {
                             name: "anotherkey",
-                            value: "anothervalue"
+                            value: "anothervalue".into()
                         }
                     ]
                 }
@@ -181,19 +230,54 @@ mod tests {
                     params: vec![
                         SdParam {
                             name: "iut",
-                            value: "3"
+                            value: "3".into()
                         },
                         SdParam {
                             name: "eventSource",
-                            value: "Application"
+                            value: "Application".into()
                         },
                         SdParam {
                             name: "eventID",
-                            value: "1011"
+                            value: "1011".into()
                         }
                     ]
                 }
             ))
         );
     }
+
+    #[test]
+    fn escaped_structured_data() {
+        let msg = r#"[a file="C:\\my \"logs\"\]" x=""][b]"#;
+        assert_eq!(
+            <Vec<StructuredData> as ParsePart>::parse(msg)
+                .expect("parsing data")
+                .1,
+            vec![
+                StructuredData {
+                    id: "a",
+                    params: vec![
+                        SdParam {
+                            name: "file",
+                            value: r#"C:\\my \"logs\"\]"#.into()
+                        },
+                        SdParam {
+                            name: "x",
+                            value: "".into()
+                        }
+                    ]
+                },
+                StructuredData {
+                    id: "b",
+                    params: vec![]
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn unterminated_structured_data() {
+        assert!(<Vec<StructuredData> as ParsePart>::parse(r#"[a x="1]"#).is_err());
+        assert!(<Vec<StructuredData> as ParsePart>::parse(r#"[a x=1]"#).is_err());
+    }
 }
@@ -0,0 +1,309 @@
+use super::{status, Command, Frames, MAX_TXNR, OFFERS};
+use crate::{format::FormatPart, Message};
+use std::{
+    collections::VecDeque,
+    error, fmt, io, mem,
+    net::{TcpStream, ToSocketAddrs},
+    time::Duration,
+};
+
+/// Responses are small, but `open`'s lists the server's offers.
+const MAX_RESPONSE_SIZE: usize = 64 * 1024;
+
+/// Sends syslog messages over RELP, keeping each message until the server
+/// acknowledged it.
+///
+/// Up to a window of messages are sent before waiting for acknowledgements.
+/// When the connection fails, the client reconnects once and sends the
+/// unacknowledged messages again before giving up with the error; they are
+/// kept, and sent again by the next call. A message the server rejects is
+/// dropped, and reported by the next [`flush`](Self::flush) or
+/// [`close`](Self::close) as an `InvalidInput` error holding a
+/// [`RelpRejected`]. Messages are thus
+/// delivered at least once, and twice when the acknowledgement of a message
+/// that arrived is lost, unless the queue of unacknowledged messages is full:
+/// a new message is then dropped and reported as an error if no room could be
+/// made by sending.
+///
+/// The client connects on the first message.
+#[derive(Debug)]
+pub struct RelpClient {
+    addr: String,
+    window: usize,
+    capacity: usize,
+    timeout: Duration,
+    connection: Option<Connection>,
+    unacked: VecDeque<Unacked>,
+    /// How many of the messages at the front of `unacked` were sent on the
+    /// current connection.
+    sent: usize,
+    /// The messages rejected since the last flush.
+    rejected: Vec<String>,
+}
+
+/// The messages the server rejected, the inner error of the `InvalidInput`
+/// error returned by [`RelpClient::flush`] and [`RelpClient::close`]. Get it
+/// with `err.get_ref().and_then(|err| err.downcast_ref::<RelpRejected>())`.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct RelpRejected {
+    pub messages: Vec<String>,
+}
+
+impl fmt::Display for RelpRejected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.messages.as_slice() {
+            [message] => write!(f, "relp: server rejected `{}`", message),
+            messages => write!(f, "relp: server rejected {} messages", messages.len()),
+        }
+    }
+}
+
+impl error::Error for RelpRejected {}
+
+#[derive(Debug)]
+struct Connection {
+    frames: Frames<TcpStream>,
+    next_txnr: u32,
+}
+
+#[derive(Debug)]
+struct Unacked {
+    txnr: u32,
+    text: String,
+}
+
+impl RelpClient {
+    /// A client of the server at `addr`, resolved on every connection.
+    pub fn new(addr: impl Into<String>) -> Self {
+        Self {
+            addr: addr.into(),
+            window: 128,
+            capacity: 10_000,
+            timeout: Duration::from_secs(30),
+            connection: None,
+            unacked: VecDeque::new(),
+            sent: 0,
+            rejected: Vec::new(),
+        }
+    }
+
+    /// How many messages may await an acknowledgement, defaults to 128.
+    pub fn window(mut self, size: usize) -> Self {
+        self.window = size.max(1);
+        self
+    }
+
+    /// How many unacknowledged messages are kept, defaults to 10000.
+    pub fn queue_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+
+    /// The timeout of connecting and of waiting for the server, defaults to 30
+    /// seconds. Applies to the next connection.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sends `message` formatted as RFC 5424, waiting for acknowledgements if
+    /// the window is full. Errors are about the connection or the queue; the
+    /// messages the server rejected meanwhile are only reported by
+    /// [`flush`](Self::flush).
+    pub fn send<T, S, M>(&mut self, message: &Message<'_, T, S, M>) -> io::Result<()>
+    where
+        T: FormatPart,
+        S: FormatPart,
+        M: AsRef<str>,
+    {
+        self.send_formatted(message.to_string())
+    }
+
+    /// Sends a message that is already formatted, see [`send`](Self::send).
+    pub fn send_formatted(&mut self, text: String) -> io::Result<()> {
+        if self.unacked.len() >= self.capacity {
+            let limit = self.capacity - 1;
+            let result = self.retrying(|client| client.transmit(limit));
+            if let Err(err) = result {
+                if self.unacked.len() >= self.capacity {
+                    return Err(io::Error::new(
+                        err.kind(),
+                        format!(
+                            "relp: queue of {} messages is full, dropped the new one: {}",
+                            self.capacity, err
+                        ),
+                    ));
+                }
+            }
+        }
+        self.unacked.push_back(Unacked { txnr: 0, text });
+        let window = self.window;
+        self.retrying(|client| client.transmit(window))
+    }
+
+    /// Waits for every message to be acknowledged, then reports the messages
+    /// the server rejected since the last flush as an `InvalidInput` error
+    /// holding a [`RelpRejected`].
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.retrying(|client| client.transmit(0))?;
+        self.take_rejected()
+    }
+
+    /// How many messages were not acknowledged yet.
+    pub fn unacked(&self) -> usize {
+        self.unacked.len()
+    }
+
+    /// Flushes and closes the session, reporting rejected messages like
+    /// [`flush`](Self::flush) once the session is closed.
+    pub fn close(mut self) -> io::Result<()> {
+        self.retrying(|client| client.transmit(0))?;
+        if let Some(mut connection) = self.connection.take() {
+            let txnr = connection.txnr();
+            connection.frames.write_frame(txnr, "close", b"")?;
+            loop {
+                match connection.read_response()? {
+                    Some((rsp, _)) if rsp == txnr => break,
+                    Some(_) => {}
+                    None => break,
+                }
+            }
+        }
+        self.take_rejected()
+    }
+
+    fn take_rejected(&mut self) -> io::Result<()> {
+        if self.rejected.is_empty() {
+            return Ok(());
+        }
+        let rejected = RelpRejected {
+            messages: mem::take(&mut self.rejected),
+        };
+        Err(io::Error::new(io::ErrorKind::InvalidInput, rejected))
+    }
+
+    /// Runs `transmit`, reconnecting once if it failed.
+    fn retrying<F>(&mut self, mut transmit: F) -> io::Result<()>
+    where
+        F: FnMut(&mut Self) -> io::Result<()>,
+    {
+        let retry = |err: &io::Error| err.kind() != io::ErrorKind::InvalidInput;
+        let result = match transmit(self) {
+            Err(err) if retry(&err) => {
+                self.connection = None;
+                transmit(self)
+            }
+            result => result,
+        };
+        if matches!(&result, Err(err) if retry(err)) {
+            self.connection = None;
+        }
+        result
+    }
+
+    /// Sends the messages not sent on the current connection yet, connecting
+    /// first if needed, and waits until at most `limit` are unacknowledged.
+    fn transmit(&mut self, limit: usize) -> io::Result<()> {
+        let connection = match &mut self.connection {
+            Some(connection) => connection,
+            None => {
+                self.sent = 0;
+                self.connection
+                    .insert(Connection::open(&self.addr, self.timeout)?)
+            }
+        };
+
+        loop {
+            if self.sent < self.unacked.len() && self.sent < self.window {
+                let unacked = &mut self.unacked[self.sent];
+                unacked.txnr = connection.txnr();
+                connection
+                    .frames
+                    .write_frame(unacked.txnr, "syslog", unacked.text.as_bytes())?;
+                self.sent += 1;
+            } else if self.unacked.len() > limit {
+                let (txnr, status) = connection.read_response()?.ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        "relp: server closed the connection",
+                    )
+                })?;
+                let Some(index) = self
+                    .unacked
+                    .iter()
+                    .take(self.sent)
+                    .position(|unacked| unacked.txnr == txnr)
+                else {
+                    continue;
+                };
+                let unacked = self.unacked.remove(index);
+                self.sent -= 1;
+                if status != Some(200) {
+                    self.rejected.extend(unacked.map(|unacked| unacked.text));
+                }
+            } else {
+                return Ok(());
+            }
+        }
+    }
+}
+
+impl Connection {
+    fn open(addr: &str, timeout: Duration) -> io::Result<Self> {
+        let addr = addr.to_socket_addrs()?.next().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("relp: `{}` resolved to no address", addr),
+            )
+        })?;
+        let stream = TcpStream::connect_timeout(&addr, timeout)?;
+        stream.set_read_timeout(Some(timeout))?;
+        stream.set_write_timeout(Some(timeout))?;
+
+        let mut connection = Self {
+            frames: Frames::new(stream, MAX_RESPONSE_SIZE),
+            next_txnr: 1,
+        };
+        let txnr = connection.txnr();
+        connection
+            .frames
+            .write_frame(txnr, "open", OFFERS.as_bytes())?;
+        let Some((_, status)) = connection.read_response()? else {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "relp: server closed the connection on open",
+            ));
+        };
+        if status != Some(200) {
+            return Err(io::Error::other("relp: server refused to open a session"));
+        }
+
+        Ok(connection)
+    }
+
+    fn txnr(&mut self) -> u32 {
+        let txnr = self.next_txnr;
+        self.next_txnr = if txnr == MAX_TXNR { 1 } else { txnr + 1 };
+        txnr
+    }
+
+    /// Reads the next `rsp`, with its transaction number and status. A
+    /// `serverclose` is an error.
+    fn read_response(&mut self) -> io::Result<Option<(u32, Option<u16>)>> {
+        loop {
+            let Some(frame) = self.frames.read_frame()? else {
+                return Ok(None);
+            };
+            match frame.command {
+                Command::Rsp => return Ok(Some((frame.txnr, status(self.frames.data(&frame))))),
+                Command::ServerClose => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::ConnectionAborted,
+                        "relp: server closed the session",
+                    ))
+                }
+                _ => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,284 @@
+//! RELP, the Reliable Event Logging Protocol of rsyslogd, see
+//! [its specification](https://github.com/rsyslog/librelp/blob/master/doc/relp.html).
+//!
+//! Every frame is `TXNR SP COMMAND SP DATALEN [SP DATA] LF`. A client opens a
+//! session with `open`, sends messages with `syslog` and ends it with `close`;
+//! the server acknowledges each command with a `rsp` of the same transaction
+//! number, so that unacknowledged messages can be sent again.
+
+mod client;
+mod server;
+
+pub use client::{RelpClient, RelpRejected};
+pub use server::{RelpConnection, RelpReceiver};
+
+use std::{
+    io::{self, Read, Write},
+    ops::Range,
+};
+
+/// The offers of both sides on `open`.
+const OFFERS: &str = concat!(
+    "relp_version=0\nrelp_software=",
+    env!("CARGO_PKG_NAME"),
+    ",",
+    env!("CARGO_PKG_VERSION"),
+    "\ncommands=syslog"
+);
+
+/// Transaction numbers wrap around to 1 after this one.
+const MAX_TXNR: u32 = 999_999_999;
+
+/// The longest command name.
+const MAX_COMMAND_LEN: usize = 32;
+
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+enum Command {
+    Open,
+    Syslog,
+    Close,
+    Rsp,
+    ServerClose,
+    Other,
+}
+
+impl Command {
+    fn parse(command: &[u8]) -> Self {
+        match command {
+            b"open" => Command::Open,
+            b"syslog" => Command::Syslog,
+            b"close" => Command::Close,
+            b"rsp" => Command::Rsp,
+            b"serverclose" => Command::ServerClose,
+            _ => Command::Other,
+        }
+    }
+}
+
+/// The header of a frame read by [`Frames`], its data being a range of the
+/// buffer.
+#[derive(Debug, Eq, PartialEq, Clone)]
+struct Frame {
+    txnr: u32,
+    command: Command,
+    data: Range<usize>,
+}
+
+/// Reads the frames of a RELP stream. Unlike syslog streams, a broken frame
+/// can't be skipped, so it is an `InvalidData` error ending the stream.
+#[derive(Debug)]
+struct Frames<S> {
+    stream: S,
+    max_frame_size: usize,
+    buf: Vec<u8>,
+    /// The end of the last frame read.
+    pos: usize,
+}
+
+impl<S> Frames<S> {
+    fn new(stream: S, max_frame_size: usize) -> Self {
+        Self {
+            stream,
+            max_frame_size,
+            buf: Vec::new(),
+            pos: 0,
+        }
+    }
+
+    fn data(&self, frame: &Frame) -> &[u8] {
+        &self.buf[frame.data.clone()]
+    }
+}
+
+impl<S: Read> Frames<S> {
+    /// Reads the next frame, `None` once the stream ended between frames.
+    fn read_frame(&mut self) -> io::Result<Option<Frame>> {
+        self.buf.drain(..self.pos);
+        self.pos = 0;
+
+        loop {
+            if let Some((frame, len)) = parse_frame(&self.buf, self.max_frame_size)? {
+                self.pos = len;
+                return Ok(Some(frame));
+            }
+
+            let len = self.buf.len();
+            self.buf.resize(len + 8192, 0);
+            let read = loop {
+                match self.stream.read(&mut self.buf[len..]) {
+                    Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                    result => break result,
+                }
+            };
+            self.buf.truncate(len + read.as_ref().copied().unwrap_or(0));
+            match read? {
+                0 if len == 0 => return Ok(None),
+                0 => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        "relp: stream ended inside a frame",
+                    ))
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+impl<S: Write> Frames<S> {
+    fn write_frame(&mut self, txnr: u32, command: &str, data: &[u8]) -> io::Result<()> {
+        let mut frame = format!("{} {} {}", txnr, command, data.len()).into_bytes();
+        if !data.is_empty() {
+            frame.push(b' ');
+            frame.extend_from_slice(data);
+        }
+        frame.push(b'\n');
+        self.stream.write_all(&frame)?;
+        self.stream.flush()
+    }
+}
+
+fn invalid(reason: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("relp: {}", reason))
+}
+
+/// Parses the frame at the start of `buf` and its length, `None` if more
+/// bytes are needed.
+fn parse_frame(buf: &[u8], max_frame_size: usize) -> io::Result<Option<(Frame, usize)>> {
+    let Some((txnr, rem)) = field(buf, 9, u8::is_ascii_digit)? else {
+        return Ok(None);
+    };
+    let Some((command, rem)) = field(rem, MAX_COMMAND_LEN, u8::is_ascii_alphabetic)? else {
+        return Ok(None);
+    };
+    let digits = rem.iter().take_while(|b| b.is_ascii_digit()).count();
+    let Some(&after) = rem.get(digits) else {
+        if digits > 9 {
+            return Err(invalid(format!("invalid DATALEN `{}`", lossy(rem))));
+        }
+        return Ok(None);
+    };
+
+    let number = |digits: &[u8]| {
+        std::str::from_utf8(digits)
+            .ok()
+            .filter(|digits| !digits.is_empty() && digits.len() <= 9)
+            .and_then(|digits| digits.parse::<usize>().ok())
+    };
+    let txnr =
+        number(txnr).ok_or_else(|| invalid(format!("invalid TXNR `{}`", lossy(txnr))))? as u32;
+    let len = number(&rem[..digits])
+        .ok_or_else(|| invalid(format!("invalid DATALEN `{}`", lossy(&rem[..digits]))))?;
+    if len > max_frame_size {
+        return Err(invalid(format!(
+            "frame of {} bytes exceeds the maximum of {}",
+            len, max_frame_size
+        )));
+    }
+
+    let data_start = buf.len() - rem.len() + digits + usize::from(len > 0);
+    let expected = if len > 0 { b' ' } else { b'\n' };
+    if after != expected {
+        return Err(invalid(format!(
+            "unexpected `{}` after DATALEN",
+            after as char
+        )));
+    }
+    let data_end = data_start + len;
+    match buf.get(data_end) {
+        None => Ok(None),
+        Some(b'\n') => Ok(Some((
+            Frame {
+                txnr,
+                command: Command::parse(command),
+                data: data_start..data_end,
+            },
+            data_end + 1,
+        ))),
+        Some(_) => Err(invalid(format!("frame {} doesn't end with LF", txnr))),
+    }
+}
+
+/// Splits `buf` at the first space, the field before it having at most `max`
+/// bytes that satisfy `valid`.
+fn field(buf: &[u8], max: usize, valid: fn(&u8) -> bool) -> io::Result<Option<(&[u8], &[u8])>> {
+    let len = buf.iter().take(max + 1).take_while(|b| valid(b)).count();
+    match buf.get(len) {
+        None if len <= max => Ok(None),
+        Some(b' ') if len > 0 && len <= max => Ok(Some((&buf[..len], &buf[len + 1..]))),
+        _ => Err(invalid(format!(
+            "invalid frame header `{}`",
+            lossy(&buf[..buf.len().min(max + 1)])
+        ))),
+    }
+}
+
+fn lossy(bytes: &[u8]) -> std::borrow::Cow<'_, str> {
+    String::from_utf8_lossy(bytes)
+}
+
+/// The status code at the start of `rsp` data, `200 OK`.
+fn status(data: &[u8]) -> Option<u16> {
+    std::str::from_utf8(data.get(..3)?).ok()?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frames(input: &[u8]) -> Vec<(u32, Command, String)> {
+        let mut frames = Frames::new(input, 64);
+        let mut parsed = Vec::new();
+        while let Some(frame) = frames.read_frame().expect("reading frame") {
+            let data = String::from_utf8_lossy(frames.data(&frame)).into_owned();
+            parsed.push((frame.txnr, frame.command, data));
+        }
+        parsed
+    }
+
+    #[test]
+    fn read_frames() {
+        assert_eq!(
+            frames(b"1 open 5 a\nb=c\n2 syslog 3 abc\n3 close 0\n4 x 0\n"),
+            vec![
+                (1, Command::Open, "a\nb=c".into()),
+                (2, Command::Syslog, "abc".into()),
+                (3, Command::Close, String::new()),
+                (4, Command::Other, String::new()),
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_frames() {
+        let error = |input: &[u8]| {
+            Frames::new(input, 8)
+                .read_frame()
+                .expect_err("reading an invalid frame")
+                .to_string()
+        };
+
+        assert_eq!(
+            error(b"x syslog 1 a\n"),
+            "relp: invalid frame header `x syslog 1`"
+        );
+        assert_eq!(
+            error(b"1 syslog 10 aaaaaaaaaa\n"),
+            "relp: frame of 10 bytes exceeds the maximum of 8"
+        );
+        assert_eq!(
+            error(b"1 syslog 1 ab\n"),
+            "relp: frame 1 doesn't end with LF"
+        );
+        assert_eq!(error(b"1 syslog 3 ab"), "relp: stream ended inside a frame");
+    }
+
+    #[test]
+    fn write_frames() {
+        let mut frames = Frames::new(Vec::new(), 64);
+        frames.write_frame(7, "rsp", b"200 OK").expect("writing");
+        frames.write_frame(8, "close", b"").expect("writing");
+
+        assert_eq!(frames.stream, b"7 rsp 6 200 OK\n8 close 0\n");
+    }
+}
@@ -0,0 +1,179 @@
+use super::{Command, Frames, OFFERS};
+use crate::{
+    net::{serve_connections, DEFAULT_MAX_CONNECTIONS, DEFAULT_READ_TIMEOUT},
+    Error, Message, ParseMsg, ParsePart,
+};
+use std::{
+    io::{self, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    time::Duration,
+};
+
+/// The default size limit of `syslog` frames, as rsyslogd's.
+const DEFAULT_MAX_FRAME_SIZE: usize = 128 * 1024;
+
+/// Receives syslog messages over RELP, acknowledging each message once it was
+/// handled.
+#[derive(Debug)]
+pub struct RelpReceiver {
+    listener: TcpListener,
+    max_frame_size: usize,
+    read_timeout: Option<Duration>,
+    max_connections: usize,
+}
+
+impl RelpReceiver {
+    pub fn bind<A: ToSocketAddrs>(addr: A) -> io::Result<Self> {
+        TcpListener::bind(addr).map(Self::new)
+    }
+
+    pub fn new(listener: TcpListener) -> Self {
+        Self {
+            listener,
+            max_frame_size: DEFAULT_MAX_FRAME_SIZE,
+            read_timeout: Some(DEFAULT_READ_TIMEOUT),
+            max_connections: DEFAULT_MAX_CONNECTIONS,
+        }
+    }
+
+    /// A longer frame ends its connection. Defaults to 128 KiB.
+    pub fn max_frame_size(mut self, size: usize) -> Self {
+        self.max_frame_size = size;
+        self
+    }
+
+    /// Sessions that stay silent for longer are closed, `None` keeping them
+    /// open. Defaults to 5 minutes.
+    pub fn read_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.read_timeout = timeout;
+        self
+    }
+
+    /// How many sessions [`run`](Self::run) reads at once, further ones
+    /// waiting to be accepted until one closes. Defaults to 1024.
+    pub fn max_connections(mut self, max: usize) -> Self {
+        self.max_connections = max;
+        self
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Waits for the next connection, whose messages can then be read with
+    /// [`RelpConnection::read_with`].
+    pub fn accept(&self) -> io::Result<(RelpConnection<TcpStream>, SocketAddr)> {
+        let (stream, peer) = self.listener.accept()?;
+        stream.set_read_timeout(self.read_timeout)?;
+
+        Ok((
+            RelpConnection::new(stream).max_frame_size(self.max_frame_size),
+            peer,
+        ))
+    }
+
+    /// Reads every session on its own thread, up to
+    /// [`max_connections`](Self::max_connections) at once, handing each
+    /// message to `handler` along with the address of its peer, until
+    /// accepting a connection fails for good, as errors such as running out of
+    /// file descriptors are waited out. The I/O error that ends a session,
+    /// such as the read timeout, is handed over too.
+    pub fn run<F>(&self, handler: F) -> io::Result<()>
+    where
+        F: Fn(SocketAddr, Result<Message<'_>, Error<'_>>) + Send + Sync + 'static,
+    {
+        serve_connections(
+            self.max_connections,
+            || self.accept(),
+            move |(mut connection, peer): (RelpConnection<TcpStream>, SocketAddr)| loop {
+                match connection.read_with(|message| handler(peer, message)) {
+                    Ok(Some(())) => {}
+                    Ok(None) => break,
+                    Err(err) => {
+                        handler(peer, Err(Error::Custom(format!("relp: {}", err))));
+                        break;
+                    }
+                }
+            },
+        )
+    }
+}
+
+/// The server side of a RELP session, answering `open` and `close` and
+/// acknowledging `syslog` commands.
+#[derive(Debug)]
+pub struct RelpConnection<S> {
+    frames: Frames<S>,
+    opened: bool,
+    text: String,
+}
+
+impl<S: Read + Write> RelpConnection<S> {
+    pub fn new(stream: S) -> Self {
+        Self {
+            frames: Frames::new(stream, DEFAULT_MAX_FRAME_SIZE),
+            opened: false,
+            text: String::new(),
+        }
+    }
+
+    /// A longer frame is an error. Defaults to 128 KiB.
+    pub fn max_frame_size(mut self, size: usize) -> Self {
+        self.frames.max_frame_size = size;
+        self
+    }
+
+    pub fn get_ref(&self) -> &S {
+        &self.frames.stream
+    }
+
+    /// Reads up to the next message and hands it to `handler` parsed, borrowing
+    /// from the connection's buffers. The message is acknowledged once
+    /// `handler` returns, even if it could not be parsed since sending it again
+    /// wouldn't help. `None` once the session was closed or the stream ended.
+    pub fn read_with<'a, T, SD, M, R, F>(&'a mut self, handler: F) -> io::Result<Option<R>>
+    where
+        T: ParsePart<'a>,
+        SD: ParsePart<'a>,
+        M: ParseMsg<'a>,
+        F: FnOnce(Result<Message<'a, T, SD, M>, Error<'a>>) -> R,
+    {
+        let frame = loop {
+            let Some(frame) = self.frames.read_frame()? else {
+                return Ok(None);
+            };
+            match frame.command {
+                Command::Syslog if self.opened => break frame,
+                Command::Syslog => {
+                    self.frames
+                        .write_frame(frame.txnr, "rsp", b"500 session not opened")?;
+                }
+                Command::Open => {
+                    self.opened = true;
+                    let data = format!("200 OK\n{}", OFFERS);
+                    self.frames
+                        .write_frame(frame.txnr, "rsp", data.as_bytes())?;
+                }
+                Command::Close => {
+                    self.frames.write_frame(frame.txnr, "rsp", b"")?;
+                    return Ok(None);
+                }
+                // Not expected from clients, and not worth an answer.
+                Command::Rsp | Command::ServerClose => {}
+                Command::Other => {
+                    self.frames
+                        .write_frame(frame.txnr, "rsp", b"500 unknown command")?;
+                }
+            }
+        };
+
+        let Self { frames, text, .. } = self;
+        text.clear();
+        text.push_str(&String::from_utf8_lossy(frames.data(&frame)));
+        let text: &'a String = text;
+        let result = handler(Message::parse(text.trim_end_matches('\n')));
+        frames.write_frame(frame.txnr, "rsp", b"200 OK")?;
+
+        Ok(Some(result))
+    }
+}
@@ -0,0 +1,395 @@
+//! A syslog sender, delivering formatted messages from a background thread.
+
+mod transport;
+
+pub use transport::Transport;
+
+use crate::{format::FormatPart, Message};
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Condvar, Mutex, MutexGuard,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+/// What [`Sender::send`] does with a message when the queue is full.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum Overflow {
+    /// The message is dropped.
+    DropNewest,
+    /// The oldest queued message is dropped to make room.
+    DropOldest,
+    /// `send` waits for room.
+    Block,
+}
+
+/// Configures a [`Sender`] before starting it.
+#[derive(Debug, Clone)]
+pub struct SenderBuilder {
+    transport: Transport,
+    queue_capacity: usize,
+    overflow: Overflow,
+    batch_size: usize,
+    connect_timeout: Duration,
+    min_backoff: Duration,
+    max_backoff: Duration,
+}
+
+impl SenderBuilder {
+    /// How many messages are kept while they can't be delivered, defaults to
+    /// 10000.
+    pub fn queue_capacity(mut self, capacity: usize) -> Self {
+        self.queue_capacity = capacity.max(1);
+        self
+    }
+
+    /// Defaults to [`Overflow::DropNewest`].
+    pub fn overflow(mut self, overflow: Overflow) -> Self {
+        self.overflow = overflow;
+        self
+    }
+
+    /// How many queued messages are written at once, defaults to 64.
+    pub fn batch_size(mut self, size: usize) -> Self {
+        self.batch_size = size.max(1);
+        self
+    }
+
+    /// The timeout of connecting, of TLS handshakes and of writes, defaults
+    /// to 5 seconds.
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = timeout;
+        self
+    }
+
+    /// The wait after a failed connection attempt, doubled on every failure
+    /// from `min` up to `max`. Defaults to 100 milliseconds up to 30 seconds.
+    pub fn backoff(mut self, min: Duration, max: Duration) -> Self {
+        self.min_backoff = min;
+        self.max_backoff = max.max(min);
+        self
+    }
+
+    /// Starts the background thread, which connects on the first message.
+    pub fn build(self) -> Sender {
+        let shared = Arc::new(Shared {
+            state: Mutex::new(State {
+                queue: VecDeque::new(),
+                in_flight: 0,
+                closed: false,
+            }),
+            changed: Condvar::new(),
+            dropped: AtomicU64::new(0),
+        });
+        let queue_capacity = self.queue_capacity;
+        let overflow = self.overflow;
+        let worker = {
+            let shared = Arc::clone(&shared);
+            thread::spawn(move || Worker::new(self, shared).run())
+        };
+
+        Sender {
+            shared,
+            queue_capacity,
+            overflow,
+            worker: Some(worker),
+        }
+    }
+}
+
+/// Sends syslog messages over a [`Transport`], formatted as RFC 5424.
+///
+/// Messages are queued and written by a background thread in batches, so
+/// `send` doesn't wait for the network. The thread reconnects with
+/// exponential backoff after a failure, keeping messages in the bounded queue
+/// meanwhile, and a batch that failed on a stream is written again on the
+/// next connection: messages may be delivered twice, but are only lost when
+/// the queue overflows or the sender is dropped while disconnected.
+///
+/// Dropping the sender delivers the queued messages, making one last
+/// connection attempt if needed.
+#[derive(Debug)]
+pub struct Sender {
+    shared: Arc<Shared>,
+    queue_capacity: usize,
+    overflow: Overflow,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl Sender {
+    pub fn builder(transport: Transport) -> SenderBuilder {
+        SenderBuilder {
+            transport,
+            queue_capacity: 10_000,
+            overflow: Overflow::DropNewest,
+            batch_size: 64,
+            connect_timeout: Duration::from_secs(5),
+            min_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(30),
+        }
+    }
+
+    /// A sender with the default configuration.
+    pub fn new(transport: Transport) -> Self {
+        Self::builder(transport).build()
+    }
+
+    /// Queues `message`, returning whether it was queued rather than dropped
+    /// by [`Overflow::DropNewest`].
+    pub fn send<T, S, M>(&self, message: &Message<'_, T, S, M>) -> bool
+    where
+        T: FormatPart,
+        S: FormatPart,
+        M: AsRef<str>,
+    {
+        self.send_formatted(message.to_string())
+    }
+
+    /// Queues a message that is already formatted, without framing.
+    pub fn send_formatted(&self, text: String) -> bool {
+        let mut state = self.shared.lock();
+        while state.queue.len() >= self.queue_capacity {
+            match self.overflow {
+                Overflow::DropNewest => {
+                    self.shared.dropped.fetch_add(1, Ordering::Relaxed);
+                    return false;
+                }
+                Overflow::DropOldest => {
+                    state.queue.pop_front();
+                    self.shared.dropped.fetch_add(1, Ordering::Relaxed);
+                }
+                Overflow::Block => state = self.shared.wait(state),
+            }
+        }
+        state.queue.push_back(text);
+        self.shared.changed.notify_all();
+
+        true
+    }
+
+    /// Waits up to `timeout` for the queued messages to be written, returning
+    /// whether they were.
+    pub fn flush(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        let mut state = self.shared.lock();
+        while !state.queue.is_empty() || state.in_flight > 0 {
+            let now = Instant::now();
+            if now >= deadline {
+                return false;
+            }
+            state = self.shared.wait_timeout(state, deadline - now);
+        }
+        true
+    }
+
+    /// How many messages were dropped, by the overflow policy, as datagrams
+    /// that could not be sent or when dropped while disconnected.
+    pub fn dropped(&self) -> u64 {
+        self.shared.dropped.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for Sender {
+    fn drop(&mut self) {
+        self.shared.lock().closed = true;
+        self.shared.changed.notify_all();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Shared {
+    state: Mutex<State>,
+    /// Notified whenever `state` changes.
+    changed: Condvar,
+    dropped: AtomicU64,
+}
+
+#[derive(Debug)]
+struct State {
+    queue: VecDeque<String>,
+    /// The messages taken by the worker and not written yet.
+    in_flight: usize,
+    closed: bool,
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn wait<'a>(&self, state: MutexGuard<'a, State>) -> MutexGuard<'a, State> {
+        self.changed
+            .wait(state)
+            .unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn wait_timeout<'a>(
+        &self,
+        state: MutexGuard<'a, State>,
+        timeout: Duration,
+    ) -> MutexGuard<'a, State> {
+        self.changed
+            .wait_timeout(state, timeout)
+            .map(|(state, _)| state)
+            .unwrap_or_else(|err| err.into_inner().0)
+    }
+}
+
+struct Worker {
+    config: SenderBuilder,
+    shared: Arc<Shared>,
+    connection: Option<transport::Connection>,
+    backoff: Duration,
+}
+
+impl Worker {
+    fn new(config: SenderBuilder, shared: Arc<Shared>) -> Self {
+        Self {
+            backoff: config.min_backoff,
+            config,
+            shared,
+            connection: None,
+        }
+    }
+
+    fn run(mut self) {
+        while let Some(mut batch) = self.next_batch() {
+            while !batch.is_empty() {
+                if !self.write(&mut batch) {
+                    // Closed while disconnected, what is left is lost.
+                    let mut state = self.shared.lock();
+                    let lost = batch.len() + state.queue.len();
+                    self.shared
+                        .dropped
+                        .fetch_add(lost as u64, Ordering::Relaxed);
+                    state.queue.clear();
+                    state.in_flight = 0;
+                    self.shared.changed.notify_all();
+                    return;
+                }
+            }
+            self.shared.lock().in_flight = 0;
+            self.shared.changed.notify_all();
+        }
+    }
+
+    /// Waits for messages, `None` once closed and there are none left.
+    fn next_batch(&self) -> Option<Vec<String>> {
+        let mut state = self.shared.lock();
+        while state.queue.is_empty() {
+            if state.closed {
+                return None;
+            }
+            state = self.shared.wait(state);
+        }
+        let len = state.queue.len().min(self.config.batch_size);
+        let batch = state.queue.drain(..len).collect::<Vec<_>>();
+        state.in_flight = batch.len();
+        self.shared.changed.notify_all();
+
+        Some(batch)
+    }
+
+    /// Writes (some of) `batch`, connecting first if needed. Returns `false`
+    /// when connecting failed after the sender was closed.
+    fn write(&mut self, batch: &mut Vec<String>) -> bool {
+        let connection = match &mut self.connection {
+            Some(connection) => connection,
+            None => match self.config.transport.connect(self.config.connect_timeout) {
+                Ok(connection) => {
+                    self.backoff = self.config.min_backoff;
+                    self.connection.insert(connection)
+                }
+                Err(_) => {
+                    if !self.back_off() {
+                        return false;
+                    }
+                    self.backoff = (self.backoff * 2).min(self.config.max_backoff);
+                    return true;
+                }
+            },
+        };
+
+        let mut dropped = 0;
+        if connection.write(batch, &mut dropped).is_err() {
+            self.connection = None;
+        }
+        self.shared.dropped.fetch_add(dropped, Ordering::Relaxed);
+        true
+    }
+
+    /// Waits for the backoff to pass, or for the sender to be closed so that a
+    /// last attempt is made. Returns `false` if it was closed already.
+    fn back_off(&self) -> bool {
+        let deadline = Instant::now() + self.backoff;
+        let mut state = self.shared.lock();
+        if state.closed {
+            return false;
+        }
+        while !state.closed {
+            let now = Instant::now();
+            if now >= deadline {
+                break;
+            }
+            state = self.shared.wait_timeout(state, deadline - now);
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unreachable(overflow: Overflow) -> Sender {
+        // Connecting to port 0 fails right away.
+        Sender::builder(Transport::Tcp("127.0.0.1:0".into()))
+            .queue_capacity(2)
+            .batch_size(1)
+            .overflow(overflow)
+            .backoff(Duration::from_secs(60), Duration::from_secs(60))
+            .build()
+    }
+
+    fn queued(sender: &Sender) -> Vec<String> {
+        sender.shared.lock().queue.iter().cloned().collect()
+    }
+
+    #[test]
+    fn drop_newest() {
+        let sender = unreachable(Overflow::DropNewest);
+        sender.send_formatted("0".into());
+        // Let the worker take the first message and fail to connect.
+        assert!(!sender.flush(Duration::from_millis(100)));
+
+        assert!(sender.send_formatted("1".into()));
+        assert!(sender.send_formatted("2".into()));
+        assert!(!sender.send_formatted("3".into()));
+        assert_eq!(queued(&sender), vec!["1", "2"]);
+        assert_eq!(sender.dropped(), 1);
+    }
+
+    #[test]
+    fn drop_oldest() {
+        let sender = unreachable(Overflow::DropOldest);
+        sender.send_formatted("0".into());
+        assert!(!sender.flush(Duration::from_millis(100)));
+
+        for text in ["1", "2", "3"] {
+            assert!(sender.send_formatted(text.into()));
+        }
+        assert_eq!(queued(&sender), vec!["2", "3"]);
+        assert_eq!(sender.dropped(), 1);
+
+        let shared = Arc::clone(&sender.shared);
+        drop(sender);
+        // The in flight message and the queued ones are lost on close.
+        assert_eq!(shared.dropped.load(Ordering::Relaxed), 4);
+    }
+}
@@ -0,0 +1,156 @@
+#[cfg(feature = "tls")]
+use rustls::{pki_types::ServerName, ClientConfig, ClientConnection, StreamOwned};
+#[cfg(feature = "tls")]
+use std::sync::Arc;
+use std::{
+    io::{self, Write},
+    net::{SocketAddr, TcpStream, ToSocketAddrs, UdpSocket},
+    time::Duration,
+};
+#[cfg(unix)]
+use std::{
+    os::unix::net::{UnixDatagram, UnixStream},
+    path::PathBuf,
+};
+
+/// Where a [`Sender`](super::Sender) delivers messages. Addresses are
+/// resolved again on every connection attempt.
+#[derive(Debug, Clone)]
+pub enum Transport {
+    /// RFC 5426, a datagram per message.
+    Udp(String),
+    /// RFC 6587, with octet counting.
+    Tcp(String),
+    /// RFC 5425, with octet counting. The server certificate is checked
+    /// against `server_name` by `config`.
+    #[cfg(feature = "tls")]
+    Tls {
+        addr: String,
+        server_name: ServerName<'static>,
+        config: Arc<ClientConfig>,
+    },
+    /// A datagram per message, as `syslog(3)` writes to `/dev/log`.
+    #[cfg(unix)]
+    UnixDatagram(PathBuf),
+    /// With octet counting.
+    #[cfg(unix)]
+    UnixStream(PathBuf),
+}
+
+impl Transport {
+    pub(super) fn connect(&self, timeout: Duration) -> io::Result<Connection> {
+        match self {
+            Transport::Udp(addr) => {
+                let addr = resolve(addr)?;
+                let local = if addr.is_ipv4() {
+                    "0.0.0.0:0"
+                } else {
+                    "[::]:0"
+                };
+                let socket = UdpSocket::bind(local)?;
+                socket.connect(addr)?;
+                Ok(Connection::Udp(socket))
+            }
+            Transport::Tcp(addr) => Ok(Connection::Stream(Box::new(connect_tcp(addr, timeout)?))),
+            #[cfg(feature = "tls")]
+            Transport::Tls {
+                addr,
+                server_name,
+                config,
+            } => {
+                let mut connection = ClientConnection::new(Arc::clone(config), server_name.clone())
+                    .map_err(io::Error::other)?;
+                let mut stream = connect_tcp(addr, timeout)?;
+                // Completed here, bounded by the read timeout, rather than by
+                // the first write.
+                while connection.is_handshaking() {
+                    connection.complete_io(&mut stream)?;
+                }
+                Ok(Connection::Stream(Box::new(StreamOwned::new(
+                    connection, stream,
+                ))))
+            }
+            #[cfg(unix)]
+            Transport::UnixDatagram(path) => {
+                let socket = UnixDatagram::unbound()?;
+                socket.connect(path)?;
+                socket.set_write_timeout(Some(timeout))?;
+                Ok(Connection::UnixDatagram(socket))
+            }
+            #[cfg(unix)]
+            Transport::UnixStream(path) => {
+                let stream = UnixStream::connect(path)?;
+                stream.set_write_timeout(Some(timeout))?;
+                Ok(Connection::Stream(Box::new(stream)))
+            }
+        }
+    }
+}
+
+fn resolve(addr: &str) -> io::Result<SocketAddr> {
+    addr.to_socket_addrs()?.next().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("sender: `{}` resolved to no address", addr),
+        )
+    })
+}
+
+/// Reads only happen in TLS handshakes, which a silent peer would otherwise
+/// block forever.
+fn connect_tcp(addr: &str, timeout: Duration) -> io::Result<TcpStream> {
+    let stream = TcpStream::connect_timeout(&resolve(addr)?, timeout)?;
+    stream.set_read_timeout(Some(timeout))?;
+    stream.set_write_timeout(Some(timeout))?;
+    stream.set_nodelay(true)?;
+    Ok(stream)
+}
+
+pub(super) enum Connection {
+    Udp(UdpSocket),
+    #[cfg(unix)]
+    UnixDatagram(UnixDatagram),
+    Stream(Box<dyn Write + Send>),
+}
+
+impl Connection {
+    /// Writes the messages at the start of `batch`, removing those that were
+    /// written. A datagram that could not be sent is removed too and counted
+    /// in `dropped`, since sending it again would most likely fail the same
+    /// way, while a stream is written again in full on the next connection.
+    pub(super) fn write(&mut self, batch: &mut Vec<String>, dropped: &mut u64) -> io::Result<()> {
+        match self {
+            Connection::Udp(socket) => send_datagrams(batch, dropped, |text| socket.send(text)),
+            #[cfg(unix)]
+            Connection::UnixDatagram(socket) => {
+                send_datagrams(batch, dropped, |text| socket.send(text))
+            }
+            Connection::Stream(stream) => {
+                let mut frames = Vec::new();
+                for text in batch.iter() {
+                    write!(frames, "{} {}", text.len(), text)?;
+                }
+                stream.write_all(&frames)?;
+                stream.flush()?;
+                batch.clear();
+                Ok(())
+            }
+        }
+    }
+}
+
+fn send_datagrams<F>(batch: &mut Vec<String>, dropped: &mut u64, send: F) -> io::Result<()>
+where
+    F: Fn(&[u8]) -> io::Result<usize>,
+{
+    let mut sent = 0;
+    let result = batch.iter().try_for_each(|text| {
+        sent += 1;
+        send(text.as_bytes()).map(|_| ())
+    });
+    if result.is_err() {
+        *dropped += 1;
+    }
+    batch.drain(..sent);
+    result
+}
@@ -0,0 +1,358 @@
+use crate::{Error, Message, ParseMsg, ParsePart};
+use std::{
+    io::{self, Read},
+    ops::Range,
+};
+
+/// How the messages of a stream are delimited, see RFC 6587.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum Framing {
+    /// Each message is prefixed by its length in bytes and a space:
+    /// `23 <13>1 - - - - - - hello`.
+    OctetCounting,
+    /// Each message ends with a newline, or a NUL as `syslog(3)` writes to
+    /// stream sockets.
+    NonTransparent,
+}
+
+/// The longest octet count accepted, so that a stream of digits is rejected
+/// early.
+const MAX_COUNT_DIGITS: usize = 10;
+
+/// Splits a byte stream into frames, detecting the framing from the first
+/// byte: a digit starts an octet count, anything else (normally the `<` of
+/// PRI) a newline delimited message.
+#[derive(Debug)]
+pub(crate) struct FrameDecoder {
+    framing: Option<Framing>,
+    max_frame_size: usize,
+    /// Bytes of an oversized octet counted frame still to skip.
+    skip: usize,
+    /// Whether an oversized newline delimited frame is being skipped.
+    discard_line: bool,
+}
+
+impl FrameDecoder {
+    pub(crate) fn new(max_frame_size: usize) -> Self {
+        Self {
+            framing: None,
+            max_frame_size,
+            skip: 0,
+            discard_line: false,
+        }
+    }
+
+    pub(crate) fn framing(&self) -> Option<Framing> {
+        self.framing
+    }
+
+    /// Decodes the start of `buf`, returning how many bytes were consumed and
+    /// the frame (as a range of `buf`) or the error found there, if any. At
+    /// `eof` whatever is left is consumed.
+    pub(crate) fn decode(
+        &mut self,
+        buf: &[u8],
+        eof: bool,
+    ) -> (usize, Option<Result<Range<usize>, Error<'static>>>) {
+        if buf.is_empty() {
+            return (0, None);
+        }
+        if self.skip > 0 {
+            let skipped = self.skip.min(buf.len());
+            self.skip -= skipped;
+            return (skipped, None);
+        }
+        if self.discard_line {
+            return match buf.iter().position(|b| *b == b'\n' || *b == b'\0') {
+                Some(end) => {
+                    self.discard_line = false;
+                    (end + 1, None)
+                }
+                None => (buf.len(), None),
+            };
+        }
+
+        let framing = *self.framing.get_or_insert(if buf[0].is_ascii_digit() {
+            Framing::OctetCounting
+        } else {
+            Framing::NonTransparent
+        });
+        match framing {
+            Framing::OctetCounting => self.decode_octet_counted(buf, eof),
+            Framing::NonTransparent => self.decode_line(buf, eof),
+        }
+    }
+
+    fn decode_octet_counted(
+        &mut self,
+        buf: &[u8],
+        eof: bool,
+    ) -> (usize, Option<Result<Range<usize>, Error<'static>>>) {
+        // Some senders end octet counted frames with a newline anyway.
+        if buf[0] == b'\n' || buf[0] == b'\r' {
+            return (1, None);
+        }
+
+        let digits = buf
+            .iter()
+            .take(MAX_COUNT_DIGITS + 1)
+            .take_while(|b| b.is_ascii_digit())
+            .count();
+        let count = match buf.get(digits) {
+            None if digits <= MAX_COUNT_DIGITS && !eof => return (0, None),
+            Some(b' ') if digits > 0 => std::str::from_utf8(&buf[..digits])
+                .ok()
+                .and_then(|count| count.parse::<usize>().ok()),
+            _ => None,
+        };
+        let count = match count {
+            Some(count) => count,
+            None => {
+                // The stream can't be followed any more, resync on the next line.
+                self.discard_line = true;
+                let header = String::from_utf8_lossy(&buf[..(digits + 1).min(buf.len())]);
+                return (
+                    0,
+                    Some(Err(Error::Custom(format!(
+                        "framing: invalid octet count `{}`",
+                        header
+                    )))),
+                );
+            }
+        };
+
+        let start = digits + 1;
+        if count > self.max_frame_size {
+            self.skip = count;
+            return (start, Some(Err(self.too_long(count))));
+        }
+        if buf.len() < start + count {
+            if eof {
+                let error = Error::Custom(format!(
+                    "framing: stream ended {} bytes into a frame of {}",
+                    buf.len() - start,
+                    count
+                ));
+                return (buf.len(), Some(Err(error)));
+            }
+            return (0, None);
+        }
+
+        (start + count, Some(Ok(start..start + count)))
+    }
+
+    fn decode_line(
+        &mut self,
+        buf: &[u8],
+        eof: bool,
+    ) -> (usize, Option<Result<Range<usize>, Error<'static>>>) {
+        let (end, consumed) = match buf.iter().position(|b| *b == b'\n' || *b == b'\0') {
+            Some(end) => (end, end + 1),
+            None if eof => (buf.len(), buf.len()),
+            None if buf.len() > self.max_frame_size + 1 => {
+                self.discard_line = true;
+                return (buf.len(), Some(Err(self.too_long(buf.len()))));
+            }
+            None => return (0, None),
+        };
+
+        let end = if end > 0 && buf[end - 1] == b'\r' {
+            end - 1
+        } else {
+            end
+        };
+        if end == 0 {
+            return (consumed, None);
+        }
+        if end > self.max_frame_size {
+            return (consumed, Some(Err(self.too_long(end))));
+        }
+
+        (consumed, Some(Ok(0..end)))
+    }
+
+    fn too_long(&self, len: usize) -> Error<'static> {
+        Error::Custom(format!(
+            "framing: frame of {} bytes exceeds the maximum of {}",
+            len, self.max_frame_size
+        ))
+    }
+}
+
+/// Reads the frames of a syslog stream, such as a TCP connection, see
+/// [`Framing`] for how they are detected.
+///
+/// Frames are decoded as UTF-8, replacing invalid sequences. Frames longer
+/// than the maximum frame size are skipped and reported as errors, and so are
+/// invalid octet counts, after which the stream is followed again from the
+/// next newline.
+#[derive(Debug)]
+pub struct FrameReader<R> {
+    reader: R,
+    decoder: FrameDecoder,
+    buf: Vec<u8>,
+    pos: usize,
+    eof: bool,
+    text: String,
+}
+
+impl<R: Read> FrameReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            decoder: FrameDecoder::new(super::DEFAULT_MAX_MESSAGE_SIZE),
+            buf: Vec::new(),
+            pos: 0,
+            eof: false,
+            text: String::new(),
+        }
+    }
+
+    /// Defaults to 8192 bytes.
+    pub fn max_frame_size(mut self, size: usize) -> Self {
+        self.decoder.max_frame_size = size;
+        self
+    }
+
+    /// The framing of the stream, once its first byte was read.
+    pub fn framing(&self) -> Option<Framing> {
+        self.decoder.framing()
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+
+    /// Reads the next frame, `None` once the stream ended. A frame that could
+    /// not be read is an `Err`, and reading may go on after it.
+    pub fn read_frame(&mut self) -> io::Result<Option<Result<&str, Error<'static>>>> {
+        loop {
+            let (consumed, frame) = self.decoder.decode(&self.buf[self.pos..], self.eof);
+            let start = self.pos;
+            self.pos += consumed;
+
+            match frame {
+                Some(Ok(range)) => {
+                    let frame = &self.buf[start + range.start..start + range.end];
+                    self.text.clear();
+                    self.text.push_str(&String::from_utf8_lossy(frame));
+                    return Ok(Some(Ok(&self.text)));
+                }
+                Some(Err(err)) => return Ok(Some(Err(err))),
+                None if consumed > 0 => continue,
+                None if self.eof => return Ok(None),
+                None => self.fill()?,
+            }
+        }
+    }
+
+    /// Reads the next frame and hands it to `handler` parsed, borrowing from
+    /// the reader's buffer. `None` once the stream ended.
+    pub fn read_with<'a, T, S, M, O, F>(&'a mut self, handler: F) -> io::Result<Option<O>>
+    where
+        T: ParsePart<'a>,
+        S: ParsePart<'a>,
+        M: ParseMsg<'a>,
+        F: FnOnce(Result<Message<'a, T, S, M>, Error<'a>>) -> O,
+    {
+        Ok(self
+            .read_frame()?
+            .map(|frame| handler(frame.and_then(Message::parse))))
+    }
+
+    fn fill(&mut self) -> io::Result<()> {
+        self.buf.drain(..self.pos);
+        self.pos = 0;
+
+        let len = self.buf.len();
+        self.buf.resize(len + 8192, 0);
+        let read = loop {
+            match self.reader.read(&mut self.buf[len..]) {
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                result => break result,
+            }
+        };
+        self.buf.truncate(len + read.as_ref().copied().unwrap_or(0));
+        self.eof = read? == 0;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frames(input: &[u8], max_frame_size: usize) -> Vec<Result<String, Error<'static>>> {
+        let mut reader = FrameReader::new(input).max_frame_size(max_frame_size);
+        let mut frames = Vec::new();
+        while let Some(frame) = reader.read_frame().expect("reading frame") {
+            frames.push(frame.map(str::to_string));
+        }
+        frames
+    }
+
+    #[test]
+    fn octet_counting() {
+        assert_eq!(
+            frames(b"5 <1>1 3 abc\n2 de", 16),
+            vec![Ok("<1>1 ".into()), Ok("abc".into()), Ok("de".into())]
+        );
+    }
+
+    #[test]
+    fn non_transparent() {
+        assert_eq!(
+            frames(b"<1>1 a\r\n\n<1>1 b\0<1>1 c", 16),
+            vec![
+                Ok("<1>1 a".into()),
+                Ok("<1>1 b".into()),
+                Ok("<1>1 c".into())
+            ]
+        );
+    }
+
+    #[test]
+    fn oversized_frames() {
+        assert_eq!(
+            frames(b"6 abcdef2 gh", 4),
+            vec![
+                Err(Error::Custom(
+                    "framing: frame of 6 bytes exceeds the maximum of 4".into()
+                )),
+                Ok("gh".into())
+            ]
+        );
+        assert_eq!(
+            frames(b"<1>123456\n<1>1\n", 4),
+            vec![
+                Err(Error::Custom(
+                    "framing: frame of 9 bytes exceeds the maximum of 4".into()
+                )),
+                Ok("<1>1".into())
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_octet_count() {
+        assert_eq!(
+            frames(b"3 abc12x oops\n1 d", 16),
+            vec![
+                Ok("abc".into()),
+                Err(Error::Custom("framing: invalid octet count `12x`".into())),
+                Ok("d".into())
+            ]
+        );
+    }
+
+    #[test]
+    fn truncated_frame() {
+        assert_eq!(
+            frames(b"10 abc", 16),
+            vec![Err(Error::Custom(
+                "framing: stream ended 3 bytes into a frame of 10".into()
+            ))]
+        );
+    }
+}
@@ -0,0 +1,25 @@
+//! Receivers that listen for syslog messages and parse them.
+
+pub(crate) mod framing;
+mod tcp;
+#[cfg(feature = "tls")]
+mod tls;
+mod udp;
+#[cfg(unix)]
+mod unix;
+
+pub use framing::{FrameReader, Framing};
+#[cfg(feature = "tls")]
+pub use rustls;
+pub use tcp::TcpReceiver;
+#[cfg(feature = "tls")]
+pub use tls::{ClientAuth, PeerCertificate, TlsPeer, TlsReceiver, TlsStream};
+pub use udp::{Datagram, Source, UdpReceiver};
+#[cfg(unix)]
+pub use unix::{
+    parse_local, Credentials, UnixConnection, UnixDatagramReceiver, UnixSource, UnixStreamReceiver,
+};
+
+/// The default size limit of messages. RFC 5426 only requires UDP receivers
+/// to take 480 (IPv4) or 1180 (IPv6) bytes, and recommends 2048.
+pub(crate) const DEFAULT_MAX_MESSAGE_SIZE: usize = 8192;
@@ -0,0 +1,106 @@
+use super::{FrameReader, DEFAULT_MAX_MESSAGE_SIZE};
+use crate::{
+    net::{serve_connections, DEFAULT_MAX_CONNECTIONS, DEFAULT_READ_TIMEOUT},
+    Error,
+};
+use std::{
+    io,
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    time::Duration,
+};
+
+/// Receives syslog messages over TCP, framed per connection with octet
+/// counting or newlines (RFC 6587), see [`FrameReader`].
+#[derive(Debug)]
+pub struct TcpReceiver {
+    pub(super) listener: TcpListener,
+    pub(super) max_frame_size: usize,
+    pub(super) read_timeout: Option<Duration>,
+    pub(super) max_connections: usize,
+}
+
+impl TcpReceiver {
+    pub fn bind<A: ToSocketAddrs>(addr: A) -> io::Result<Self> {
+        TcpListener::bind(addr).map(Self::new)
+    }
+
+    pub fn new(listener: TcpListener) -> Self {
+        Self {
+            listener,
+            max_frame_size: DEFAULT_MAX_MESSAGE_SIZE,
+            read_timeout: Some(DEFAULT_READ_TIMEOUT),
+            max_connections: DEFAULT_MAX_CONNECTIONS,
+        }
+    }
+
+    /// Longer frames are skipped and reported as errors. Defaults to 8192.
+    pub fn max_frame_size(mut self, size: usize) -> Self {
+        self.max_frame_size = size;
+        self
+    }
+
+    /// Connections that stay silent for longer are closed, `None` keeping
+    /// them open. Defaults to 5 minutes.
+    pub fn read_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.read_timeout = timeout;
+        self
+    }
+
+    /// How many connections [`run`](Self::run) reads at once, further ones
+    /// waiting to be accepted until one closes. Defaults to 1024.
+    pub fn max_connections(mut self, max: usize) -> Self {
+        self.max_connections = max;
+        self
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Accepts the next stream, with the read timeout set.
+    pub(super) fn accept_stream(&self) -> io::Result<(TcpStream, SocketAddr)> {
+        let (stream, peer) = self.listener.accept()?;
+        stream.set_read_timeout(self.read_timeout)?;
+
+        Ok((stream, peer))
+    }
+
+    /// Waits for the next connection, whose messages can then be read with
+    /// [`FrameReader::read_with`].
+    pub fn accept(&self) -> io::Result<(FrameReader<TcpStream>, SocketAddr)> {
+        let (stream, peer) = self.accept_stream()?;
+
+        Ok((
+            FrameReader::new(stream).max_frame_size(self.max_frame_size),
+            peer,
+        ))
+    }
+
+    /// Reads every connection on its own thread, up to
+    /// [`max_connections`](Self::max_connections) at once, handing each frame
+    /// to `handler` along with the address of its peer, until accepting a
+    /// connection fails for good, as errors such as running out of file
+    /// descriptors are waited out. Frames are parsed by the handler, e.g. with
+    /// `Message::parse`. Frames that could not be read are handed over as
+    /// errors, and so is the I/O error that ends a connection, such as the
+    /// read timeout.
+    pub fn run<F>(&self, handler: F) -> io::Result<()>
+    where
+        F: Fn(SocketAddr, Result<&str, Error<'_>>) + Send + Sync + 'static,
+    {
+        serve_connections(
+            self.max_connections,
+            || self.accept(),
+            move |(mut frames, peer)| loop {
+                match frames.read_frame() {
+                    Ok(Some(frame)) => handler(peer, frame),
+                    Ok(None) => break,
+                    Err(err) => {
+                        handler(peer, Err(Error::Custom(format!("tcp: {}", err))));
+                        break;
+                    }
+                }
+            },
+        )
+    }
+}
@@ -0,0 +1,353 @@
+use super::{FrameReader, TcpReceiver};
+use crate::{net::serve_connections, Error};
+use rustls::{
+    client::danger::HandshakeSignatureValid,
+    crypto::{ring, verify_tls12_signature, verify_tls13_signature, CryptoProvider},
+    pki_types::{CertificateDer, PrivateKeyDer, UnixTime},
+    server::{
+        danger::{ClientCertVerified, ClientCertVerifier},
+        WebPkiClientVerifier,
+    },
+    DigitallySignedStruct, DistinguishedName, RootCertStore, ServerConfig, ServerConnection,
+    SignatureScheme, StreamOwned,
+};
+use sha2::{Digest, Sha256};
+use std::{
+    io,
+    net::{SocketAddr, TcpStream},
+    sync::Arc,
+    time::Duration,
+};
+
+/// A TLS connection accepted by [`TlsReceiver`].
+pub type TlsStream = StreamOwned<ServerConnection, TcpStream>;
+
+/// Whether and how clients authenticate with a certificate.
+#[derive(Debug, Clone)]
+pub enum ClientAuth {
+    /// Clients are not asked for a certificate.
+    None,
+    /// Clients must present a certificate issued by one of these roots.
+    Roots(RootCertStore),
+    /// Clients must present a certificate, which is not validated but checked
+    /// against the fingerprint allowlist instead, as RFC 5425 allows for
+    /// self-signed certificates. Every client is refused until a fingerprint
+    /// is allowed.
+    SelfSigned,
+}
+
+/// The certificate a client authenticated with.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct PeerCertificate {
+    /// The subject distinguished name, e.g. `CN=client.example.com, O=Example`.
+    pub subject: String,
+    pub common_name: Option<String>,
+    /// The SHA-256 digest of the DER encoded certificate.
+    pub fingerprint: [u8; 32],
+}
+
+impl PeerCertificate {
+    fn new(der: &CertificateDer<'_>) -> io::Result<Self> {
+        let (_, certificate) = x509_parser::parse_x509_certificate(der).map_err(|err| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("tls: invalid peer certificate: {}", err),
+            )
+        })?;
+        let common_name = certificate
+            .subject()
+            .iter_common_name()
+            .next()
+            .and_then(|cn| cn.as_str().ok())
+            .map(str::to_string);
+
+        Ok(Self {
+            subject: certificate.subject().to_string(),
+            common_name,
+            fingerprint: Sha256::digest(der).into(),
+        })
+    }
+}
+
+/// The other end of a TLS connection.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct TlsPeer {
+    pub addr: SocketAddr,
+    /// The client certificate, when clients authenticate.
+    pub certificate: Option<PeerCertificate>,
+}
+
+#[derive(Debug, Default, Clone)]
+struct Allowlist {
+    subjects: Vec<String>,
+    fingerprints: Vec<[u8; 32]>,
+    /// Whether certificates are not verified, as with
+    /// [`ClientAuth::SelfSigned`].
+    unverified: bool,
+}
+
+impl Allowlist {
+    /// Any client is allowed when both lists are empty, otherwise its
+    /// certificate must match one of their entries. Unverified certificates
+    /// must match a fingerprint, since anyone can make one with any subject.
+    fn allows(&self, certificate: Option<&PeerCertificate>) -> bool {
+        if self.unverified {
+            return certificate
+                .is_some_and(|certificate| self.fingerprints.contains(&certificate.fingerprint));
+        }
+        if self.subjects.is_empty() && self.fingerprints.is_empty() {
+            return true;
+        }
+
+        certificate.is_some_and(|certificate| {
+            self.fingerprints.contains(&certificate.fingerprint)
+                || self.subjects.iter().any(|subject| {
+                    *subject == certificate.subject
+                        || Some(subject) == certificate.common_name.as_ref()
+                })
+        })
+    }
+}
+
+/// Receives syslog messages over TLS (RFC 5425), accepting connections with a
+/// [`TcpReceiver`] and framing them the same way. The read timeout and maximum
+/// number of connections of the [`TcpReceiver`] apply too.
+#[derive(Debug)]
+pub struct TlsReceiver {
+    tcp: TcpReceiver,
+    config: Arc<ServerConfig>,
+    allowlist: Arc<Allowlist>,
+    handshake_timeout: Duration,
+}
+
+impl TlsReceiver {
+    /// Serves `cert_chain` (the server certificate first) with `key`.
+    pub fn new(
+        tcp: TcpReceiver,
+        cert_chain: Vec<CertificateDer<'static>>,
+        key: PrivateKeyDer<'static>,
+        client_auth: ClientAuth,
+    ) -> Result<Self, rustls::Error> {
+        let provider = Arc::new(ring::default_provider());
+        let builder = ServerConfig::builder_with_provider(Arc::clone(&provider))
+            .with_safe_default_protocol_versions()?;
+        let unverified = matches!(client_auth, ClientAuth::SelfSigned);
+        let builder = match client_auth {
+            ClientAuth::None => builder.with_no_client_auth(),
+            ClientAuth::Roots(roots) => {
+                let verifier =
+                    WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider)
+                        .build()
+                        .map_err(|err| rustls::Error::General(format!("tls: {}", err)))?;
+                builder.with_client_cert_verifier(verifier)
+            }
+            ClientAuth::SelfSigned => {
+                builder.with_client_cert_verifier(Arc::new(AnyClientCert(provider)))
+            }
+        };
+
+        let mut receiver =
+            Self::with_config(tcp, Arc::new(builder.with_single_cert(cert_chain, key)?));
+        Arc::make_mut(&mut receiver.allowlist).unverified = unverified;
+
+        Ok(receiver)
+    }
+
+    /// Uses a rustls configuration built by hand.
+    pub fn with_config(tcp: TcpReceiver, config: Arc<ServerConfig>) -> Self {
+        Self {
+            tcp,
+            config,
+            allowlist: Arc::default(),
+            handshake_timeout: Duration::from_secs(10),
+        }
+    }
+
+    /// How long a client may take to complete the handshake, defaults to 10
+    /// seconds.
+    pub fn handshake_timeout(mut self, timeout: Duration) -> Self {
+        self.handshake_timeout = timeout;
+        self
+    }
+
+    /// Only accepts clients whose certificate has this subject or common
+    /// name, or one of the other allowed subjects and fingerprints. Ignored
+    /// with [`ClientAuth::SelfSigned`], whose certificates are not verified.
+    pub fn allow_subject(mut self, subject: impl Into<String>) -> Self {
+        Arc::make_mut(&mut self.allowlist)
+            .subjects
+            .push(subject.into());
+        self
+    }
+
+    /// Only accepts clients whose certificate has this SHA-256 fingerprint,
+    /// or one of the other allowed subjects and fingerprints.
+    pub fn allow_fingerprint(mut self, fingerprint: [u8; 32]) -> Self {
+        Arc::make_mut(&mut self.allowlist)
+            .fingerprints
+            .push(fingerprint);
+        self
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.tcp.local_addr()
+    }
+
+    /// Waits for the next connection and completes its handshake. Fails when
+    /// the handshake does, or when the client is not allowed.
+    pub fn accept(&self) -> io::Result<(FrameReader<TlsStream>, TlsPeer)> {
+        let (stream, addr) = self.tcp.accept_stream()?;
+        let (stream, peer) = handshake(
+            &self.config,
+            &self.allowlist,
+            stream,
+            addr,
+            self.handshake_timeout,
+            self.tcp.read_timeout,
+        )?;
+
+        Ok((
+            FrameReader::new(stream).max_frame_size(self.tcp.max_frame_size),
+            peer,
+        ))
+    }
+
+    /// Reads every connection on its own thread, like [`TcpReceiver::run`]
+    /// and with its read timeout and maximum number of connections, handing
+    /// each frame to `handler` along with the peer's identity. Failed
+    /// handshakes are handed over as errors.
+    pub fn run<F>(&self, handler: F) -> io::Result<()>
+    where
+        F: Fn(&TlsPeer, Result<&str, Error<'_>>) + Send + Sync + 'static,
+    {
+        let config = Arc::clone(&self.config);
+        let allowlist = Arc::clone(&self.allowlist);
+        let max_frame_size = self.tcp.max_frame_size;
+        let handshake_timeout = self.handshake_timeout;
+        let read_timeout = self.tcp.read_timeout;
+        serve_connections(
+            self.tcp.max_connections,
+            || self.tcp.accept_stream(),
+            move |(stream, addr)| {
+                let (stream, peer) = match handshake(
+                    &config,
+                    &allowlist,
+                    stream,
+                    addr,
+                    handshake_timeout,
+                    read_timeout,
+                ) {
+                    Ok(accepted) => accepted,
+                    Err(err) => {
+                        let peer = TlsPeer {
+                            addr,
+                            certificate: None,
+                        };
+                        return handler(&peer, Err(Error::Custom(format!("tls: {}", err))));
+                    }
+                };
+                let mut frames = FrameReader::new(stream).max_frame_size(max_frame_size);
+                loop {
+                    match frames.read_frame() {
+                        Ok(Some(frame)) => handler(&peer, frame),
+                        // Clients commonly close without a close_notify alert.
+                        Ok(None) => break,
+                        Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => break,
+                        Err(err) => {
+                            handler(&peer, Err(Error::Custom(format!("tls: {}", err))));
+                            break;
+                        }
+                    }
+                }
+            },
+        )
+    }
+}
+
+fn handshake(
+    config: &Arc<ServerConfig>,
+    allowlist: &Allowlist,
+    stream: TcpStream,
+    addr: SocketAddr,
+    timeout: Duration,
+    read_timeout: Option<Duration>,
+) -> io::Result<(TlsStream, TlsPeer)> {
+    let connection = ServerConnection::new(Arc::clone(config)).map_err(io::Error::other)?;
+    stream.set_read_timeout(Some(timeout))?;
+    stream.set_write_timeout(Some(timeout))?;
+    let mut stream = StreamOwned::new(connection, stream);
+    while stream.conn.is_handshaking() {
+        stream.conn.complete_io(&mut stream.sock)?;
+    }
+    stream.sock.set_read_timeout(read_timeout)?;
+    stream.sock.set_write_timeout(None)?;
+
+    let certificate = match stream
+        .conn
+        .peer_certificates()
+        .and_then(|chain| chain.first())
+    {
+        Some(der) => Some(PeerCertificate::new(der)?),
+        None => None,
+    };
+    if !allowlist.allows(certificate.as_ref()) {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            "tls: client certificate not allowed",
+        ));
+    }
+
+    Ok((stream, TlsPeer { addr, certificate }))
+}
+
+/// Accepts any client certificate, still checking that the client holds its
+/// private key.
+#[derive(Debug)]
+struct AnyClientCert(Arc<CryptoProvider>);
+
+impl ClientCertVerifier for AnyClientCert {
+    fn root_hint_subjects(&self) -> &[DistinguishedName] {
+        &[]
+    }
+
+    fn verify_client_cert(
+        &self,
+        _: &CertificateDer<'_>,
+        _: &[CertificateDer<'_>],
+        _: UnixTime,
+    ) -> Result<ClientCertVerified, rustls::Error> {
+        Ok(ClientCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
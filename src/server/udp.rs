@@ -0,0 +1,169 @@
+use super::DEFAULT_MAX_MESSAGE_SIZE;
+use crate::{Error, Message, ParseMsg, ParsePart};
+use std::{
+    io,
+    net::{SocketAddr, ToSocketAddrs, UdpSocket},
+};
+
+/// Where a datagram came from.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub struct Source {
+    pub addr: SocketAddr,
+    /// Whether the datagram was longer than the maximum message size and was
+    /// cut to it.
+    pub truncated: bool,
+}
+
+/// A datagram received by [`UdpReceiver::recv`], owning its text.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Datagram {
+    pub source: Source,
+    pub text: String,
+}
+
+impl Datagram {
+    pub fn parse<'a, T, S, M>(&'a self) -> Result<Message<'a, T, S, M>, Error<'a>>
+    where
+        T: ParsePart<'a>,
+        S: ParsePart<'a>,
+        M: ParseMsg<'a>,
+    {
+        Message::parse(&self.text)
+    }
+}
+
+/// Receives syslog messages over UDP, one per datagram as RFC 5426 requires.
+///
+/// Datagrams are decoded as UTF-8, replacing invalid sequences, and a trailing
+/// newline or NUL (which some senders add) is dropped.
+#[derive(Debug)]
+pub struct UdpReceiver {
+    socket: UdpSocket,
+    max_message_size: usize,
+    buf: Vec<u8>,
+    text: String,
+}
+
+impl UdpReceiver {
+    pub fn bind<A: ToSocketAddrs>(addr: A) -> io::Result<Self> {
+        UdpSocket::bind(addr).map(Self::new)
+    }
+
+    pub fn new(socket: UdpSocket) -> Self {
+        Self {
+            socket,
+            max_message_size: DEFAULT_MAX_MESSAGE_SIZE,
+            buf: Vec::new(),
+            text: String::new(),
+        }
+    }
+
+    /// Longer datagrams are truncated to `size` bytes and flagged in their
+    /// [`Source`]. Defaults to 8192.
+    pub fn max_message_size(mut self, size: usize) -> Self {
+        self.max_message_size = size;
+        self
+    }
+
+    /// The underlying socket, e.g. to set a read timeout.
+    pub fn socket(&self) -> &UdpSocket {
+        &self.socket
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+
+    /// Waits for the next datagram.
+    pub fn recv(&mut self) -> io::Result<Datagram> {
+        let source = self.recv_text()?;
+
+        Ok(Datagram {
+            source,
+            text: self.text.clone(),
+        })
+    }
+
+    /// Waits for the next datagram and hands it to `handler` parsed, borrowing
+    /// from the receiver's buffer.
+    pub fn recv_with<'a, T, S, M, R, F>(&'a mut self, handler: F) -> io::Result<R>
+    where
+        T: ParsePart<'a>,
+        S: ParsePart<'a>,
+        M: ParseMsg<'a>,
+        F: FnOnce(Source, Result<Message<'a, T, S, M>, Error<'a>>) -> R,
+    {
+        let source = self.recv_text()?;
+
+        Ok(handler(source, Message::parse(&self.text)))
+    }
+
+    /// Hands every datagram to `handler`, until receiving fails.
+    pub fn run<F: FnMut(Datagram)>(&mut self, mut handler: F) -> io::Result<()> {
+        loop {
+            handler(self.recv()?);
+        }
+    }
+
+    fn recv_text(&mut self) -> io::Result<Source> {
+        // One extra byte tells a datagram that fits exactly from a longer one.
+        self.buf.resize(self.max_message_size + 1, 0);
+        let (len, addr) = self.socket.recv_from(&mut self.buf)?;
+        let truncated = len > self.max_message_size;
+
+        let mut data = &self.buf[..len.min(self.max_message_size)];
+        if let Some((b'\n' | b'\0', rest)) = data.split_last() {
+            data = rest.strip_suffix(b"\r").unwrap_or(rest);
+        }
+        self.text.clear();
+        self.text.push_str(&String::from_utf8_lossy(data));
+
+        Ok(Source { addr, truncated })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{msg::Raw, StructuredData};
+
+    fn loopback() -> (UdpReceiver, UdpSocket) {
+        let receiver = UdpReceiver::bind("127.0.0.1:0").expect("binding receiver");
+        let sender = UdpSocket::bind("127.0.0.1:0").expect("binding sender");
+        sender
+            .connect(receiver.local_addr().expect("receiver address"))
+            .expect("connecting sender");
+
+        (receiver, sender)
+    }
+
+    #[test]
+    fn truncated_datagram() {
+        let (receiver, sender) = loopback();
+        let mut receiver = receiver.max_message_size(16);
+        sender
+            .send(b"<13>1 - host app - - - a long message")
+            .expect("sending");
+
+        let datagram = receiver.recv().expect("receiving");
+
+        assert!(datagram.source.truncated);
+        assert_eq!(datagram.text, "<13>1 - host app");
+    }
+
+    #[test]
+    fn trailing_newline() {
+        let (mut receiver, sender) = loopback();
+        sender.send(b"<13>1 - - - - - - hi\r\n").expect("sending");
+
+        let msg = receiver
+            .recv_with(
+                |_, message: Result<Message<Option<&str>, Vec<StructuredData>, Raw>, _>| {
+                    message.map(|message| message.msg.msg.to_string())
+                },
+            )
+            .expect("receiving");
+
+        assert_eq!(msg, Ok("hi".to_string()));
+    }
+}
@@ -0,0 +1,585 @@
+use super::{FrameReader, DEFAULT_MAX_MESSAGE_SIZE};
+use crate::{
+    net::{serve_connections, DEFAULT_MAX_CONNECTIONS, DEFAULT_READ_TIMEOUT},
+    parser::parse_pri,
+    Error, Message,
+};
+use std::{
+    fmt::Write,
+    io,
+    os::unix::net::{UnixDatagram, UnixListener, UnixStream},
+    path::Path,
+    time::Duration,
+};
+
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// The process that sent a message, as told by the kernel. Only available on
+/// Linux.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub struct Credentials {
+    pub pid: u32,
+    pub uid: u32,
+    pub gid: u32,
+}
+
+/// Where a datagram came from.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub struct UnixSource {
+    pub credentials: Option<Credentials>,
+    /// Whether the datagram was longer than the maximum message size and was
+    /// cut to it.
+    pub truncated: bool,
+}
+
+/// Receives messages from local processes on a datagram socket such as
+/// `/dev/log`, see [`parse_local`] for the formats understood. Messages
+/// without HOSTNAME get the local host name, and on Linux those without
+/// APP-NAME or PROCID get the sender's command name and PID.
+#[derive(Debug)]
+pub struct UnixDatagramReceiver {
+    socket: UnixDatagram,
+    max_message_size: usize,
+    buf: Vec<u8>,
+    text: String,
+    local: Local,
+}
+
+impl UnixDatagramReceiver {
+    pub fn bind<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::new(UnixDatagram::bind(path)?)
+    }
+
+    /// Asks the kernel for the credentials of the senders, where supported.
+    pub fn new(socket: UnixDatagram) -> io::Result<Self> {
+        #[cfg(target_os = "linux")]
+        linux::pass_credentials(&socket)?;
+
+        Ok(Self {
+            socket,
+            max_message_size: DEFAULT_MAX_MESSAGE_SIZE,
+            buf: Vec::new(),
+            text: String::new(),
+            local: Local::new()?,
+        })
+    }
+
+    /// Longer datagrams are truncated to `size` bytes and flagged in their
+    /// [`UnixSource`]. Defaults to 8192.
+    pub fn max_message_size(mut self, size: usize) -> Self {
+        self.max_message_size = size;
+        self
+    }
+
+    /// The HOSTNAME given to messages without one, the local host name by
+    /// default.
+    pub fn hostname(mut self, hostname: impl Into<String>) -> Self {
+        self.local.hostname = hostname.into();
+        self
+    }
+
+    /// The underlying socket, e.g. to set a read timeout.
+    pub fn socket(&self) -> &UnixDatagram {
+        &self.socket
+    }
+
+    /// Waits for the next datagram and hands it to `handler` parsed, borrowing
+    /// from the receiver's buffers.
+    pub fn recv_with<'a, R, F>(&'a mut self, handler: F) -> io::Result<R>
+    where
+        F: FnOnce(UnixSource, Result<Message<'a>, Error<'a>>) -> R,
+    {
+        self.buf.resize(self.max_message_size, 0);
+        #[cfg(target_os = "linux")]
+        let (len, truncated, credentials) =
+            linux::recv_with_credentials(&self.socket, &mut self.buf)?;
+        #[cfg(not(target_os = "linux"))]
+        let (len, truncated, credentials) = {
+            // One extra byte tells a datagram that fits exactly from a longer one.
+            self.buf.push(0);
+            let len = self.socket.recv(&mut self.buf)?;
+            let truncated = len > self.max_message_size;
+            (len.min(self.max_message_size), truncated, None)
+        };
+
+        let mut data = &self.buf[..len];
+        while let Some((b'\n' | b'\0', rest)) = data.split_last() {
+            data = rest;
+        }
+        self.text.clear();
+        self.text.push_str(&String::from_utf8_lossy(data));
+
+        let source = UnixSource {
+            credentials,
+            truncated,
+        };
+        Ok(handler(source, self.local.parse(&self.text, credentials)))
+    }
+
+    /// Hands every datagram to `handler`, until receiving fails.
+    pub fn run<F>(&mut self, mut handler: F) -> io::Result<()>
+    where
+        F: FnMut(UnixSource, Result<Message<'_>, Error<'_>>),
+    {
+        loop {
+            self.recv_with(&mut handler)?;
+        }
+    }
+}
+
+/// Receives messages from local processes on a stream socket, framed with
+/// newlines, NULs or octet counting (see [`FrameReader`]), and parsed like
+/// [`UnixDatagramReceiver`] does.
+#[derive(Debug)]
+pub struct UnixStreamReceiver {
+    listener: UnixListener,
+    max_frame_size: usize,
+    read_timeout: Option<Duration>,
+    max_connections: usize,
+    local: Local,
+}
+
+/// A connection accepted by [`UnixStreamReceiver`].
+#[derive(Debug)]
+pub struct UnixConnection {
+    frames: FrameReader<UnixStream>,
+    credentials: Option<Credentials>,
+    local: Local,
+}
+
+impl UnixStreamReceiver {
+    pub fn bind<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::new(UnixListener::bind(path)?)
+    }
+
+    pub fn new(listener: UnixListener) -> io::Result<Self> {
+        Ok(Self {
+            listener,
+            max_frame_size: DEFAULT_MAX_MESSAGE_SIZE,
+            read_timeout: Some(DEFAULT_READ_TIMEOUT),
+            max_connections: DEFAULT_MAX_CONNECTIONS,
+            local: Local::new()?,
+        })
+    }
+
+    /// Longer frames are skipped and reported as errors. Defaults to 8192.
+    pub fn max_frame_size(mut self, size: usize) -> Self {
+        self.max_frame_size = size;
+        self
+    }
+
+    /// Connections that stay silent for longer are closed, `None` keeping
+    /// them open. Defaults to 5 minutes.
+    pub fn read_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.read_timeout = timeout;
+        self
+    }
+
+    /// How many connections [`run`](Self::run) reads at once, further ones
+    /// waiting to be accepted until one closes. Defaults to 1024.
+    pub fn max_connections(mut self, max: usize) -> Self {
+        self.max_connections = max;
+        self
+    }
+
+    /// The HOSTNAME given to messages without one, the local host name by
+    /// default.
+    pub fn hostname(mut self, hostname: impl Into<String>) -> Self {
+        self.local.hostname = hostname.into();
+        self
+    }
+
+    /// Waits for the next connection and looks up its peer's credentials.
+    pub fn accept(&self) -> io::Result<UnixConnection> {
+        let (stream, _) = self.listener.accept()?;
+        stream.set_read_timeout(self.read_timeout)?;
+        #[cfg(target_os = "linux")]
+        let credentials = linux::peer_credentials(&stream).ok();
+        #[cfg(not(target_os = "linux"))]
+        let credentials = None;
+
+        Ok(UnixConnection {
+            frames: FrameReader::new(stream).max_frame_size(self.max_frame_size),
+            credentials,
+            local: self.local.clone(),
+        })
+    }
+
+    /// Reads every connection on its own thread, handing each message to
+    /// `handler` along with the credentials of its sender, until accepting a
+    /// connection fails for good, like
+    /// [`TcpReceiver::run`](super::TcpReceiver::run). The I/O error that ends
+    /// a connection is handed over too.
+    pub fn run<F>(&self, handler: F) -> io::Result<()>
+    where
+        F: Fn(Option<Credentials>, Result<Message<'_>, Error<'_>>) + Send + Sync + 'static,
+    {
+        serve_connections(
+            self.max_connections,
+            || self.accept(),
+            move |mut connection: UnixConnection| loop {
+                let credentials = connection.credentials;
+                match connection.read_with(|message| handler(credentials, message)) {
+                    Ok(Some(())) => {}
+                    Ok(None) => break,
+                    Err(err) => {
+                        handler(credentials, Err(Error::Custom(format!("unix: {}", err))));
+                        break;
+                    }
+                }
+            },
+        )
+    }
+}
+
+impl UnixConnection {
+    pub fn credentials(&self) -> Option<Credentials> {
+        self.credentials
+    }
+
+    /// Reads the next message and hands it to `handler` parsed, borrowing from
+    /// the connection's buffers. `None` once the stream ended.
+    pub fn read_with<'a, R, F>(&'a mut self, handler: F) -> io::Result<Option<R>>
+    where
+        F: FnOnce(Result<Message<'a>, Error<'a>>) -> R,
+    {
+        let local = &mut self.local;
+        let credentials = self.credentials;
+
+        Ok(self
+            .frames
+            .read_frame()?
+            .map(|frame| handler(frame.and_then(|text| local.parse(text, credentials)))))
+    }
+}
+
+/// Fills in the header fields local messages leave out.
+#[derive(Debug, Clone)]
+struct Local {
+    hostname: String,
+    app_name: String,
+    proc_id: String,
+}
+
+impl Local {
+    fn new() -> io::Result<Self> {
+        Ok(Self {
+            hostname: local_hostname()?,
+            app_name: String::new(),
+            proc_id: String::new(),
+        })
+    }
+
+    fn parse<'a>(
+        &'a mut self,
+        text: &'a str,
+        credentials: Option<Credentials>,
+    ) -> Result<Message<'a>, Error<'a>> {
+        let mut message = parse_local(text)?;
+
+        self.app_name.clear();
+        self.proc_id.clear();
+        if let Some(credentials) = credentials {
+            if message.app_name.is_none() {
+                #[cfg(target_os = "linux")]
+                if let Ok(comm) = std::fs::read_to_string(format!("/proc/{}/comm", credentials.pid))
+                {
+                    self.app_name.push_str(comm.trim_end());
+                }
+            }
+            if message.proc_id.is_none() {
+                let _ = write!(self.proc_id, "{}", credentials.pid);
+            }
+        }
+
+        message.hostname = message.hostname.or(Some(self.hostname.as_str()));
+        message.app_name = message
+            .app_name
+            .or(Some(self.app_name.as_str()).filter(|name| !name.is_empty()));
+        message.proc_id = message
+            .proc_id
+            .or(Some(self.proc_id.as_str()).filter(|id| !id.is_empty()));
+
+        Ok(message)
+    }
+}
+
+/// Parses the forms local processes log in:
+///
+/// - RFC 5424, `<13>1 2024-01-01T00:00:00Z host app 42 - - hello`,
+/// - RFC 3164 as written by `syslog(3)`, `<13>Jan  1 00:00:00 app[42]: hello`,
+///   with an optional HOSTNAME before the tag,
+/// - a bare `<13>hello`.
+///
+/// The RFC 3164 timestamp is kept as written, and VERSION is 0 for the forms
+/// other than RFC 5424. Text that only looks like RFC 5424, such as
+/// `<14>42 apples`, is read as one of the other forms.
+pub fn parse_local(text: &str) -> Result<Message<'_>, Error<'_>> {
+    let (rem, pri) = parse_pri(text)?;
+    let mut version = rem.splitn(2, ' ');
+    if version
+        .next()
+        .is_some_and(|v| !v.is_empty() && v.bytes().all(|b| b.is_ascii_digit()))
+        && version.next().is_some()
+    {
+        if let Ok(message) = Message::parse(text) {
+            return Ok(message);
+        }
+    }
+
+    let (timestamp, rem) = match rem.get(..15) {
+        Some(timestamp) if is_bsd_timestamp(timestamp) && rem[15..].starts_with(' ') => {
+            (Some(timestamp), &rem[16..])
+        }
+        _ => (None, rem),
+    };
+    let (hostname, tag) = match split_tag(rem) {
+        Some(tag) => (None, Some(tag)),
+        // Only messages with a timestamp may have a HOSTNAME before the tag.
+        None if timestamp.is_some() => match rem.split_once(' ') {
+            Some((hostname, rest)) => match split_tag(rest) {
+                Some(tag) => (Some(hostname), Some(tag)),
+                None => (None, None),
+            },
+            None => (None, None),
+        },
+        None => (None, None),
+    };
+    let (app_name, proc_id, msg) = match tag {
+        Some(tag) => tag,
+        None => (None, None, rem),
+    };
+
+    Ok(Message {
+        facility: pri >> 3,
+        severity: pri & 7,
+        version: 0,
+        timestamp,
+        hostname,
+        app_name,
+        proc_id,
+        msg_id: None,
+        structured_data: vec![],
+        msg: msg.into(),
+    })
+}
+
+/// Checks for `Mmm dd hh:mm:ss`, with the day padded with a space.
+fn is_bsd_timestamp(timestamp: &str) -> bool {
+    let bytes = timestamp.as_bytes();
+    let digits = |range: std::ops::Range<usize>| {
+        bytes[range]
+            .iter()
+            .all(|b| b.is_ascii_digit() || *b == b' ')
+    };
+
+    MONTHS.iter().any(|month| month.as_bytes() == &bytes[..3])
+        && bytes[3] == b' '
+        && digits(4..6)
+        && bytes[6] == b' '
+        && digits(7..9)
+        && bytes[9] == b':'
+        && digits(10..12)
+        && bytes[12] == b':'
+        && digits(13..15)
+}
+
+/// Splits `app[42]: hello` or `app: hello` into the tag's name, its PID and
+/// the rest of the message.
+fn split_tag(rem: &str) -> Option<(Option<&str>, Option<&str>, &str)> {
+    let end = rem.find([':', ' '])?;
+    if !rem[end..].starts_with(':') {
+        return None;
+    }
+    let tag = &rem[..end];
+    let msg = &rem[end + 1..];
+    let msg = msg.strip_prefix(' ').unwrap_or(msg);
+
+    match tag.strip_suffix(']').and_then(|tag| tag.split_once('[')) {
+        Some((name, pid)) if !name.is_empty() => Some((Some(name), Some(pid), msg)),
+        None if !tag.is_empty() && !tag.contains('[') => Some((Some(tag), None, msg)),
+        _ => None,
+    }
+}
+
+fn local_hostname() -> io::Result<String> {
+    let mut buf = [0u8; 256];
+    // SAFETY: the length passed is the one of the buffer.
+    if unsafe { libc::gethostname(buf.as_mut_ptr().cast(), buf.len()) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let len = buf.iter().position(|b| *b == 0).unwrap_or(buf.len());
+
+    Ok(String::from_utf8_lossy(&buf[..len]).into_owned())
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use super::Credentials;
+    use std::{
+        io, mem,
+        os::unix::{
+            io::AsRawFd,
+            net::{UnixDatagram, UnixStream},
+        },
+        ptr,
+    };
+
+    impl From<libc::ucred> for Credentials {
+        fn from(ucred: libc::ucred) -> Self {
+            Self {
+                pid: ucred.pid as u32,
+                uid: ucred.uid,
+                gid: ucred.gid,
+            }
+        }
+    }
+
+    pub(super) fn pass_credentials(socket: &UnixDatagram) -> io::Result<()> {
+        let on: libc::c_int = 1;
+        // SAFETY: the option value is a c_int of the size passed.
+        let result = unsafe {
+            libc::setsockopt(
+                socket.as_raw_fd(),
+                libc::SOL_SOCKET,
+                libc::SO_PASSCRED,
+                ptr::addr_of!(on).cast(),
+                mem::size_of::<libc::c_int>() as libc::socklen_t,
+            )
+        };
+        if result != 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(())
+    }
+
+    /// Receives a datagram into `buf`, returning its length, whether it was
+    /// truncated and the sender's credentials.
+    pub(super) fn recv_with_credentials(
+        socket: &UnixDatagram,
+        buf: &mut [u8],
+    ) -> io::Result<(usize, bool, Option<Credentials>)> {
+        let mut iov = libc::iovec {
+            iov_base: buf.as_mut_ptr().cast(),
+            iov_len: buf.len(),
+        };
+        // Aligned for cmsghdr, and larger than CMSG_SPACE(sizeof(ucred)).
+        let mut control = [0u64; 8];
+        // SAFETY: msghdr is plain data, for which zeroes are valid.
+        let mut msg: libc::msghdr = unsafe { mem::zeroed() };
+        msg.msg_iov = &mut iov;
+        msg.msg_iovlen = 1;
+        msg.msg_control = control.as_mut_ptr().cast();
+        msg.msg_controllen = mem::size_of_val(&control) as _;
+
+        // SAFETY: msg points to buffers that outlive the call.
+        let len = unsafe { libc::recvmsg(socket.as_raw_fd(), &mut msg, 0) };
+        if len < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let mut credentials = None;
+        // SAFETY: the control messages were written by the kernel into
+        // `control`, and are walked with the CMSG macros.
+        unsafe {
+            let mut cmsg = libc::CMSG_FIRSTHDR(&msg);
+            while !cmsg.is_null() {
+                if (*cmsg).cmsg_level == libc::SOL_SOCKET
+                    && (*cmsg).cmsg_type == libc::SCM_CREDENTIALS
+                {
+                    let ucred = ptr::read_unaligned(libc::CMSG_DATA(cmsg).cast::<libc::ucred>());
+                    credentials = Some(ucred.into());
+                }
+                cmsg = libc::CMSG_NXTHDR(&msg, cmsg);
+            }
+        }
+
+        Ok((
+            len as usize,
+            msg.msg_flags & libc::MSG_TRUNC != 0,
+            credentials,
+        ))
+    }
+
+    pub(super) fn peer_credentials(stream: &UnixStream) -> io::Result<Credentials> {
+        // SAFETY: ucred is plain data, for which zeroes are valid.
+        let mut ucred: libc::ucred = unsafe { mem::zeroed() };
+        let mut len = mem::size_of::<libc::ucred>() as libc::socklen_t;
+        // SAFETY: the option value is a ucred of the size passed.
+        let result = unsafe {
+            libc::getsockopt(
+                stream.as_raw_fd(),
+                libc::SOL_SOCKET,
+                libc::SO_PEERCRED,
+                ptr::addr_of_mut!(ucred).cast(),
+                &mut len,
+            )
+        };
+        if result != 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(ucred.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glibc_message() {
+        let message = parse_local("<13>Oct  9 22:33:20 myapp[1234]: hello: world")
+            .expect("parsing local message");
+
+        assert_eq!(message.facility, 1);
+        assert_eq!(message.severity, 5);
+        assert_eq!(message.version, 0);
+        assert_eq!(message.timestamp, Some("Oct  9 22:33:20"));
+        assert_eq!(message.hostname, None);
+        assert_eq!(message.app_name, Some("myapp"));
+        assert_eq!(message.proc_id, Some("1234"));
+        assert_eq!(message.msg.msg, "hello: world");
+    }
+
+    #[test]
+    fn bsd_message_with_hostname() {
+        let message = parse_local("<34>Oct 11 22:14:15 mymachine su: 'su root' failed")
+            .expect("parsing local message");
+
+        assert_eq!(message.hostname, Some("mymachine"));
+        assert_eq!(message.app_name, Some("su"));
+        assert_eq!(message.proc_id, None);
+        assert_eq!(message.msg.msg, "'su root' failed");
+    }
+
+    #[test]
+    fn bare_message() {
+        let message = parse_local("<14>just some text").expect("parsing local message");
+
+        assert_eq!(message.timestamp, None);
+        assert_eq!(message.app_name, None);
+        assert_eq!(message.msg.msg, "just some text");
+    }
+
+    #[test]
+    fn rfc5424_message() {
+        let message = parse_local("<13>1 - host app 42 - - hello").expect("parsing local message");
+
+        assert_eq!(message.version, 1);
+        assert_eq!(message.hostname, Some("host"));
+        assert_eq!(message.proc_id, Some("42"));
+    }
+
+    #[test]
+    fn number_is_not_a_version() {
+        let message = parse_local("<14>42 apples").expect("parsing local message");
+
+        assert_eq!(message.version, 0);
+        assert_eq!(message.app_name, None);
+        assert_eq!(message.msg.msg, "42 apples");
+    }
+}
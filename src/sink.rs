@@ -0,0 +1,138 @@
+//! Where the logging backends write messages, and the header fields they fill
+//! in.
+
+use crate::format::sanitize;
+use std::{
+    fmt,
+    fs::OpenOptions,
+    io::{self, Write},
+    path::Path,
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Where the `log` and `tracing` backends write messages.
+pub enum Sink {
+    /// A message per line.
+    Stderr,
+    /// A message per line.
+    Writer(Mutex<Box<dyn Write + Send>>),
+    /// Over the transport of a sender, framed as it requires.
+    #[cfg(feature = "sender")]
+    Sender(crate::sender::Sender),
+}
+
+impl Sink {
+    /// Appends to the file at `path`, creating it if needed.
+    pub fn file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self::writer(file))
+    }
+
+    pub fn writer<W: Write + Send + 'static>(writer: W) -> Self {
+        Sink::Writer(Mutex::new(Box::new(writer)))
+    }
+
+    pub(crate) fn write(&self, text: String) {
+        // A newline would end the message early.
+        let line = || format!("{}\n", text.replace('\n', " "));
+        match self {
+            Sink::Stderr => {
+                let _ = io::stderr().write_all(line().as_bytes());
+            }
+            Sink::Writer(writer) => {
+                let mut writer = writer.lock().unwrap_or_else(|err| err.into_inner());
+                let _ = writer.write_all(line().as_bytes());
+            }
+            #[cfg(feature = "sender")]
+            Sink::Sender(sender) => {
+                sender.send_formatted(text);
+            }
+        }
+    }
+
+    #[cfg(feature = "log")]
+    pub(crate) fn flush(&self) {
+        match self {
+            Sink::Stderr => {
+                let _ = io::stderr().flush();
+            }
+            Sink::Writer(writer) => {
+                let _ = writer.lock().unwrap_or_else(|err| err.into_inner()).flush();
+            }
+            #[cfg(feature = "sender")]
+            Sink::Sender(sender) => {
+                sender.flush(std::time::Duration::from_secs(5));
+            }
+        }
+    }
+}
+
+impl fmt::Debug for Sink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Sink::Stderr => f.write_str("Stderr"),
+            Sink::Writer(_) => f.write_str("Writer"),
+            #[cfg(feature = "sender")]
+            Sink::Sender(sender) => f.debug_tuple("Sender").field(sender).finish(),
+        }
+    }
+}
+
+/// The current time, in microseconds since the epoch.
+pub(crate) fn now() -> Option<u64> {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .ok()
+        .map(|elapsed| elapsed.as_micros() as u64)
+}
+
+/// The name of the executable, made a valid APP-NAME.
+pub(crate) fn app_name() -> Option<String> {
+    let exe = std::env::current_exe().ok()?;
+    let stem = exe.file_stem()?.to_string_lossy();
+    header_field(&stem, 48)
+}
+
+/// The name of the host, as `gethostname(2)` returns it, made a valid
+/// HOSTNAME.
+#[cfg(unix)]
+pub(crate) fn hostname() -> Option<String> {
+    let mut buf = [0u8; 256];
+    // SAFETY: the length passed is the one of the buffer.
+    if unsafe { libc::gethostname(buf.as_mut_ptr().cast(), buf.len()) } != 0 {
+        return None;
+    }
+    let len = buf.iter().position(|b| *b == 0).unwrap_or(buf.len());
+
+    header_field(&String::from_utf8_lossy(&buf[..len]), 255)
+}
+
+#[cfg(not(unix))]
+pub(crate) fn hostname() -> Option<String> {
+    header_field(&std::env::var("COMPUTERNAME").ok()?, 255)
+}
+
+fn header_field(value: &str, max: usize) -> Option<String> {
+    match value {
+        "" => None,
+        _ => Some(sanitize(value, max, |c| c.is_ascii_graphic()).into_owned()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn header_fields() {
+        assert_eq!(header_field("", 48), None);
+        assert_eq!(header_field("my app\u{e9}", 48).as_deref(), Some("my_app_"));
+        assert_eq!(
+            header_field(&"a".repeat(60), 48).map(|name| name.len()),
+            Some(48)
+        );
+        assert!(app_name().is_some());
+        assert!(hostname().is_some());
+    }
+}
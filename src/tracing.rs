@@ -0,0 +1,225 @@
+//! A [`tracing_subscriber`] layer writing events as RFC 5424 messages through
+//! the crate's formatter. The `message` of an event is its MSG, its other
+//! fields are the params of an SD element and the fields of its spans those
+//! of another, a field of an inner span hiding that of an outer one:
+//!
+//! ```text
+//! <14>1 2024-01-01T00:00:00.000000Z web1 api 42 - [event@32473 status="200"][span@32473 request_id="7"] done
+//! ```
+
+pub use crate::sink::Sink;
+
+use crate::{
+    format::{escape_param_value, sanitize_sd_name},
+    parser::{SdParam, StructuredData},
+    sink, Error, Message,
+};
+use std::{borrow::Cow, fmt};
+use tracing_core::{
+    field::{Field, Visit},
+    span::{Attributes, Id, Record},
+    Event, Level, Subscriber,
+};
+use tracing_subscriber::{layer::Context, registry::LookupSpan, Layer};
+
+/// The syslog severity of `level`, `TRACE` sharing DEBUG's.
+pub fn severity(level: &Level) -> u8 {
+    match *level {
+        Level::ERROR => 3,
+        Level::WARN => 4,
+        Level::INFO => 6,
+        _ => 7,
+    }
+}
+
+/// Writes events to a [`Sink`] as RFC 5424 messages, with the fields of the
+/// event and of the spans it is in as structured data.
+///
+/// HOSTNAME, APP-NAME and PROCID are those of the process unless set. Levels
+/// are filtered by composing the layer with a filter.
+#[derive(Debug)]
+pub struct SyslogLayer {
+    sink: Sink,
+    facility: u8,
+    hostname: Option<String>,
+    app_name: Option<String>,
+    proc_id: String,
+    event_sd_id: String,
+    span_sd_id: String,
+}
+
+impl SyslogLayer {
+    pub fn new(sink: Sink) -> Self {
+        Self {
+            sink,
+            facility: 1,
+            hostname: sink::hostname(),
+            app_name: sink::app_name(),
+            proc_id: std::process::id().to_string(),
+            event_sd_id: "event@32473".into(),
+            span_sd_id: "span@32473".into(),
+        }
+    }
+
+    /// Defaults to 1, user-level messages.
+    pub fn facility(mut self, facility: u8) -> Self {
+        self.facility = facility;
+        self
+    }
+
+    pub fn hostname(mut self, hostname: impl Into<String>) -> Self {
+        self.hostname = Some(hostname.into());
+        self
+    }
+
+    pub fn app_name(mut self, app_name: impl Into<String>) -> Self {
+        self.app_name = Some(app_name.into());
+        self
+    }
+
+    /// The SD-ID of the fields of events, defaults to `event@32473`. Fails
+    /// unless `id` is a valid SD-ID.
+    pub fn event_sd_id(mut self, id: impl Into<String>) -> Result<Self, Error<'static>> {
+        self.event_sd_id = sd_id(id.into())?;
+        Ok(self)
+    }
+
+    /// The SD-ID of the fields of the spans, defaults to `span@32473`.
+    /// Fails unless `id` is a valid SD-ID.
+    pub fn span_sd_id(mut self, id: impl Into<String>) -> Result<Self, Error<'static>> {
+        self.span_sd_id = sd_id(id.into())?;
+        Ok(self)
+    }
+
+    fn format(&self, event: &Event<'_>, fields: &Fields, span_fields: &Fields) -> String {
+        let message = fields
+            .0
+            .iter()
+            .find(|(name, _)| *name == "message")
+            .map(|(_, message)| message.as_str());
+        let event_params = escaped(fields.0.iter().filter(|(name, _)| *name != "message"));
+        let span_params = escaped(span_fields.0.iter());
+
+        let mut structured_data = Vec::new();
+        for (id, params) in [
+            (&self.event_sd_id, &event_params),
+            (&self.span_sd_id, &span_params),
+        ] {
+            if !params.is_empty() {
+                structured_data.push(StructuredData::from((id.as_str(), sd_params(params))));
+            }
+        }
+
+        Message {
+            facility: self.facility,
+            severity: severity(event.metadata().level()),
+            version: 1,
+            timestamp: sink::now(),
+            hostname: self.hostname.as_deref(),
+            app_name: self.app_name.as_deref(),
+            proc_id: Some(&self.proc_id),
+            msg_id: None,
+            structured_data,
+            msg: message.unwrap_or(""),
+        }
+        .to_string()
+    }
+}
+
+/// A name, as `name` or `name@12345` for private ones, of at most 32
+/// characters that can be written as they are.
+fn sd_id(id: String) -> Result<String, Error<'static>> {
+    let valid = match id.split_once('@') {
+        Some((name, pen)) => {
+            !name.is_empty() && !pen.is_empty() && pen.bytes().all(|b| b.is_ascii_digit())
+        }
+        None => true,
+    };
+    if !valid || sanitize_sd_name(&id) != id {
+        return Err(Error::Custom(format!("tracing: invalid SD-ID `{}`", id)));
+    }
+
+    Ok(id)
+}
+
+/// Fields as SD-PARAMs, field names being made valid SD-NAMEs since tracing
+/// allows any string.
+fn escaped<'a, I>(fields: I) -> Vec<(Cow<'static, str>, Cow<'a, str>)>
+where
+    I: Iterator<Item = &'a (&'static str, String)>,
+{
+    fields
+        .map(|(name, value)| (sanitize_sd_name(name), escape_param_value(value)))
+        .collect()
+}
+
+fn sd_params<'a>(params: &'a [(Cow<'static, str>, Cow<'a, str>)]) -> Vec<SdParam<'a>> {
+    params
+        .iter()
+        .map(|(name, value)| SdParam::from((&**name, &**value)))
+        .collect()
+}
+
+impl<S> Layer<S> for SyslogLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            let mut fields = Fields::default();
+            attrs.record(&mut fields);
+            span.extensions_mut().insert(fields);
+        }
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            if let Some(fields) = span.extensions_mut().get_mut::<Fields>() {
+                values.record(fields);
+            }
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let mut fields = Fields::default();
+        event.record(&mut fields);
+        let mut span_fields = Fields::default();
+        for span in ctx
+            .event_scope(event)
+            .into_iter()
+            .flat_map(|scope| scope.from_root())
+        {
+            if let Some(fields) = span.extensions().get::<Fields>() {
+                for (name, value) in &fields.0 {
+                    span_fields.insert(name, value.clone());
+                }
+            }
+        }
+
+        self.sink.write(self.format(event, &fields, &span_fields));
+    }
+}
+
+/// The fields of an event or span, unescaped, a field recorded again
+/// replacing its value.
+#[derive(Debug, Default)]
+struct Fields(Vec<(&'static str, String)>);
+
+impl Fields {
+    fn insert(&mut self, name: &'static str, value: String) {
+        match self.0.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, old)) => *old = value,
+            None => self.0.push((name, value)),
+        }
+    }
+}
+
+impl Visit for Fields {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.insert(field.name(), value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.insert(field.name(), format!("{:?}", value));
+    }
+}
@@ -0,0 +1,118 @@
+use arrow_array::{
+    cast::AsArray,
+    types::{Int32Type, TimestampNanosecondType, UInt16Type, UInt8Type},
+    Array,
+};
+use rsyslog::{
+    arrow::{HerokuRouterColumns, RecordBatchBuilder},
+    parser::{msg::HerokuRouter, StructuredData},
+    Message,
+};
+
+#[test]
+fn arrow_test_message() {
+    let msg = r#"<29>1 2016-02-21T04:32:57.853Z web1 someservice - - [origin x-service="someservice"] GET /v1/ok 200"#;
+    let message: Message = Message::parse(msg).expect("parsing message");
+
+    let mut builder = RecordBatchBuilder::new();
+    builder.append(&message);
+    let batch = builder.finish().expect("building batch");
+
+    let names = batch
+        .schema()
+        .fields()
+        .iter()
+        .map(|field| field.name().clone())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        [
+            "facility",
+            "severity",
+            "version",
+            "timestamp",
+            "hostname",
+            "app_name",
+            "proc_id",
+            "msg_id",
+            "structured_data",
+            "msg"
+        ]
+    );
+    assert_eq!(batch.num_rows(), 1);
+    assert_eq!(batch.column(0).as_primitive::<UInt8Type>().value(0), 3);
+    assert_eq!(batch.column(1).as_primitive::<UInt8Type>().value(0), 5);
+    assert_eq!(
+        batch
+            .column(3)
+            .as_primitive::<TimestampNanosecondType>()
+            .value(0),
+        1_456_029_177_853_000_000
+    );
+    assert!(batch.column(6).is_null(0));
+    assert_eq!(
+        batch.column(9).as_string::<i32>().value(0),
+        "GET /v1/ok 200"
+    );
+}
+
+#[test]
+fn arrow_heroku_router_columns() {
+    let msgs = concat!(
+        "<158>1 2021-03-01T19:04:19.887695+00:00 host heroku router - - ",
+        "at=info method=POST path=\"/api/v1/events/smartcam\" ",
+        "host=ratatoskr.mobility46.se request_id=5599e09a ",
+        "fwd=\"157.230.107.240\" dyno=web.1 connect=0ms service=97ms ",
+        "status=200 bytes=140 protocol=https\n",
+        "<158>1 2012-10-11T03:47:20+00:00 host heroku router - - ",
+        r#"at=error code=H12 desc="Request timeout" method=GET path="/" "#,
+        "host=myapp.herokuapp.com request_id=8601b555 ",
+        r#"fwd="204.204.204.204" dyno=web.1 connect=1ms service=30000ms "#,
+        "status=503 bytes= protocol=http"
+    );
+
+    let mut builder = RecordBatchBuilder::with_msg_columns(HerokuRouterColumns::default());
+    for message in Message::<Option<&str>, Vec<StructuredData>, HerokuRouter>::iter(msgs) {
+        builder.append(&message.expect("parsing message"));
+    }
+    let batch = builder.finish().expect("building batch");
+
+    assert_eq!(batch.num_rows(), 2);
+    let status = batch.column_by_name("status").expect("status column");
+    assert_eq!(status.as_primitive::<UInt16Type>().values(), &[200, 503]);
+    let bytes = batch.column_by_name("bytes").expect("bytes column");
+    assert!(bytes.is_valid(0) && bytes.is_null(1));
+    let codes = batch.column_by_name("code").expect("code column");
+    let codes = codes.as_dictionary::<Int32Type>();
+    assert!(codes.is_null(0));
+    assert_eq!(codes.values().as_string::<i32>().value(0), "H12");
+}
+
+#[cfg(feature = "parquet")]
+#[test]
+fn parquet_roundtrip() {
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+    use rsyslog::parser::msg::Raw;
+
+    let msgs = "<29>1 - web1 app - - - first\n<29>1 - web2 app - - [a b=\"c\"] second";
+    let mut builder = RecordBatchBuilder::new();
+    for message in Message::<Option<&str>, Vec<StructuredData>, Raw>::iter(msgs) {
+        builder.append(&message.expect("parsing message"));
+    }
+    let batch = builder.finish().expect("building batch");
+
+    let path = std::env::temp_dir().join(format!("rsyslog-{}.parquet", std::process::id()));
+    let file = std::fs::File::create(&path).expect("creating parquet file");
+    rsyslog::arrow::write_parquet(file, [batch.clone()]).expect("writing parquet");
+
+    let file = std::fs::File::open(&path).expect("opening parquet file");
+    let reader = ParquetRecordBatchReaderBuilder::try_new(file)
+        .and_then(|builder| builder.build())
+        .expect("reading parquet");
+    let batches = reader
+        .collect::<Result<Vec<_>, _>>()
+        .expect("reading batches");
+    std::fs::remove_file(&path).expect("removing parquet file");
+
+    assert_eq!(batches, vec![batch]);
+}
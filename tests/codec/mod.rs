@@ -0,0 +1,68 @@
+use futures_util::{SinkExt, StreamExt};
+use rsyslog::{
+    codec::{self, SyslogCodec},
+    parser::msg::Raw,
+    server::Framing,
+    Message,
+};
+use tokio_util::codec::FramedWrite;
+
+#[tokio::test]
+async fn round_trip_over_tcp() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("binding listener");
+    let addr = listener.local_addr().expect("local address");
+
+    let sender = tokio::spawn(async move {
+        let stream = tokio::net::TcpStream::connect(addr)
+            .await
+            .expect("connecting");
+        let mut frames = FramedWrite::new(stream, SyslogCodec::default());
+        for (i, msg) in ["first", "second\nline"].into_iter().enumerate() {
+            let message: Message = Message {
+                facility: 1,
+                severity: 6,
+                version: 1,
+                timestamp: Some("2024-01-01T00:00:00Z"),
+                hostname: Some("host"),
+                app_name: Some("app"),
+                proc_id: Some(if i == 0 { "1" } else { "2" }),
+                msg_id: None,
+                structured_data: vec![],
+                msg: Raw::from(msg),
+            };
+            frames.send(&message).await.expect("sending message");
+        }
+    });
+
+    let (stream, _) = listener.accept().await.expect("accepting");
+    let frames = codec::frames(stream)
+        .map(|frame| frame.expect("reading").expect("decoding frame"))
+        .collect::<Vec<_>>()
+        .await;
+    sender.await.expect("sender task");
+
+    assert_eq!(frames.len(), 2);
+    let message: Message = frames[1].parse().expect("parsing message");
+    assert_eq!(message.proc_id, Some("2"));
+    assert_eq!(message.msg, Raw::from("second\nline"));
+}
+
+#[tokio::test]
+async fn newline_framing() {
+    let (client, server) = tokio::io::duplex(64);
+    let mut writer = FramedWrite::new(client, SyslogCodec::new().framing(Framing::NonTransparent));
+    let message: Message = Message::parse("<13>1 - - - - - - hello").expect("parsing");
+    writer.send(message).await.expect("sending message");
+    drop(writer);
+
+    let frames = codec::frames(server).collect::<Vec<_>>().await;
+    assert_eq!(frames.len(), 1);
+    let frame = frames[0]
+        .as_ref()
+        .expect("reading")
+        .as_ref()
+        .expect("decoding");
+    assert_eq!(frame.text, "<13>1 - - - - - - hello");
+}
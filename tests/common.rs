@@ -0,0 +1,32 @@
+use std::{
+    io::{self, Write},
+    sync::{Arc, Mutex},
+};
+
+/// A writer whose output can be read back line by line.
+#[derive(Clone, Default)]
+pub struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+impl Buffer {
+    pub fn lines(&self) -> Vec<String> {
+        let buf = self.0.lock().expect("locking buffer");
+        String::from_utf8_lossy(&buf)
+            .lines()
+            .map(String::from)
+            .collect()
+    }
+}
+
+impl Write for Buffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0
+            .lock()
+            .expect("locking buffer")
+            .extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
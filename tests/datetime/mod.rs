@@ -22,7 +22,26 @@ fn message_with_timestamp() {
             proc_id: Some("proc_id"),
             msg_id: Some("msg_id"),
             structured_data: vec![],
-            msg: Raw { msg: "a message" }
+            msg: Raw::from("a message")
         })
     );
 }
+
+#[test]
+fn format_timestamp() {
+    for (msg, formatted) in [
+        (
+            "<1>1 2021-03-01T19:04:19.887695+00:00 host app - - - a message",
+            "<1>1 2021-03-01T19:04:19.887695Z host app - - - a message",
+        ),
+        (
+            "<1>1 2003-10-11T22:14:15.003000123-07:30 host app - - - a message",
+            "<1>1 2003-10-11T22:14:15.003-07:30 host app - - - a message",
+        ),
+    ] {
+        let message: Message<Option<DateTime>, Vec<StructuredData>, Raw> =
+            Message::parse(msg).expect("parsing message");
+
+        assert_eq!(message.to_string(), formatted);
+    }
+}
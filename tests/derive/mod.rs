@@ -0,0 +1,117 @@
+use rsyslog::{
+    parser::{
+        msg::{HerokuErrorCode, HerokuRouter, LineRaw},
+        Skip,
+    },
+    Error, Message, ParseMsg, ParsePart,
+};
+
+#[derive(Debug, PartialEq, ParseMsg)]
+struct Router<'a> {
+    at: &'a str,
+    code: Option<HerokuErrorCode<'a>>,
+    #[rsyslog(key = "service", suffix = "ms")]
+    service_ms: u32,
+    status: u16,
+    bytes: Option<u64>,
+    #[rsyslog(extra)]
+    extra: Vec<(&'a str, &'a str)>,
+}
+
+#[derive(Debug, PartialEq, ParseMsg)]
+struct Counter {
+    count: u32,
+}
+
+#[derive(Debug, PartialEq, ParseMsg)]
+enum Drain<'a> {
+    #[rsyslog(when(app_name = "heroku", proc_id = "router"))]
+    Router(Box<HerokuRouter<'a>>),
+    #[rsyslog(when(app_name = "app"))]
+    Counter(Counter),
+    Other(LineRaw<'a>),
+}
+
+#[derive(Debug, PartialEq, ParsePart)]
+enum Timestamp<'a> {
+    Skipped(Skip),
+    Raw(Option<&'a str>),
+}
+
+#[test]
+fn derive_struct() {
+    let msg = concat!(
+        "<158>1 2012-10-11T03:47:20+00:00 host heroku router - ",
+        r#"at=error code=H12 desc="Request timeout" method=GET path="/" "#,
+        "service=30000ms status=503 bytes= protocol=http"
+    );
+    let msg: Result<Message<Option<&str>, Skip, Router>, Error> = Message::parse(msg);
+
+    assert_eq!(
+        msg.map(|m| m.msg),
+        Ok(Router {
+            at: "error",
+            code: Some(HerokuErrorCode::H12),
+            service_ms: 30000,
+            status: 503,
+            bytes: None,
+            extra: vec![
+                ("desc", "Request timeout"),
+                ("method", "GET"),
+                ("path", "/"),
+                ("protocol", "http")
+            ]
+        })
+    );
+}
+
+#[test]
+fn derive_struct_errors() {
+    let msg = "<158>1 - host app web.1 - at=info status=200";
+    let msg: Result<Message<Option<&str>, Skip, Router>, Error> = Message::parse(msg);
+    assert_eq!(
+        msg,
+        Err(Error::Custom(
+            "Router: missing required key `service`".into()
+        ))
+    );
+
+    let msg = "<158>1 - host app web.1 - count=many";
+    let msg: Result<Message<Option<&str>, Skip, Counter>, Error> = Message::parse(msg);
+    assert!(matches!(msg, Err(Error::Custom(e)) if e.starts_with("Counter: key `count`")));
+}
+
+#[test]
+fn derive_enum() {
+    let msg = concat!(
+        "<134>1 - host app web.1 - count=3\n",
+        "<134>1 - host app web.1 - not a counter\n",
+        "<134>1 - host heroku web.1 - count=3"
+    );
+    let msgs = Message::<Option<&str>, Skip, Drain>::iter(msg)
+        .map(|m| m.map(|m| m.msg))
+        .collect::<Result<Vec<_>, _>>();
+
+    assert_eq!(
+        msgs,
+        Ok(vec![
+            Drain::Counter(Counter { count: 3 }),
+            Drain::Other(LineRaw {
+                msg: "not a counter".into()
+            }),
+            Drain::Other(LineRaw::from("count=3")),
+        ])
+    );
+    assert_eq!(
+        Drain::from(Counter { count: 1 }),
+        Drain::Counter(Counter { count: 1 })
+    );
+}
+
+#[test]
+fn derive_part() {
+    let msg = "<1>1 2021-03-01T19:04:19.887695+00:00 host app - - - msg";
+    let msg: Result<Message<Timestamp, Skip, LineRaw>, Error> = Message::parse(msg);
+
+    assert_eq!(msg.map(|m| m.timestamp), Ok(Timestamp::Skipped(Skip)));
+}
@@ -0,0 +1,62 @@
+use rsyslog::Message;
+use serde_json::json;
+
+#[test]
+fn ecs_test_message() {
+    let msg = r#"<165>1 2003-10-11T22:14:15.003-07:00 mymachine.example.com evntslog 1234 ID47 [exampleSDID@32473 iut="3" eventSource="Application"] An application event"#;
+    let message: Message = Message::parse(msg).expect("parsing message");
+
+    let ecs = serde_json::to_value(message.to_ecs().original(msg)).expect("serializing");
+
+    assert_eq!(
+        ecs,
+        json!({
+            "@timestamp": "2003-10-12T05:14:15.003000000Z",
+            "message": "An application event",
+            "log": {
+                "level": "Notice",
+                "syslog": {
+                    "facility": { "code": 20, "name": "local4" },
+                    "severity": { "code": 5, "name": "Notice" },
+                    "priority": 165,
+                    "version": 1,
+                    "hostname": "mymachine.example.com",
+                    "appname": "evntslog",
+                    "procid": "1234",
+                    "msgid": "ID47",
+                    "structured_data": {
+                        "exampleSDID@32473": { "iut": "3", "eventSource": "Application" }
+                    }
+                }
+            },
+            "host": { "hostname": "mymachine.example.com" },
+            "process": { "name": "evntslog", "pid": 1234 },
+            "event": { "original": msg },
+            "ecs": { "version": "8.11.0" }
+        })
+    );
+}
+
+#[test]
+fn ecs_nil_fields() {
+    let message: Message = Message::parse("<14>1 - - - - - - hi").expect("parsing message");
+
+    let ecs = serde_json::to_value(message.to_ecs()).expect("serializing");
+
+    assert_eq!(
+        ecs,
+        json!({
+            "message": "hi",
+            "log": {
+                "level": "Informational",
+                "syslog": {
+                    "facility": { "code": 1, "name": "user" },
+                    "severity": { "code": 6, "name": "Informational" },
+                    "priority": 14,
+                    "version": 1
+                }
+            },
+            "ecs": { "version": "8.11.0" }
+        })
+    );
+}
@@ -0,0 +1,74 @@
+use rsyslog::{
+    gelf::{self, Compression},
+    Message,
+};
+
+#[test]
+fn gelf_test_message() {
+    let msg = r#"<29>1 2016-02-21T04:32:57.853Z web1 someservice 42 - [origin x-service="someservice"][meta sequenceId="14125553"] GET /v1/ok 200"#;
+    let message: Message = Message::parse(msg).expect("parsing message");
+
+    let gelf: serde_json::Value =
+        serde_json::from_slice(&gelf::to_vec(&message)).expect("decoding gelf");
+
+    assert_eq!(
+        gelf,
+        serde_json::json!({
+            "version": "1.1",
+            "host": "web1",
+            "short_message": "GET /v1/ok 200",
+            "timestamp": 1456029177.853,
+            "level": 5,
+            "_facility": 3,
+            "_app_name": "someservice",
+            "_proc_id": "42",
+            "_origin_x-service": "someservice",
+            "_meta_sequenceId": "14125553",
+        })
+    );
+}
+
+#[test]
+fn gelf_full_message() {
+    let msg = "<11>1 - - app - - - panicked\n  at main.rs:1\n";
+    let message: Message = Message::parse(msg).expect("parsing message");
+
+    let gelf: serde_json::Value =
+        serde_json::from_slice(&gelf::to_vec(&message)).expect("decoding gelf");
+
+    assert_eq!(gelf["host"], "-");
+    assert_eq!(gelf["short_message"], "panicked");
+    assert_eq!(gelf["full_message"], "panicked\n  at main.rs:1\n");
+    assert_eq!(gelf.get("timestamp"), None);
+}
+
+#[test]
+fn gelf_gzip_datagram() {
+    use std::io::Read;
+
+    let message: Message = Message::parse("<14>1 - host app - - - hello").expect("parsing message");
+    let datagrams =
+        gelf::udp_datagrams(&message, Compression::Gzip, 8192).expect("encoding datagrams");
+    assert_eq!(datagrams.len(), 1);
+
+    let mut json = Vec::new();
+    flate2::read::GzDecoder::new(&datagrams[0][..])
+        .read_to_end(&mut json)
+        .expect("decompressing");
+    assert_eq!(json, gelf::to_vec(&message));
+}
+
+#[test]
+fn gelf_invalid_timestamps() {
+    for msg in [
+        "<29>1 2016-02-21T04:32:57.12345678éZ web1 app - - - hi",
+        "<29>1 2016-02-21T04:32:57+9999999999999999:00 web1 app - - - hi",
+    ] {
+        let message: Message = Message::parse(msg).expect("parsing message");
+        let gelf: serde_json::Value =
+            serde_json::from_slice(&gelf::to_vec(&message)).expect("decoding gelf");
+
+        assert_eq!(gelf["short_message"], "hi");
+        assert!(gelf.get("timestamp").is_none(), "{}", gelf);
+    }
+}
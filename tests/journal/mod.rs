@@ -0,0 +1,111 @@
+use rsyslog::{
+    journal::{self, JournalReader},
+    Message,
+};
+
+#[test]
+fn message_to_journal_and_back() {
+    let msg = r#"<29>1 2016-02-21T04:32:57.853Z host app 42 ID47 [meta eventSource="App"] started"#;
+    let message: Message = Message::parse(msg).expect("parsing message");
+
+    let mut export = Vec::new();
+    journal::write_export(&mut export, &message).expect("writing export");
+
+    assert_eq!(
+        String::from_utf8_lossy(&export),
+        concat!(
+            "SYSLOG_TIMESTAMP=2016-02-21T04:32:57.853Z\n",
+            "PRIORITY=5\n",
+            "SYSLOG_FACILITY=3\n",
+            "_HOSTNAME=host\n",
+            "SYSLOG_IDENTIFIER=app\n",
+            "_PID=42\n",
+            "MESSAGE_ID=ID47\n",
+            "SD_META_EVENTSOURCE=App\n",
+            "MESSAGE=started\n",
+            "\n"
+        )
+    );
+
+    let entry = JournalReader::new(&export)
+        .next()
+        .expect("an entry")
+        .expect("reading entry");
+    let back = entry.to_message().expect("mapping entry");
+
+    assert_eq!(back.facility, 3);
+    assert_eq!(back.severity, 5);
+    assert_eq!(back.timestamp, None);
+    assert_eq!(back.hostname, Some("host"));
+    assert_eq!(back.app_name, Some("app"));
+    assert_eq!(back.proc_id, Some("42"));
+    assert_eq!(back.msg_id, Some("ID47"));
+    assert_eq!(back.msg.msg, "started");
+}
+
+#[test]
+fn journal_timestamp_roundtrip() {
+    let entry = concat!(
+        "__REALTIME_TIMESTAMP=1700000000123456\n",
+        "_HOSTNAME=node\n",
+        "SYSLOG_PID=7\n",
+        "MESSAGE=hi\n",
+        "\n"
+    );
+    let message = JournalReader::new(entry.as_bytes())
+        .next()
+        .expect("an entry")
+        .and_then(|entry| entry.to_message())
+        .expect("mapping entry");
+
+    assert_eq!(message.timestamp, Some(1700000000123456));
+    assert_eq!((message.facility, message.severity), (1, 6));
+    assert_eq!(message.proc_id, Some("7"));
+
+    let mut export = Vec::new();
+    journal::write_export(&mut export, &message).expect("writing export");
+    assert!(export.starts_with(b"__REALTIME_TIMESTAMP=1700000000123456\n"));
+}
+
+#[test]
+fn journal_sd_params_dont_collide() {
+    let long = "n".repeat(80);
+    let msg = format!(
+        r#"<29>1 - host app 42 - [x pid="1" priority="0" message="m" _hostname="h" {}="v"] hi"#,
+        long
+    );
+    let message: Message = Message::parse(&msg).expect("parsing message");
+
+    let mut export = Vec::new();
+    journal::write_export(&mut export, &message).expect("writing export");
+
+    let entry = JournalReader::new(&export)
+        .next()
+        .expect("an entry")
+        .expect("reading entry");
+    let names = entry
+        .fields
+        .iter()
+        .map(|(name, _)| *name)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        vec![
+            "PRIORITY",
+            "SYSLOG_FACILITY",
+            "_HOSTNAME",
+            "SYSLOG_IDENTIFIER",
+            "_PID",
+            "SD_X_PID",
+            "SD_X_PRIORITY",
+            "SD_X_MESSAGE",
+            "SD_X__HOSTNAME",
+            &format!("SD_X_{}", "N".repeat(59)),
+            "MESSAGE",
+        ]
+    );
+    let back = entry.to_message().expect("mapping entry");
+    assert_eq!((back.severity, back.proc_id), (5, Some("42")));
+    assert_eq!(back.hostname, Some("host"));
+    assert_eq!(back.msg.msg, "hi");
+}
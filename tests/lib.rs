@@ -1,3 +1,31 @@
+#[cfg(feature = "arrow")]
+pub mod arrow;
+#[cfg(feature = "codec")]
+pub mod codec;
+#[cfg(any(feature = "log", feature = "tracing"))]
+mod common;
 #[cfg(feature = "chrono-timestamp")]
 pub mod datetime;
+#[cfg(feature = "derive")]
+pub mod derive;
+#[cfg(feature = "serde")]
+pub mod ecs;
+#[cfg(feature = "gelf")]
+pub mod gelf;
+pub mod journal;
+#[cfg(feature = "log")]
+pub mod log;
+pub mod logplex;
+#[cfg(feature = "serde")]
+pub mod otel;
 pub mod parser;
+#[cfg(feature = "relp")]
+pub mod relp;
+#[cfg(all(feature = "sender", feature = "server"))]
+pub mod sender;
+#[cfg(feature = "serde")]
+pub mod serde;
+#[cfg(feature = "server")]
+pub mod server;
+#[cfg(feature = "tracing")]
+pub mod tracing;
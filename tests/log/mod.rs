@@ -0,0 +1,152 @@
+use crate::common::Buffer;
+use ::log::{Level, LevelFilter, Log, Record};
+use rsyslog::{
+    log::{Sink, SyslogLogger, SD_ID},
+    parser::{SdParam, StructuredData},
+    Message,
+};
+
+fn logger(buffer: &Buffer) -> SyslogLogger {
+    SyslogLogger::new(Sink::writer(buffer.clone()))
+        .hostname("web1")
+        .app_name("billing")
+        .facility(16)
+}
+
+#[test]
+fn log_record_round_trip() {
+    let buffer = Buffer::default();
+    logger(&buffer).log(
+        &Record::builder()
+            .level(Level::Warn)
+            .target("billing::db")
+            .file(Some("src/db.rs"))
+            .line(Some(42))
+            .args(format_args!("query took {}ms", 120))
+            .build(),
+    );
+
+    let lines = buffer.lines();
+    let message: Message = Message::parse(&lines[0]).expect("parsing message");
+    assert_eq!(message.facility, 16);
+    assert_eq!(message.severity, 4);
+    assert!(message.timestamp.is_some());
+    assert_eq!(message.hostname, Some("web1"));
+    assert_eq!(message.app_name, Some("billing"));
+    assert_eq!(
+        message.proc_id,
+        Some(std::process::id().to_string().as_str())
+    );
+    assert_eq!(
+        message.structured_data,
+        vec![StructuredData {
+            id: SD_ID,
+            params: vec![
+                SdParam::from(("target", "billing::db")),
+                ("file", "src/db.rs").into(),
+                ("line", "42").into(),
+            ]
+        }]
+    );
+    assert_eq!(message.msg.msg, "query took 120ms");
+}
+
+#[test]
+fn log_escapes_and_single_lines() {
+    let buffer = Buffer::default();
+    logger(&buffer).log(
+        &Record::builder()
+            .level(Level::Trace)
+            .target("app")
+            .file(Some(r#"C:\src\my "app"]\main.rs"#))
+            .args(format_args!("first\nsecond"))
+            .build(),
+    );
+
+    let lines = buffer.lines();
+    assert_eq!(lines.len(), 1);
+    let message: Message = Message::parse(&lines[0]).expect("parsing message");
+    assert_eq!(message.severity, 7);
+    assert_eq!(
+        message.structured_data[0].params,
+        vec![
+            SdParam::from(("target", "app")),
+            ("file", r#"C:\\src\\my \"app\"\]\\main.rs"#).into(),
+        ]
+    );
+    assert_eq!(message.msg.msg, "first second");
+}
+
+#[test]
+fn log_level_filter() {
+    let buffer = Buffer::default();
+    let logger = logger(&buffer).level(LevelFilter::Info);
+    for level in [Level::Error, Level::Info, Level::Debug] {
+        logger.log(
+            &Record::builder()
+                .level(level)
+                .args(format_args!("{}", level))
+                .build(),
+        );
+    }
+
+    let severities = buffer
+        .lines()
+        .iter()
+        .map(|line| {
+            let message: Message = Message::parse(line).expect("parsing message");
+            (message.severity, message.msg.msg.to_string())
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        severities,
+        vec![(3, "ERROR".to_string()), (6, "INFO".to_string())]
+    );
+}
+
+#[cfg(feature = "sender")]
+#[test]
+fn log_to_sender() {
+    use rsyslog::sender::{Sender, Transport};
+    use std::{net::UdpSocket, time::Duration};
+
+    let socket = UdpSocket::bind("127.0.0.1:0").expect("binding socket");
+    socket
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .expect("setting timeout");
+    let addr = socket.local_addr().expect("socket address").to_string();
+    let logger = SyslogLogger::new(Sink::Sender(Sender::new(Transport::Udp(addr))));
+
+    logger.log(
+        &Record::builder()
+            .level(Level::Error)
+            .target("app")
+            .args(format_args!("disk full"))
+            .build(),
+    );
+    logger.flush();
+
+    let mut buf = [0; 1024];
+    let len = socket.recv(&mut buf).expect("receiving datagram");
+    let text = std::str::from_utf8(&buf[..len]).expect("utf-8 datagram");
+    let message: Message = Message::parse(text).expect("parsing message");
+    assert_eq!(message.severity, 3);
+    assert_eq!(message.msg.msg, "disk full");
+}
+
+#[test]
+fn log_default_header() {
+    let buffer = Buffer::default();
+    SyslogLogger::new(Sink::writer(buffer.clone())).log(
+        &Record::builder()
+            .level(Level::Info)
+            .args(format_args!("hi"))
+            .build(),
+    );
+
+    let lines = buffer.lines();
+    let message: Message = Message::parse(&lines[0]).expect("parsing message");
+    assert!(message.hostname.is_some());
+    assert!(message.app_name.is_some());
+    assert_eq!(message.msg.msg, "hi");
+}
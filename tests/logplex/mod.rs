@@ -0,0 +1,192 @@
+use rsyslog::{
+    logplex::{LogplexRequest, CONTENT_TYPE},
+    parser::msg::Heroku,
+    Error,
+};
+
+const FRAMES: &[&str] = &[
+    "<40>1 2012-11-30T06:45:29+00:00 host app web.3 - State changed from starting to up\n",
+    concat!(
+        "<158>1 2012-11-30T06:45:30+00:00 host heroku router - at=info method=GET path=\"/\" ",
+        "host=example.herokuapp.com request_id=a1b2 fwd=\"10.0.0.1\" dyno=web.3 connect=1ms ",
+        "service=4ms status=200 bytes=1548 protocol=https\n"
+    ),
+];
+
+fn body(frames: &[&str]) -> String {
+    frames
+        .iter()
+        .map(|frame| format!("{} {}", frame.len(), frame))
+        .collect()
+}
+
+fn headers(msg_count: &str) -> Vec<(&'static str, &str)> {
+    vec![
+        ("content-type", CONTENT_TYPE),
+        ("logplex-msg-count", msg_count),
+        ("logplex-frame-id", "09C557EAFCFB6CF2740EE62F62971098"),
+        (
+            "logplex-drain-token",
+            "d.fc6b856b-3332-4546-93de-7d0ee272c3bd",
+        ),
+    ]
+}
+
+fn lookup<'a>(headers: &'a [(&'static str, &'a str)]) -> impl Fn(&str) -> Option<&'a str> {
+    move |name| {
+        headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| *value)
+    }
+}
+
+#[test]
+fn drain_request() {
+    let body = body(FRAMES);
+    let headers = headers("2");
+    let request = LogplexRequest::new(lookup(&headers), body.as_bytes()).expect("validating");
+
+    assert_eq!(request.frame_id, "09C557EAFCFB6CF2740EE62F62971098");
+    assert_eq!(
+        request.drain_token,
+        "d.fc6b856b-3332-4546-93de-7d0ee272c3bd"
+    );
+    assert_eq!(request.frames[0], FRAMES[0].trim_end());
+
+    let messages = request
+        .messages()
+        .collect::<Result<Vec<_>, _>>()
+        .expect("parsing messages");
+    assert_eq!(messages[0].proc_id, Some("web.3"));
+    assert!(matches!(
+        &messages[0].msg,
+        Heroku::Other(line) if line.msg == "State changed from starting to up"
+    ));
+    assert!(matches!(
+        &messages[1].msg,
+        Heroku::Router(router) if router.status == 200 && router.path == "/"
+    ));
+}
+
+#[test]
+fn msg_count_mismatch() {
+    let body = body(FRAMES);
+    let headers = headers("3");
+
+    assert_eq!(
+        LogplexRequest::new(lookup(&headers), body.as_bytes()),
+        Err(Error::Custom(
+            "logplex: `Logplex-Msg-Count` is 3 but the body has 2 frames".into()
+        ))
+    );
+}
+
+#[test]
+fn invalid_headers() {
+    let body = body(FRAMES);
+    let mut headers = headers("2");
+    headers[0].1 = "text/plain";
+    assert_eq!(
+        LogplexRequest::new(lookup(&headers), body.as_bytes()),
+        Err(Error::Custom(
+            "logplex: unexpected content type `text/plain`".into()
+        ))
+    );
+
+    headers[0].1 = "application/logplex-1; charset=utf-8";
+    headers.remove(3);
+    assert_eq!(
+        LogplexRequest::new(lookup(&headers), body.as_bytes()),
+        Err(Error::Custom(
+            "logplex: missing `Logplex-Drain-Token`".into()
+        ))
+    );
+}
+
+#[cfg(feature = "http")]
+#[test]
+fn http_request() {
+    let request = http::Request::post("/logs")
+        .header("Content-Type", CONTENT_TYPE)
+        .header("Logplex-Msg-Count", "1")
+        .header("Logplex-Frame-Id", "frame")
+        .header("Logplex-Drain-Token", "d.token")
+        .body(body(&FRAMES[..1]).into_bytes())
+        .expect("building request");
+    let request = LogplexRequest::from_http(&request).expect("validating");
+
+    assert_eq!(request.frame_id, "frame");
+    assert_eq!(request.messages().count(), 1);
+}
+
+#[cfg(feature = "axum")]
+#[tokio::test]
+async fn axum_drain() {
+    use axum::{routing::post, Router};
+    use rsyslog::logplex::Logplex;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    async fn drain(logplex: Logplex) -> String {
+        let request = logplex.request();
+        let paths = request
+            .messages()
+            .filter_map(|message| match message.ok()?.msg {
+                Heroku::Router(router) => Some(router.path.to_string()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        format!("{} {}", request.drain_token, paths.join(","))
+    }
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("binding listener");
+    let addr = listener.local_addr().expect("local address");
+    let app = Router::new().route("/logs", post(drain));
+    tokio::spawn(async move { axum::serve(listener, app).await });
+
+    let post = |msg_count: &str| {
+        let body = body(FRAMES);
+        let request = format!(
+            concat!(
+                "POST /logs HTTP/1.1\r\nHost: {}\r\nContent-Type: {}\r\n",
+                "Logplex-Msg-Count: {}\r\nLogplex-Frame-Id: frame\r\n",
+                "Logplex-Drain-Token: d.token\r\nContent-Length: {}\r\n",
+                "Connection: close\r\n\r\n{}"
+            ),
+            addr,
+            CONTENT_TYPE,
+            msg_count,
+            body.len(),
+            body
+        );
+        async move {
+            let mut stream = tokio::net::TcpStream::connect(addr)
+                .await
+                .expect("connecting");
+            stream
+                .write_all(request.as_bytes())
+                .await
+                .expect("writing request");
+            let mut response = String::new();
+            stream
+                .read_to_string(&mut response)
+                .await
+                .expect("reading response");
+            response
+        }
+    };
+
+    let response = post("2").await;
+    assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+    assert!(response.ends_with("d.token /"), "{}", response);
+
+    let response = post("1").await;
+    assert!(response.starts_with("HTTP/1.1 400"), "{}", response);
+    assert!(
+        response.ends_with("logplex: `Logplex-Msg-Count` is 1 but the body has 2 frames"),
+        "{}",
+        response
+    );
+}
@@ -0,0 +1,29 @@
+use rsyslog::Message;
+use serde_json::json;
+
+#[test]
+fn otel_test_message() {
+    let msg = r#"<11>1 2016-02-21T04:32:57.853Z web1 app 42 - [meta sequenceId="14125553"] failed"#;
+    let message: Message = Message::parse(msg).expect("parsing message");
+
+    let record = serde_json::to_value(message.to_otel_log_record()).expect("serializing");
+
+    assert_eq!(
+        record,
+        json!({
+            "Timestamp": 1456029177853000000_u64,
+            "SeverityNumber": 17,
+            "SeverityText": "Error",
+            "Body": "failed",
+            "Attributes": {
+                "facility": 1,
+                "priority": 11,
+                "version": 1,
+                "appname": "app",
+                "proc_id": "42",
+                "structured_data": { "meta": { "sequenceId": "14125553" } }
+            },
+            "Resource": { "host.name": "web1" }
+        })
+    );
+}
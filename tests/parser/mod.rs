@@ -1,10 +1,15 @@
 use rsyslog::{
     parser::{
-        msg::{HerokuRouter, Raw},
+        msg::{
+            Audit, AuditEvents, CiscoMnemonic, Cri, Heroku, HerokuErrorCode, HerokuRouter, LineRaw,
+            LogStream, Logfmt, LogfmtPairs, Multiline, Raw, Reassemble, Regex, RegexCaptures,
+            RegexType, RegexValue,
+        },
         SdParam, Skip, StructuredData,
     },
-    Error, Message,
+    Error, Message, Parser,
 };
+use std::marker::PhantomData;
 
 #[test]
 fn empty_message() {
@@ -23,7 +28,7 @@ fn empty_message() {
             proc_id: None,
             msg_id: None,
             structured_data: vec![],
-            msg: Raw { msg: "" }
+            msg: Raw::from("")
         })
     );
 }
@@ -45,7 +50,7 @@ fn timestamp_message() {
             proc_id: None,
             msg_id: None,
             structured_data: vec![],
-            msg: Raw { msg: "" }
+            msg: Raw::from("")
         })
     );
 }
@@ -67,7 +72,7 @@ fn timestamp_host_message() {
             proc_id: None,
             msg_id: None,
             structured_data: vec![],
-            msg: Raw { msg: "" }
+            msg: Raw::from("")
         })
     );
 }
@@ -89,7 +94,7 @@ fn timestamp_host_app_name_message() {
             proc_id: None,
             msg_id: None,
             structured_data: vec![],
-            msg: Raw { msg: "" }
+            msg: Raw::from("")
         })
     );
 }
@@ -111,7 +116,7 @@ fn timestamp_host_app_name_proc_id_message() {
             proc_id: Some("proc_id"),
             msg_id: None,
             structured_data: vec![],
-            msg: Raw { msg: "" }
+            msg: Raw::from("")
         })
     );
 }
@@ -133,7 +138,7 @@ fn timestamp_host_app_name_proc_id_msg_id_message() {
             proc_id: Some("proc_id"),
             msg_id: Some("msg_id"),
             structured_data: vec![],
-            msg: Raw { msg: "" }
+            msg: Raw::from("")
         })
     );
 }
@@ -161,7 +166,7 @@ fn timestamp_host_app_name_proc_id_structured_data_message() {
                 id: "structured_data",
                 params: vec![]
             }],
-            msg: Raw { msg: "" }
+            msg: Raw::from("")
         })
     );
 }
@@ -190,19 +195,19 @@ fn complex_message() {
                 params: vec![
                     SdParam {
                         name: "iut",
-                        value: "3"
+                        value: "3".into()
                     },
                     SdParam {
                         name: "eventSource",
-                        value: "Application"
+                        value: "Application".into()
                     },
                     SdParam {
                         name: "eventID",
-                        value: "1011"
+                        value: "1011".into()
                     }
                 ]
             }],
-            msg: Raw { msg: "" }
+            msg: Raw::from("")
         })
     );
 }
@@ -233,14 +238,14 @@ fn complex_message2() {
                     id: "origin",
                     params: vec![SdParam {
                         name: "x-service",
-                        value: "someservice"
+                        value: "someservice".into()
                     },]
                 },
                 StructuredData {
                     id: "meta",
                     params: vec![SdParam {
                         name: "sequenceId",
-                        value: "14125553"
+                        value: "14125553".into()
                     },]
                 }
             ],
@@ -249,6 +254,7 @@ fn complex_message2() {
                     r#"127.0.0.1 - - 1456029177 "GET /v1/ok HTTP/1.1" "#,
                     r#"200 145 "-" "hacheck 0.9.0" 24306 127.0.0.1:40124 575"#
                 )
+                .into()
             }
         })
     );
@@ -283,7 +289,7 @@ fn heroku_test_message() {
                 code: None,
                 desc: None,
                 method: "POST",
-                path: "/api/v1/events/smartcam",
+                path: "/api/v1/events/smartcam".into(),
                 host: "ratatoskr.mobility46.se",
                 request_id: "5599e09a-f8e3-4ed9-8be8-6883ce842cf2",
                 fwd: "157.230.107.240",
@@ -292,7 +298,8 @@ fn heroku_test_message() {
                 service: 97,
                 status: 200,
                 bytes: Some(140),
-                protocol: "https"
+                protocol: "https",
+                extra: vec![]
             }
         })
     );
@@ -324,10 +331,10 @@ fn heroku_error_test_message() {
             structured_data: Skip,
             msg: HerokuRouter {
                 at: "error",
-                code: Some("H12"),
-                desc: Some("Request timeout"),
+                code: Some(HerokuErrorCode::H12),
+                desc: Some("Request timeout".into()),
                 method: "GET",
-                path: "/",
+                path: "/".into(),
                 host: "myapp.herokuapp.com",
                 request_id: "8601b555-6a83-4c12-8269-97c8e32cdb22",
                 fwd: "204.204.204.204",
@@ -336,8 +343,258 @@ fn heroku_error_test_message() {
                 service: 30000,
                 status: 503,
                 bytes: None,
-                protocol: "http"
+                protocol: "http",
+                extra: vec![]
             }
         })
     );
 }
+
+#[test]
+fn heroku_reordered_keys_test_message() {
+    let msg = concat!(
+        "<158>1 2021-03-01T19:04:19.887695+00:00 host heroku router - ",
+        "at=info method=GET path=\"/search?host=evil.com\" ",
+        "request_id=5599e09a-f8e3-4ed9-8be8-6883ce842cf2 host=myapp.herokuapp.com ",
+        "fwd=\"157.230.107.240\" dyno=web.2 service=12ms connect=1ms ",
+        "status=304 protocol=https tls_version=tls1.3"
+    );
+    let msg: Result<Message<Option<&str>, Skip, HerokuRouter>, Error> = Message::parse(msg);
+
+    assert_eq!(
+        msg.map(|m| m.msg),
+        Ok(HerokuRouter {
+            at: "info",
+            code: None,
+            desc: None,
+            method: "GET",
+            path: "/search?host=evil.com".into(),
+            host: "myapp.herokuapp.com",
+            request_id: "5599e09a-f8e3-4ed9-8be8-6883ce842cf2",
+            fwd: "157.230.107.240",
+            dyno: "web.2",
+            connect: 1,
+            service: 12,
+            status: 304,
+            bytes: None,
+            protocol: "https",
+            extra: vec![("tls_version", "tls1.3".into())]
+        })
+    );
+}
+
+#[test]
+fn heroku_missing_key_test_message() {
+    let msg = concat!(
+        "<158>1 2021-03-01T19:04:19.887695+00:00 host heroku router - ",
+        "at=info method=GET path=\"/\" host=myapp.herokuapp.com ",
+        "request_id=5599e09a-f8e3-4ed9-8be8-6883ce842cf2 fwd=\"157.230.107.240\" ",
+        "dyno=web.1 connect=1ms service=12ms protocol=https"
+    );
+    let msg: Result<Message<Option<&str>, Skip, HerokuRouter>, Error> = Message::parse(msg);
+
+    assert_eq!(
+        msg,
+        Err(Error::Custom(
+            "heroku router: missing required key `status`".into()
+        ))
+    );
+}
+
+#[test]
+fn heroku_drain_test_messages() {
+    let msg = concat!(
+        "<45>1 2021-03-01T19:04:20+00:00 host heroku web.1 - Error R14 (Memory quota exceeded)\n",
+        "<45>1 2021-03-01T19:04:21+00:00 host heroku web.1 - Process running mem=1021M(199.4%)\n",
+        "<134>1 2021-03-01T19:04:22+00:00 host app heroku-redis - source=REDIS ",
+        "addon=redis-shallow-1234 sample#active-connections=1 sample#load-avg-1m=0.045"
+    );
+
+    let msgs = Message::<Option<&str>, Skip, Heroku>::iter(msg)
+        .map(|m| m.map(|m| m.msg))
+        .collect::<Result<Vec<_>, _>>()
+        .expect("parsing messages");
+
+    assert!(matches!(
+        &msgs[0],
+        Heroku::RuntimeError(e) if e.code == HerokuErrorCode::R14
+    ));
+    assert_eq!(
+        msgs[1],
+        Heroku::Other(LineRaw {
+            msg: "Process running mem=1021M(199.4%)".into()
+        })
+    );
+    assert!(matches!(
+        &msgs[2],
+        Heroku::AddonMetrics(m) if m.addon == Some("redis-shallow-1234") && m.samples.len() == 2
+    ));
+}
+
+#[test]
+fn regex_test_message() {
+    let regex = Regex::new(r#"(?P<ip>\S+) - - (?P<ts>\d+) "(?P<request>[^"]*)" (?P<status>\d{3})"#)
+        .expect("compiling regex")
+        .coerce("status", RegexType::Int)
+        .expect("coercing group");
+    let msg = concat!(
+        "<29>1 2016-02-21T04:32:57+00:00 web1 someservice - - - ",
+        r#"127.0.0.1 - - 1456029177 "GET /v1/ok HTTP/1.1" 200 145"#
+    );
+    let msg: Message<Option<&str>, Vec<StructuredData>, RegexCaptures> =
+        Message::parse_with(msg, &regex).expect("parsing message");
+
+    assert_eq!(msg.hostname, Some("web1"));
+    assert_eq!(msg.msg.get_str("request"), Some("GET /v1/ok HTTP/1.1"));
+    assert_eq!(msg.msg.get("status"), Some(&RegexValue::Int(200)));
+}
+
+#[test]
+fn configured_parser_test_messages() {
+    let parser = Parser::new()
+        .structured_data(PhantomData::<Skip>)
+        .msg(Logfmt::with_keys(&["status", "dyno"]));
+    let msg = concat!(
+        "<158>1 2021-03-01T19:04:19.887695+00:00 host heroku router - ",
+        "at=info dyno=web.1 status=200\n",
+        "<158>1 2021-03-01T19:04:20.887695+00:00 host heroku router - ",
+        "at=info dyno=web.2 status=503"
+    );
+
+    let msgs = parser
+        .iter(msg)
+        .map(|m| m.map(|m| m.msg))
+        .collect::<Result<Vec<_>, _>>();
+
+    assert_eq!(
+        msgs,
+        Ok(vec![
+            LogfmtPairs {
+                pairs: vec![("dyno", "web.1".into()), ("status", "200".into())]
+            },
+            LogfmtPairs {
+                pairs: vec![("dyno", "web.2".into()), ("status", "503".into())]
+            }
+        ])
+    );
+
+    let default = Parser::new()
+        .parse("<1>1 - host - - - - at=info")
+        .expect("parsing message");
+    assert_eq!(default.hostname, Some("host"));
+    assert_eq!(default.msg, Raw::from("at=info"));
+}
+
+#[test]
+fn cri_reassemble_test_messages() {
+    let msg = concat!(
+        "<14>1 - node1 kubelet - - - 2024-01-01T00:00:00.000Z stdout P first half, \n",
+        "<14>1 - node1 kubelet - - - 2024-01-01T00:00:00.001Z stderr F an error\n",
+        "<14>1 - node1 kubelet - - - 2024-01-01T00:00:00.002Z stdout F second half\n",
+        "<14>1 - node1 kubelet - - - 2024-01-01T00:00:00.003Z stdout F whole"
+    );
+
+    let records = Reassemble::new(Message::<Option<&str>, Vec<StructuredData>, Cri>::iter(msg))
+        .map(|m| m.map(|m| (m.msg.timestamp, m.msg.stream, m.msg.log)))
+        .collect::<Result<Vec<_>, _>>();
+
+    assert_eq!(
+        records,
+        Ok(vec![
+            (
+                "2024-01-01T00:00:00.001Z",
+                LogStream::Stderr,
+                "an error".into()
+            ),
+            (
+                "2024-01-01T00:00:00.000Z",
+                LogStream::Stdout,
+                "first half, second half".into()
+            ),
+            (
+                "2024-01-01T00:00:00.003Z",
+                LogStream::Stdout,
+                "whole".into()
+            ),
+        ])
+    );
+}
+
+#[test]
+fn audit_events_test_messages() {
+    let msg = concat!(
+        "<85>1 - host auditd - - - type=SYSCALL msg=audit(1364481363.243:24287): syscall=2 comm=\"cat\"\n",
+        "<85>1 - host auditd - - - type=PATH msg=audit(1364481363.243:24287): item=0 name=\"/etc/shadow\"\n",
+        "<85>1 - host auditd - - - type=EOE msg=audit(1364481363.243:24287): \n",
+        "<85>1 - host auditd - - - type=USER_LOGIN msg=audit(1364481364.001:24288): res=success\n",
+        "<85>1 - host auditd - - - type=SYSCALL msg=audit(1364481364.002:24289): syscall=59"
+    );
+
+    let events = AuditEvents::new(Message::<Option<&str>, Vec<StructuredData>, Audit>::iter(
+        msg,
+    ))
+    .map(|m| {
+        m.map(|m| {
+            let types = m
+                .msg
+                .records
+                .iter()
+                .map(|r| r.record_type)
+                .collect::<Vec<_>>();
+            (m.msg.id.serial, types)
+        })
+    })
+    .collect::<Result<Vec<_>, _>>();
+
+    assert_eq!(
+        events,
+        Ok(vec![
+            (24287, vec!["SYSCALL", "PATH"]),
+            (24288, vec!["USER_LOGIN"]),
+            (24289, vec!["SYSCALL"]),
+        ])
+    );
+}
+
+#[test]
+fn cisco_test_message() {
+    let msg = "<187>1 2024-01-01T00:00:00Z switch1 - - - - 000042: *Mar  1 18:46:11.123 UTC: %LINEPROTO-5-UPDOWN: Line protocol on Interface Vlan1, changed state to up";
+
+    let message = Message::<Option<&str>, Vec<StructuredData>, CiscoMnemonic>::parse(msg)
+        .expect("parsing message");
+
+    assert_eq!(message.severity, 3);
+    assert_eq!(message.msg.sequence, Some(42));
+    assert_eq!(message.msg.facility, "LINEPROTO");
+    assert_eq!(message.msg.severity, 5);
+    assert_eq!(message.msg.mnemonic, "UPDOWN");
+    assert_eq!(
+        message.msg.text,
+        "Line protocol on Interface Vlan1, changed state to up"
+    );
+}
+
+#[test]
+fn multiline_max_lines_iter() {
+    let parser = Parser::new().msg(Multiline::new().max_lines(2));
+    let msg = concat!(
+        "<11>1 - host app - - - panicked\n",
+        "  at main.rs:1\n",
+        "  at lib.rs:2\n",
+        "  at std.rs:3\n",
+        "<14>1 - host app - - - next"
+    );
+
+    let msgs = parser
+        .iter(msg)
+        .map(|m| m.map(|m| m.msg.msg.into_owned()))
+        .collect::<Result<Vec<_>, _>>();
+
+    assert_eq!(
+        msgs,
+        Ok(vec![
+            "panicked\n  at main.rs:1".to_string(),
+            "next".to_string()
+        ])
+    );
+}
@@ -0,0 +1,202 @@
+use rsyslog::{
+    parser::msg::Raw,
+    relp::{RelpClient, RelpReceiver, RelpRejected},
+    Message,
+};
+use std::{
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{SocketAddr, TcpListener},
+    sync::mpsc,
+    thread,
+};
+
+fn message(msg: &str) -> Message<'_> {
+    Message {
+        facility: 13,
+        severity: 6,
+        version: 1,
+        timestamp: Some("2024-01-01T00:00:00Z"),
+        hostname: Some("host"),
+        app_name: Some("audit"),
+        proc_id: None,
+        msg_id: None,
+        structured_data: vec![],
+        msg: Raw::from(msg),
+    }
+}
+
+fn receiver(listener: TcpListener) -> (SocketAddr, mpsc::Receiver<String>) {
+    let receiver = RelpReceiver::new(listener);
+    let addr = receiver.local_addr().expect("receiver address");
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        receiver.run(move |_, message| {
+            let message = message.expect("receiving message");
+            tx.send(message.msg.msg.to_string()).expect("sending msg");
+        })
+    });
+    (addr, rx)
+}
+
+#[test]
+fn relp_client_to_receiver() {
+    let (addr, rx) = receiver(TcpListener::bind("127.0.0.1:0").expect("binding"));
+    let mut client = RelpClient::new(addr.to_string()).window(2);
+
+    for msg in ["one", "two", "three", "four", "five"] {
+        client.send(&message(msg)).expect("sending message");
+        assert!(client.unacked() <= 2);
+    }
+    client.close().expect("closing session");
+
+    let received = rx.iter().take(5).collect::<Vec<_>>();
+    assert_eq!(received, vec!["one", "two", "three", "four", "five"]);
+}
+
+#[test]
+fn relp_client_retransmits_on_reconnect() {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("binding");
+    let addr = listener.local_addr().expect("listener address");
+
+    // A server that opens the session, reads two messages without
+    // acknowledging them and drops the connection.
+    let flaky = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().expect("accepting");
+        let mut received = Vec::new();
+        let mut buf = [0; 1024];
+        let mut opened = false;
+        while received.windows(8).filter(|w| w == b" syslog ").count() < 2 {
+            let read = stream.read(&mut buf).expect("reading");
+            received.extend_from_slice(&buf[..read]);
+            if !opened && received.contains(&b'\n') {
+                stream
+                    .write_all(b"1 rsp 6 200 OK\n")
+                    .expect("answering open");
+                opened = true;
+            }
+        }
+        listener
+    });
+
+    let mut client = RelpClient::new(addr.to_string());
+    client.send(&message("first")).expect("sending message");
+    client.send(&message("second")).expect("sending message");
+    assert_eq!(client.unacked(), 2);
+
+    let (_, rx) = receiver(flaky.join().expect("flaky server"));
+    client.flush().expect("flushing");
+    assert_eq!(client.unacked(), 0);
+
+    let received = rx.iter().take(2).collect::<Vec<_>>();
+    assert_eq!(received, vec!["first", "second"]);
+}
+
+#[test]
+fn relp_receiver_acks_and_closes() {
+    let (addr, rx) = receiver(TcpListener::bind("127.0.0.1:0").expect("binding"));
+    let mut stream = std::net::TcpStream::connect(addr).expect("connecting");
+    let msg = "<14>1 - host app - - - hi";
+    write!(
+        stream,
+        "1 open 14 relp_version=0\n2 syslog {} {}\n3 close 0\n",
+        msg.len(),
+        msg
+    )
+    .expect("writing frames");
+
+    let mut responses = String::new();
+    stream
+        .read_to_string(&mut responses)
+        .expect("reading responses");
+
+    assert_eq!(rx.recv().expect("receiving message"), "hi");
+    assert!(responses.starts_with("1 rsp "), "{}", responses);
+    assert!(
+        responses.ends_with("\n2 rsp 6 200 OK\n3 rsp 0\n"),
+        "{}",
+        responses
+    );
+}
+
+#[test]
+fn relp_client_queue_capacity() {
+    // Nothing listens on the port once the listener is dropped.
+    let addr = TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .expect("reserving a port");
+    let mut client = RelpClient::new(addr.to_string()).queue_capacity(2);
+
+    assert!(client.send(&message("one")).is_err());
+    assert!(client.send(&message("two")).is_err());
+    assert_eq!(client.unacked(), 2);
+
+    let err = client.send(&message("three")).expect_err("queue is full");
+    assert!(
+        err.to_string().contains("queue of 2 messages is full"),
+        "{}",
+        err
+    );
+    assert_eq!(client.unacked(), 2);
+}
+
+#[test]
+fn relp_client_reports_rejections_on_flush() {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("binding");
+    let addr = listener.local_addr().expect("listener address");
+
+    // A server that rejects the messages containing `bad`.
+    thread::spawn(move || {
+        let (stream, _) = listener.accept().expect("accepting");
+        let mut writer = stream.try_clone().expect("cloning stream");
+        let mut reader = BufReader::new(stream);
+        let word = |reader: &mut BufReader<_>| {
+            let mut word = Vec::new();
+            reader.read_until(b' ', &mut word).expect("reading");
+            String::from_utf8(word).expect("utf-8").trim().to_string()
+        };
+        loop {
+            let txnr = word(&mut reader);
+            let command = word(&mut reader);
+            let mut len = Vec::new();
+            while let Some(b) = reader.by_ref().bytes().next() {
+                match b.expect("reading") {
+                    b' ' | b'\n' => break,
+                    b => len.push(b),
+                }
+            }
+            let len: usize = String::from_utf8(len)
+                .expect("utf-8")
+                .parse()
+                .expect("length");
+            let mut data = vec![0; len + usize::from(len > 0)];
+            reader.read_exact(&mut data).expect("reading data");
+            let bad = data.windows(3).any(|w| w == b"bad");
+            let response = match command.as_str() {
+                "close" => format!("{} rsp 0\n", txnr),
+                "syslog" if bad => format!("{} rsp 7 500 bad\n", txnr),
+                _ => format!("{} rsp 6 200 OK\n", txnr),
+            };
+            writer.write_all(response.as_bytes()).expect("answering");
+            if command == "close" {
+                break;
+            }
+        }
+    });
+
+    let mut client = RelpClient::new(addr.to_string()).window(1);
+    client.send(&message("bad")).expect("sending message");
+    client.send(&message("good")).expect("sending message");
+
+    let err = client.flush().expect_err("a message was rejected");
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    let rejected = err
+        .get_ref()
+        .and_then(|err| err.downcast_ref::<RelpRejected>())
+        .expect("rejected messages");
+    assert_eq!(rejected.messages.len(), 1);
+    assert!(rejected.messages[0].ends_with(" bad"), "{:?}", rejected);
+    assert_eq!(client.unacked(), 0);
+
+    client.send(&message("good")).expect("sending message");
+    client.close().expect("closing session");
+}
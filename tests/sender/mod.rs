@@ -0,0 +1,211 @@
+use rsyslog::{
+    parser::msg::Raw,
+    sender::{Sender, Transport},
+    server::{TcpReceiver, UdpReceiver},
+    Message,
+};
+use std::{
+    net::{SocketAddr, TcpListener},
+    sync::mpsc,
+    thread,
+    time::Duration,
+};
+
+fn message(msg: &str) -> Message<'_> {
+    Message {
+        facility: 1,
+        severity: 5,
+        version: 1,
+        timestamp: Some("2024-01-01T00:00:00Z"),
+        hostname: Some("host"),
+        app_name: Some("app"),
+        proc_id: None,
+        msg_id: None,
+        structured_data: vec![("meta@32473", vec![("seq", msg).into()]).into()],
+        msg: Raw::from(msg),
+    }
+}
+
+/// Runs a TCP receiver on `addr`, forwarding the MSG of each message.
+fn tcp_receiver(addr: &str) -> (SocketAddr, mpsc::Receiver<String>) {
+    let receiver = TcpReceiver::bind(addr).expect("binding receiver");
+    let addr = receiver.local_addr().expect("receiver address");
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        receiver.run(move |_, frame| {
+            let message: Message = Message::parse(frame.expect("reading frame")).expect("parsing");
+            tx.send(message.msg.msg.to_string()).expect("sending msg");
+        })
+    });
+    (addr, rx)
+}
+
+#[test]
+fn sender_over_udp() {
+    let mut receiver = UdpReceiver::bind("127.0.0.1:0").expect("binding receiver");
+    let addr = receiver.local_addr().expect("receiver address");
+    let sender = Sender::new(Transport::Udp(addr.to_string()));
+
+    assert!(sender.send(&message("one")));
+    let datagram = receiver.recv().expect("receiving");
+    let received: Message = datagram.parse().expect("parsing message");
+
+    assert_eq!(received, message("one"));
+}
+
+#[test]
+fn sender_over_tcp_in_order() {
+    let (addr, rx) = tcp_receiver("127.0.0.1:0");
+    let sender = Sender::builder(Transport::Tcp(addr.to_string()))
+        .batch_size(2)
+        .build();
+
+    for msg in ["one", "two", "three"] {
+        sender.send(&message(msg));
+    }
+    assert!(sender.flush(Duration::from_secs(5)));
+
+    let received = rx.iter().take(3).collect::<Vec<_>>();
+    assert_eq!(received, vec!["one", "two", "three"]);
+}
+
+#[test]
+fn sender_reconnects() {
+    // Find a free port, then leave it closed until messages are queued.
+    let addr = TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .expect("finding a port");
+    let sender = Sender::builder(Transport::Tcp(addr.to_string()))
+        .backoff(Duration::from_millis(10), Duration::from_millis(50))
+        .build();
+    sender.send(&message("queued"));
+    assert!(!sender.flush(Duration::from_millis(100)));
+
+    let (_, rx) = tcp_receiver(&addr.to_string());
+    sender.send(&message("later"));
+    assert!(sender.flush(Duration::from_secs(5)));
+
+    let received = rx.iter().take(2).collect::<Vec<_>>();
+    assert_eq!(received, vec!["queued", "later"]);
+    assert_eq!(sender.dropped(), 0);
+}
+
+#[test]
+fn sender_delivers_queue_on_drop() {
+    let (addr, rx) = tcp_receiver("127.0.0.1:0");
+    let sender = Sender::new(Transport::Tcp(addr.to_string()));
+    for i in 0..100 {
+        sender.send(&message(&i.to_string()));
+    }
+    drop(sender);
+
+    assert_eq!(rx.iter().take(100).count(), 100);
+}
+
+#[cfg(unix)]
+#[test]
+fn sender_over_unix_datagram() {
+    use rsyslog::server::UnixDatagramReceiver;
+
+    let path = std::env::temp_dir().join(format!("rsyslog-sender-{}.sock", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let mut receiver = UnixDatagramReceiver::bind(&path).expect("binding receiver");
+    let sender = Sender::new(Transport::UnixDatagram(path.clone()));
+
+    sender.send(&message("local"));
+    let msg = receiver
+        .recv_with(|_, message| message.map(|message| message.msg.msg.to_string()))
+        .expect("receiving");
+    std::fs::remove_file(&path).expect("removing socket");
+
+    assert_eq!(msg, Ok("local".to_string()));
+}
+
+#[cfg(feature = "tls")]
+#[test]
+fn sender_over_tls() {
+    use rsyslog::server::{
+        rustls::{
+            crypto::ring,
+            pki_types::{PrivateKeyDer, PrivatePkcs8KeyDer},
+            ClientConfig, RootCertStore,
+        },
+        ClientAuth, TlsReceiver,
+    };
+    use std::sync::Arc;
+
+    let key = rcgen::KeyPair::generate().expect("generating key");
+    let certificate = rcgen::CertificateParams::new(vec!["localhost".to_string()])
+        .expect("certificate params")
+        .self_signed(&key)
+        .expect("signing certificate");
+    let receiver = TlsReceiver::new(
+        TcpReceiver::bind("127.0.0.1:0").expect("binding receiver"),
+        vec![certificate.der().clone()],
+        PrivateKeyDer::from(PrivatePkcs8KeyDer::from(key.serialize_der())),
+        ClientAuth::None,
+    )
+    .expect("tls config");
+    let addr = receiver.local_addr().expect("receiver address");
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        receiver.run(move |_, frame| {
+            let message: Message = Message::parse(frame.expect("reading frame")).expect("parsing");
+            tx.send(message.msg.msg.to_string()).expect("sending msg");
+        })
+    });
+
+    let mut roots = RootCertStore::empty();
+    roots.add(certificate.der().clone()).expect("adding root");
+    let config = ClientConfig::builder_with_provider(Arc::new(ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .expect("protocol versions")
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    let sender = Sender::new(Transport::Tls {
+        addr: addr.to_string(),
+        server_name: "localhost".try_into().expect("server name"),
+        config: Arc::new(config),
+    });
+
+    sender.send(&message("secret"));
+    sender.send(&message("stuff"));
+    assert!(sender.flush(Duration::from_secs(5)));
+
+    let received = rx.iter().take(2).collect::<Vec<_>>();
+    assert_eq!(received, vec!["secret", "stuff"]);
+}
+
+#[cfg(feature = "tls")]
+#[test]
+fn sender_tls_silent_peer() {
+    use rsyslog::server::rustls::{crypto::ring, ClientConfig, RootCertStore};
+    use std::{sync::Arc, time::Instant};
+
+    // Accepts connections and never answers the handshake.
+    let listener = TcpListener::bind("127.0.0.1:0").expect("binding listener");
+    let addr = listener.local_addr().expect("listener address");
+    thread::spawn(move || {
+        let streams = listener.incoming().collect::<Vec<_>>();
+        drop(streams);
+    });
+
+    let config = ClientConfig::builder_with_provider(Arc::new(ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .expect("protocol versions")
+        .with_root_certificates(RootCertStore::empty())
+        .with_no_client_auth();
+    let sender = Sender::builder(Transport::Tls {
+        addr: addr.to_string(),
+        server_name: "localhost".try_into().expect("server name"),
+        config: Arc::new(config),
+    })
+    .connect_timeout(Duration::from_millis(200))
+    .build();
+
+    sender.send(&message("lost"));
+    assert!(!sender.flush(Duration::from_millis(500)));
+    let start = Instant::now();
+    drop(sender);
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
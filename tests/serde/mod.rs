@@ -0,0 +1,111 @@
+use rsyslog::{
+    parser::{
+        msg::{
+            CiscoMnemonic, Cri, HerokuRouter, HerokuRuntimeError, HerokuRuntimeMetrics, LineRaw,
+            Logfmt, LogfmtPairs, MultilineRaw, Regex, RegexCaptures,
+        },
+        Skip,
+    },
+    Message,
+};
+use std::borrow::Cow;
+
+/// A message with the MSG type `$msg`, after a JSON round trip.
+macro_rules! json_roundtrip {
+    ($message:expr, $msg:ty) => {{
+        let message: Message<Option<&str>, Skip, $msg> = $message;
+        let json = serde_json::to_string(&message).expect("serializing");
+        let back: Message<Option<&str>, Skip, $msg> =
+            serde_json::from_str(&json).expect("deserializing");
+        assert_eq!(back, message);
+    }};
+}
+
+#[test]
+fn message_roundtrip() {
+    let msg = r#"<29>1 2016-02-21T04:32:57+00:00 web1 someservice - - [origin x-service="someservice"][meta sequenceId="14125553"] 127.0.0.1 GET /v1/ok 200 145"#;
+    let message: Message = Message::parse(msg).expect("parsing message");
+
+    let json = serde_json::to_string(&message).expect("serializing");
+    let back: Message = serde_json::from_str(&json).expect("deserializing");
+
+    assert_eq!(back, message);
+}
+
+#[test]
+fn heroku_router_roundtrip() {
+    let msg = r#"<158>1 2012-10-11T03:47:20+00:00 host heroku router - at=error code=H12 desc="Request timeout" method=GET path="/" host=myapp.herokuapp.com request_id=8601b555-6a83-4c12-8269-97c8e32cdb22 fwd="204.204.204.204" dyno=web.1 connect=0ms service=30000ms status=503 bytes=0 protocol=https"#;
+    let message: Message<Option<&str>, Skip, HerokuRouter> =
+        Message::parse(msg).expect("parsing message");
+
+    let json = serde_json::to_value(&message).expect("serializing");
+    assert_eq!(json["msg"]["code"], "H12");
+
+    let json = json.to_string();
+    let back: Message<Option<&str>, Skip, HerokuRouter> =
+        serde_json::from_str(&json).expect("deserializing");
+    assert_eq!(back, message);
+}
+
+#[test]
+fn escaped_strings_roundtrip() {
+    let msg = r#"<29>1 - web1 app - - [meta path="C:\\logs\"x\"" plain="v"] said "hi" \o/"#;
+    let message: Message = Message::parse(msg).expect("parsing message");
+
+    let json = serde_json::to_string(&message).expect("serializing");
+    let back: Message = serde_json::from_str(&json).expect("deserializing");
+
+    assert_eq!(back, message);
+    let params = &back.structured_data[0].params;
+    assert!(matches!(params[0].value, Cow::Owned(_)));
+    assert!(matches!(params[1].value, Cow::Borrowed("v")));
+    assert!(matches!(back.msg.msg, Cow::Owned(_)));
+}
+
+#[test]
+fn cisco_escaped_text_roundtrip() {
+    let msg = r#"<187>1 - router - - - - %SYS-5-CONFIG_I: Configured by "admin" from C:\temp"#;
+    let message: Message<Option<&str>, Skip, CiscoMnemonic> =
+        Message::parse(msg).expect("parsing message");
+
+    let json = serde_json::to_string(&message).expect("serializing");
+    let back: Message<Option<&str>, Skip, CiscoMnemonic> =
+        serde_json::from_str(&json).expect("deserializing");
+
+    assert_eq!(back, message);
+    assert_eq!(back.msg.text, r#"Configured by "admin" from C:\temp"#);
+}
+
+#[test]
+fn escaped_msg_types_roundtrip() {
+    let parse = |msg| format!("<14>1 - host app - - {}", msg);
+
+    let msg = parse("Exception in \"main\"\n\tat App.run(App.java:1)\n");
+    json_roundtrip!(Message::parse(&msg).expect("parsing"), MultilineRaw);
+    let msg = parse(r#"said "hi" from C:\tmp"#);
+    json_roundtrip!(Message::parse(&msg).expect("parsing"), LineRaw);
+    let msg = parse(r#"2024-01-01T00:00:00Z stdout F said "hi" from C:\tmp"#);
+    json_roundtrip!(Message::parse(&msg).expect("parsing"), Cri);
+    let msg = parse(concat!(
+        r#"at=error code=H12 desc="C:\timeout" method=GET path="/a\b" host=h "#,
+        r#"request_id=1 fwd="1.2.3.4" dyno=web.1 connect=0ms service=1ms status=503 "#,
+        r#"bytes=0 protocol=https note="C:\x""#
+    ));
+    json_roundtrip!(Message::parse(&msg).expect("parsing"), HerokuRouter);
+    let msg = parse(r#"Error R10 (Boot "timeout") -> failed to bind to "$PORT""#);
+    json_roundtrip!(Message::parse(&msg).expect("parsing"), HerokuRuntimeError);
+    let msg = parse(r#"source=web.1 dyno=heroku.1 sample#load_avg_1m=0.01 note="C:\tmp""#);
+    json_roundtrip!(Message::parse(&msg).expect("parsing"), HerokuRuntimeMetrics);
+
+    let msg = parse(r#"path="C:\tmp" status=200"#);
+    json_roundtrip!(
+        Message::parse_with(&msg, &Logfmt::new()).expect("parsing"),
+        LogfmtPairs
+    );
+    let regex = Regex::new(r"(?P<text>.*)").expect("compiling regex");
+    let msg = parse(r#"said "hi""#);
+    json_roundtrip!(
+        Message::parse_with(&msg, &regex).expect("parsing"),
+        RegexCaptures
+    );
+}
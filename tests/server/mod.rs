@@ -0,0 +1,149 @@
+#[cfg(feature = "tls")]
+mod tls;
+#[cfg(unix)]
+mod unix;
+
+use rsyslog::{
+    parser::{msg::Raw, StructuredData},
+    server::{Framing, TcpReceiver, UdpReceiver},
+    Message,
+};
+use std::{
+    io::{Read, Write},
+    net::{TcpStream, UdpSocket},
+    sync::mpsc,
+    thread,
+    time::Duration,
+};
+
+#[test]
+fn udp_receiver_test_messages() {
+    let mut receiver = UdpReceiver::bind("127.0.0.1:0").expect("binding receiver");
+    let addr = receiver.local_addr().expect("receiver address");
+    let sender = UdpSocket::bind("127.0.0.1:0").expect("binding sender");
+    let msg = r#"<29>1 2016-02-21T04:32:57+00:00 web1 someservice - - [origin x-service="someservice"] GET /v1/ok 200"#;
+    sender.send_to(msg.as_bytes(), addr).expect("sending");
+    sender.send_to(b"not syslog", addr).expect("sending");
+
+    let datagram = receiver.recv().expect("receiving");
+    assert_eq!(
+        datagram.source.addr,
+        sender.local_addr().expect("sender address")
+    );
+    assert!(!datagram.source.truncated);
+    let message: Message = datagram.parse().expect("parsing message");
+    assert_eq!(message.hostname, Some("web1"));
+    assert_eq!(message.msg.msg, "GET /v1/ok 200");
+
+    let failed = receiver
+        .recv_with(
+            |source, message: Result<Message<Option<&str>, Vec<StructuredData>, Raw>, _>| {
+                (source.truncated, message.is_err())
+            },
+        )
+        .expect("receiving");
+    assert_eq!(failed, (false, true));
+}
+
+#[test]
+fn tcp_receiver_framings() {
+    let receiver = TcpReceiver::bind("127.0.0.1:0")
+        .expect("binding receiver")
+        .max_frame_size(64);
+    let addr = receiver.local_addr().expect("receiver address");
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        receiver.run(move |_, frame| {
+            let frame = frame.map(|frame| {
+                let message: Message = Message::parse(frame).expect("parsing message");
+                message.msg.msg.to_string()
+            });
+            tx.send(frame.map_err(|err| err.to_string()))
+                .expect("sending frame");
+        })
+    });
+
+    let mut octet_counted = TcpStream::connect(addr).expect("connecting");
+    octet_counted
+        .write_all(b"26 <13>1 - host app - - - one")
+        .expect("writing");
+    octet_counted.flush().expect("flushing");
+    assert_eq!(rx.recv(), Ok(Ok("one".to_string())));
+    octet_counted
+        .write_all(b"70 ")
+        .and_then(|_| octet_counted.write_all(&[b'x'; 70]))
+        .and_then(|_| octet_counted.write_all(b"26 <13>1 - host app - - - two"))
+        .expect("writing");
+    assert_eq!(
+        rx.recv(),
+        Ok(Err(
+            "framing: frame of 70 bytes exceeds the maximum of 64".to_string()
+        ))
+    );
+    assert_eq!(rx.recv(), Ok(Ok("two".to_string())));
+
+    let mut line_framed = TcpStream::connect(addr).expect("connecting");
+    line_framed
+        .write_all(b"<13>1 - host app - - - three\n<13>1 - host app - - - four\n")
+        .expect("writing");
+    assert_eq!(rx.recv(), Ok(Ok("three".to_string())));
+    assert_eq!(rx.recv(), Ok(Ok("four".to_string())));
+}
+
+#[test]
+fn tcp_receiver_read_with() {
+    let receiver = TcpReceiver::bind("127.0.0.1:0").expect("binding receiver");
+    let mut sender =
+        TcpStream::connect(receiver.local_addr().expect("receiver address")).expect("connecting");
+    sender
+        .write_all(b"<13>1 - host app - - [a b=\"c\"] hi\nnot syslog\n")
+        .expect("writing");
+    drop(sender);
+
+    let (mut frames, peer) = receiver.accept().expect("accepting");
+    assert!(peer.ip().is_loopback());
+    let first = frames
+        .read_with(
+            |message: Result<Message<Option<&str>, Vec<StructuredData>, Raw>, _>| {
+                message.map(|message| message.structured_data.len())
+            },
+        )
+        .expect("reading");
+    assert_eq!(first, Some(Ok(1)));
+    assert_eq!(frames.framing(), Some(Framing::NonTransparent));
+    let second = frames
+        .read_with(
+            |message: Result<Message<Option<&str>, Vec<StructuredData>, Raw>, _>| message.is_err(),
+        )
+        .expect("reading");
+    assert_eq!(second, Some(true));
+    assert!(frames.read_frame().expect("reading").is_none());
+}
+
+#[test]
+fn tcp_receiver_idle_timeout() {
+    let receiver = TcpReceiver::bind("127.0.0.1:0")
+        .expect("binding receiver")
+        .read_timeout(Some(Duration::from_millis(100)))
+        .max_connections(1);
+    let addr = receiver.local_addr().expect("receiver address");
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        receiver.run(move |_, frame| {
+            tx.send(frame.map(str::to_string).map_err(|err| err.to_string()))
+                .expect("sending frame");
+        })
+    });
+
+    let mut idle = TcpStream::connect(addr).expect("connecting");
+    idle.set_read_timeout(Some(Duration::from_secs(5)))
+        .expect("setting timeout");
+    assert!(rx.recv().expect("receiving").is_err());
+    assert_eq!(idle.read(&mut [0; 1]).expect("reading"), 0);
+
+    let mut sender = TcpStream::connect(addr).expect("connecting");
+    sender
+        .write_all(b"<13>1 - host app - - - hi\n")
+        .expect("writing");
+    assert_eq!(rx.recv(), Ok(Ok("<13>1 - host app - - - hi".to_string())));
+}
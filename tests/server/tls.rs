@@ -0,0 +1,232 @@
+use rcgen::{BasicConstraints, Certificate, CertificateParams, DnType, IsCa, KeyPair};
+use rsyslog::{
+    parser::{msg::Raw, StructuredData},
+    server::{
+        rustls::{
+            crypto::ring,
+            pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer},
+            ClientConfig, ClientConnection, RootCertStore, StreamOwned,
+        },
+        ClientAuth, TcpReceiver, TlsReceiver,
+    },
+    Message,
+};
+use sha2::{Digest, Sha256};
+use std::{
+    io::{self, Write},
+    net::{SocketAddr, TcpStream},
+    sync::{mpsc, Arc},
+    thread,
+    time::{Duration, Instant},
+};
+
+struct Identity {
+    certificate: Certificate,
+    key: KeyPair,
+}
+
+impl Identity {
+    fn chain(&self) -> Vec<CertificateDer<'static>> {
+        vec![self.certificate.der().clone()]
+    }
+
+    fn key(&self) -> PrivateKeyDer<'static> {
+        PrivatePkcs8KeyDer::from(self.key.serialize_der()).into()
+    }
+}
+
+fn params(common_name: &str) -> CertificateParams {
+    let mut params =
+        CertificateParams::new(vec!["localhost".to_string()]).expect("certificate params");
+    params
+        .distinguished_name
+        .push(DnType::CommonName, common_name);
+    params
+}
+
+fn self_signed(common_name: &str, is_ca: bool) -> Identity {
+    let key = KeyPair::generate().expect("generating key");
+    let mut params = params(common_name);
+    if is_ca {
+        params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+    }
+    let certificate = params.self_signed(&key).expect("signing certificate");
+
+    Identity { certificate, key }
+}
+
+fn signed_by(common_name: &str, ca: &Identity) -> Identity {
+    let key = KeyPair::generate().expect("generating key");
+    let certificate = params(common_name)
+        .signed_by(&key, &ca.certificate, &ca.key)
+        .expect("signing certificate");
+
+    Identity { certificate, key }
+}
+
+fn send(addr: SocketAddr, ca: &Identity, client: &Identity, frames: &[u8]) -> io::Result<()> {
+    let mut roots = RootCertStore::empty();
+    roots
+        .add(ca.certificate.der().clone())
+        .expect("adding root");
+    let config = ClientConfig::builder_with_provider(Arc::new(ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .expect("protocol versions")
+        .with_root_certificates(roots)
+        .with_client_auth_cert(client.chain(), client.key())
+        .expect("client config");
+    let connection = ClientConnection::new(
+        Arc::new(config),
+        "localhost".try_into().expect("server name"),
+    )
+    .expect("client connection");
+
+    let mut stream = StreamOwned::new(connection, TcpStream::connect(addr)?);
+    stream.write_all(frames)?;
+    stream.conn.send_close_notify();
+    stream.flush()
+}
+
+#[test]
+fn tls_receiver_client_subject() {
+    let ca = self_signed("test ca", true);
+    let server = signed_by("localhost", &ca);
+    let client = signed_by("client.example.com", &ca);
+    let mut roots = RootCertStore::empty();
+    roots
+        .add(ca.certificate.der().clone())
+        .expect("adding root");
+
+    let receiver = TlsReceiver::new(
+        TcpReceiver::bind("127.0.0.1:0").expect("binding receiver"),
+        vec![
+            server.certificate.der().clone(),
+            ca.certificate.der().clone(),
+        ],
+        server.key(),
+        ClientAuth::Roots(roots),
+    )
+    .expect("tls config")
+    .allow_subject("client.example.com");
+    let addr = receiver.local_addr().expect("receiver address");
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        receiver.run(move |peer, frame| {
+            let name = peer
+                .certificate
+                .as_ref()
+                .and_then(|certificate| certificate.common_name.clone());
+            let frame = frame.map(|frame| {
+                let message: Message = Message::parse(frame).expect("parsing message");
+                message.msg.msg.to_string()
+            });
+            tx.send((name, frame.map_err(|err| err.to_string())))
+                .expect("sending frame");
+        })
+    });
+
+    send(
+        addr,
+        &ca,
+        &client,
+        b"26 <13>1 - host app - - - one26 <13>1 - host app - - - two",
+    )
+    .expect("sending messages");
+
+    let name = Some("client.example.com".to_string());
+    assert_eq!(rx.recv(), Ok((name.clone(), Ok("one".to_string()))));
+    assert_eq!(rx.recv(), Ok((name, Ok("two".to_string()))));
+}
+
+#[test]
+fn tls_receiver_fingerprints() {
+    let server = self_signed("localhost", false);
+    let allowed = self_signed("allowed", false);
+    let other = self_signed("other", false);
+
+    let receiver = TlsReceiver::new(
+        TcpReceiver::bind("127.0.0.1:0").expect("binding receiver"),
+        server.chain(),
+        server.key(),
+        ClientAuth::SelfSigned,
+    )
+    .expect("tls config")
+    .allow_fingerprint(Sha256::digest(allowed.certificate.der()).into());
+    let addr = receiver.local_addr().expect("receiver address");
+
+    let sender = thread::spawn(move || {
+        let _ = send(addr, &server, &other, b"<13>1 - - - - - - denied\n");
+        send(addr, &server, &allowed, b"<13>1 - - - - - - allowed\n")
+    });
+
+    let denied = receiver.accept().map(|(_, peer)| peer);
+    assert_eq!(
+        denied.map_err(|err| err.kind()),
+        Err(io::ErrorKind::PermissionDenied)
+    );
+
+    let (mut frames, peer) = receiver.accept().expect("accepting");
+    assert_eq!(
+        peer.certificate.map(|certificate| certificate.subject),
+        Some("CN=allowed".to_string())
+    );
+    let msg = frames
+        .read_with(
+            |message: Result<Message<Option<&str>, Vec<StructuredData>, Raw>, _>| {
+                message.map(|message| message.msg.msg.to_string())
+            },
+        )
+        .expect("reading");
+    assert_eq!(msg, Some(Ok("allowed".to_string())));
+    sender
+        .join()
+        .expect("sender thread")
+        .expect("sending message");
+}
+
+fn self_signed_receiver(server: &Identity) -> TlsReceiver {
+    TlsReceiver::new(
+        TcpReceiver::bind("127.0.0.1:0").expect("binding receiver"),
+        server.chain(),
+        server.key(),
+        ClientAuth::SelfSigned,
+    )
+    .expect("tls config")
+}
+
+#[test]
+fn tls_receiver_self_signed_needs_fingerprints() {
+    // Nothing allowed, and subjects of unverified certificates don't count.
+    for allow_subject in [false, true] {
+        let server = self_signed("localhost", false);
+        let client = self_signed("client.example.com", false);
+        let mut receiver = self_signed_receiver(&server);
+        if allow_subject {
+            receiver = receiver.allow_subject("client.example.com");
+        }
+        let addr = receiver.local_addr().expect("receiver address");
+        let sender = thread::spawn(move || send(addr, &server, &client, b"<13>1 - - - - - - hi\n"));
+
+        let denied = receiver.accept().map(|(_, peer)| peer);
+        assert_eq!(
+            denied.map_err(|err| err.kind()),
+            Err(io::ErrorKind::PermissionDenied)
+        );
+        let _ = sender.join();
+    }
+}
+
+#[test]
+fn tls_receiver_handshake_timeout() {
+    let server = self_signed("localhost", false);
+    let receiver = self_signed_receiver(&server).handshake_timeout(Duration::from_millis(200));
+    let addr = receiver.local_addr().expect("receiver address");
+
+    // Connects and never says a word.
+    let _silent = TcpStream::connect(addr).expect("connecting");
+    let start = Instant::now();
+    let result = receiver.accept().map(|(_, peer)| peer);
+
+    assert!(result.is_err());
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
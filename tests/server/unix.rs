@@ -0,0 +1,88 @@
+use rsyslog::server::{UnixDatagramReceiver, UnixStreamReceiver};
+use std::{
+    io::Write,
+    os::unix::net::{UnixDatagram, UnixStream},
+    path::PathBuf,
+};
+
+fn socket_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("rsyslog-{}-{}.sock", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+#[test]
+fn unix_datagram_receiver_fills_header() {
+    let path = socket_path("dgram");
+    let mut receiver = UnixDatagramReceiver::bind(&path)
+        .expect("binding receiver")
+        .hostname("localbox");
+    let sender = UnixDatagram::unbound().expect("creating sender");
+    sender
+        .send_to(b"<14>Oct  9 22:33:20 myapp[7]: started\n", &path)
+        .expect("sending");
+    sender.send_to(b"<11>disk full", &path).expect("sending");
+
+    let first = receiver
+        .recv_with(|_, message| {
+            message.map(|message| (message.hostname, message.app_name, message.proc_id))
+        })
+        .expect("receiving");
+    assert_eq!(first, Ok((Some("localbox"), Some("myapp"), Some("7"))));
+
+    let (source, second) = receiver
+        .recv_with(|source, message| {
+            let message = message.expect("parsing message");
+            (
+                source,
+                (
+                    message.severity,
+                    message.app_name.map(str::to_string),
+                    message.proc_id.map(str::to_string),
+                    message.msg.msg.to_string(),
+                ),
+            )
+        })
+        .expect("receiving");
+    std::fs::remove_file(&path).expect("removing socket");
+
+    assert!(!source.truncated);
+    assert_eq!(second.0, 3);
+    assert_eq!(second.3, "disk full");
+    if cfg!(target_os = "linux") {
+        let credentials = source.credentials.expect("sender credentials");
+        let comm = std::fs::read_to_string("/proc/self/comm").expect("reading comm");
+        assert_eq!(credentials.pid, std::process::id());
+        assert_eq!(second.1.as_deref(), Some(comm.trim_end()));
+        assert_eq!(second.2, Some(std::process::id().to_string()));
+    }
+}
+
+#[test]
+fn unix_stream_receiver_nul_framing() {
+    let path = socket_path("stream");
+    let receiver = UnixStreamReceiver::bind(&path).expect("binding receiver");
+    let mut sender = UnixStream::connect(&path).expect("connecting");
+    sender
+        .write_all(b"<13>Oct  9 22:33:20 app[7]: one\0<13>Oct  9 22:33:21 app[7]: two\0")
+        .expect("writing");
+    drop(sender);
+
+    let mut connection = receiver.accept().expect("accepting");
+    std::fs::remove_file(&path).expect("removing socket");
+    if cfg!(target_os = "linux") {
+        assert_eq!(
+            connection.credentials().map(|credentials| credentials.pid),
+            Some(std::process::id())
+        );
+    }
+    let mut msgs = Vec::new();
+    while let Some(msg) = connection
+        .read_with(|message| message.map(|message| message.msg.msg.to_string()))
+        .expect("reading")
+    {
+        msgs.push(msg.expect("parsing message"));
+    }
+
+    assert_eq!(msgs, ["one", "two"]);
+}
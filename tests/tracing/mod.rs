@@ -0,0 +1,166 @@
+use crate::common::Buffer;
+use rsyslog::{
+    parser::{SdParam, StructuredData},
+    tracing::{Sink, SyslogLayer},
+    Message,
+};
+use tracing_subscriber::{layer::SubscriberExt, Registry};
+
+/// Runs `f` with a syslog layer writing to the returned lines.
+fn traced<F: FnOnce()>(layer: fn(SyslogLayer) -> SyslogLayer, f: F) -> Vec<String> {
+    let buffer = Buffer::default();
+    let layer = layer(SyslogLayer::new(Sink::writer(buffer.clone())).app_name("api"));
+    ::tracing::subscriber::with_default(Registry::default().with(layer), f);
+    buffer.lines()
+}
+
+#[test]
+fn tracing_event_round_trip() {
+    let lines = traced(
+        |layer| layer,
+        || {
+            let span = ::tracing::info_span!("request", request_id = 7, path = "/users");
+            let _entered = span.enter();
+            ::tracing::warn!(status = 503, retry = true, "upstream unavailable");
+        },
+    );
+
+    let message: Message = Message::parse(&lines[0]).expect("parsing message");
+    assert_eq!(message.facility, 1);
+    assert_eq!(message.severity, 4);
+    assert!(message.timestamp.is_some());
+    assert!(message.hostname.is_some());
+    assert_eq!(message.app_name, Some("api"));
+    assert_eq!(
+        message.proc_id,
+        Some(std::process::id().to_string().as_str())
+    );
+    assert_eq!(
+        message.structured_data,
+        vec![
+            StructuredData {
+                id: "event@32473",
+                params: vec![SdParam::from(("status", "503")), ("retry", "true").into(),]
+            },
+            StructuredData {
+                id: "span@32473",
+                params: vec![
+                    SdParam::from(("request_id", "7")),
+                    ("path", "/users").into(),
+                ]
+            },
+        ]
+    );
+    assert_eq!(message.msg.msg, "upstream unavailable");
+}
+
+#[test]
+fn tracing_escapes_fields() {
+    let lines = traced(
+        |layer| {
+            layer
+                .event_sd_id("ev@1")
+                .and_then(|layer| layer.span_sd_id("sp@1"))
+                .expect("valid SD-IDs")
+        },
+        || {
+            let span = ::tracing::debug_span!("job", name = ::tracing::field::Empty);
+            let _entered = span.enter();
+            span.record("name", r#"nightly "full" [backup]"#);
+            ::tracing::error!(path = r#"C:\data"#, "user name" = "x", "failed");
+            ::tracing::trace!(count = 2);
+        },
+    );
+
+    let messages = lines
+        .iter()
+        .map(|line| Message::parse(line).expect("parsing message"))
+        .collect::<Vec<Message>>();
+    assert_eq!(messages[0].severity, 3);
+    assert_eq!(
+        messages[0].structured_data,
+        vec![
+            StructuredData {
+                id: "ev@1",
+                params: vec![
+                    SdParam::from(("path", r#"C:\\data"#)),
+                    ("user_name", "x").into(),
+                ]
+            },
+            StructuredData {
+                id: "sp@1",
+                params: vec![SdParam::from(("name", r#"nightly \"full\" [backup\]"#))]
+            },
+        ]
+    );
+    assert_eq!(messages[0].msg.msg, "failed");
+
+    assert_eq!(messages[1].severity, 7);
+    assert_eq!(
+        messages[1].structured_data[0].params,
+        vec![("count", "2").into()]
+    );
+    assert_eq!(messages[1].msg.msg, "");
+}
+
+#[test]
+fn tracing_nested_spans() {
+    let lines = traced(
+        |layer| layer,
+        || {
+            let request = ::tracing::info_span!("request", request_id = 7, user = "anonymous");
+            let _request = request.enter();
+            let query = ::tracing::info_span!("query", table = "users", user = "alice");
+            let _query = query.enter();
+            ::tracing::info!("slow query");
+        },
+    );
+
+    let message: Message = Message::parse(&lines[0]).expect("parsing message");
+    assert_eq!(
+        message.structured_data,
+        vec![StructuredData {
+            id: "span@32473",
+            params: vec![
+                SdParam::from(("request_id", "7")),
+                ("user", "alice").into(),
+                ("table", "users").into(),
+            ]
+        }]
+    );
+}
+
+#[test]
+fn tracing_without_fields() {
+    let lines = traced(
+        |layer| layer.facility(16).hostname("web1"),
+        || {
+            ::tracing::info!("started");
+        },
+    );
+
+    let message: Message = Message::parse(&lines[0]).expect("parsing message");
+    assert_eq!(message.facility, 16);
+    assert_eq!(message.severity, 6);
+    assert_eq!(message.hostname, Some("web1"));
+    assert!(message.structured_data.is_empty());
+    assert_eq!(message.msg.msg, "started");
+}
+
+#[test]
+fn tracing_invalid_sd_ids() {
+    for id in [
+        "",
+        "my event",
+        "event@",
+        "event@x",
+        "a]b",
+        "e\"",
+        &"e".repeat(33),
+    ] {
+        let layer = SyslogLayer::new(Sink::Stderr);
+        assert!(layer.event_sd_id(id).is_err(), "{}", id);
+    }
+    let layer = SyslogLayer::new(Sink::Stderr);
+    assert!(layer.span_sd_id("origin").is_ok());
+}